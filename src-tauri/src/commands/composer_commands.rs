@@ -1,6 +1,6 @@
 
 use crate::{
-    user::composer::COMPOSITIONS,
+    user::composer::compositions,
     utils::classes::charting::{ChartingData, DataBlock},
 };
 use serde::{Deserialize, Serialize};
@@ -16,7 +16,7 @@ pub struct CompositionMetadata {
 pub fn get_compositions() -> Result<Vec<CompositionMetadata>, tauri::Error> {
     let mut composition_metadatas: Vec<CompositionMetadata> = vec![];
 
-    for composition in &*COMPOSITIONS {
+    for composition in compositions() {
         composition_metadatas.push(CompositionMetadata {
             id: composition.id().into(),
             name: composition.name().into(),
@@ -52,7 +52,7 @@ pub fn get_composition_data(
         data_blocks: vec![],
     };
 
-    let composition = (&*COMPOSITIONS)
+    let composition = compositions()
         .into_iter()
         .find(|composition| composition.id() == params.id)
         .unwrap();
@@ -1,12 +1,33 @@
+use std::collections::HashMap;
 use std::fs::File;
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
 use std::thread;
 
+use crate::library::engines::cross_validation::{
+    cross_validate, CombinatorialPurgedCV, PurgedKFold,
+};
+use crate::library::engines::optimizers::convergence::render_convergence;
+use crate::library::engines::optimizers::grid::OptimizationParameter;
+use crate::library::engines::optimizers::importance::{parameter_importances, render_importances};
+use crate::library::engines::optimizers::objective_function::{
+    ObjectiveDefinition, ObjectiveFunction, ObjectiveSelection,
+};
+use crate::library::engines::optimizers::overfitting::probability_of_backtest_overfitting;
+use crate::library::engines::optimizers::{restore_series, OptimizerKind};
+use crate::library::engines::optimizers::progress::{OptimizationProgress, OPTIMIZATION_MONITOR};
+use crate::library::engines::optimizers::robustness::{
+    rank_by_robustness, rank_by_score, score_surfaces,
+};
+use crate::library::engines::optimizers::study::STUDY_STORE;
 use crate::user::composer::CompositionDataType;
-use crate::user::strategies::{Metric, STRATEGIES};
+use crate::user::library::metrics::MetricRegistry;
+use crate::user::strategies::{strategies, BacktestResult, IStrategy, Metric, ParameterSchema};
 use crate::utils::classes::charting::{ChartingData, DataBlock};
 use crate::utils::paths::get_app_data_dir;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use tauri::Emitter;
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize)]
@@ -20,7 +41,7 @@ pub struct StrategyMetadata {
 pub async fn get_strategies() -> Result<Vec<StrategyMetadata>, String> {
     let mut strategies_metadatas: Vec<StrategyMetadata> = vec![];
 
-    for strategy_metadata in STRATEGIES.iter() {
+    for strategy_metadata in strategies() {
         strategies_metadatas.push(StrategyMetadata {
             id: strategy_metadata.id().into(),
             name: strategy_metadata.name().into(),
@@ -34,11 +55,22 @@ pub async fn get_strategies() -> Result<Vec<StrategyMetadata>, String> {
 #[derive(Serialize, Deserialize)]
 pub struct BacktestStrategyParams {
     pub id: String,
+    // Replaces the strategy's optimization target, e.g. `sharpe - 0.5 * max_drawdown`
+    pub objective: Option<ObjectiveDefinition>,
+    // Backtests this configuration alone instead of optimizing, parameters left out
+    // take their default
+    pub parameters: Option<Vec<OptimizationParameterPair>>,
+    // Replaces the ranges of the strategy's parameter schema for this optimization
+    pub optimization_ranges: Option<Vec<OptimizationParameter>>,
+    // Replaces the optimizer the strategy runs by default
+    pub optimizer: Option<OptimizerKind>,
+    // Ranks the optimization results by Pareto dominance on these objectives, two at least
+    pub objectives: Option<Vec<ObjectiveSelection>>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct MetricPair {
-    key: Metric,
+    key: String,
     value: f32,
 }
 
@@ -54,9 +86,22 @@ pub struct BacktestResultResponse {
     pub equity_growth_charting_data: Vec<ChartingData>,
     pub portfolio_growth_charting_data: Vec<ChartingData>,
     pub percentage_growth_charting_data: Vec<ChartingData>,
+    pub rolling_metrics_charting_data: Vec<ChartingData>,
     pub data_blocks: Vec<DataBlock>,
     pub metrics: Vec<MetricPair>,
     pub parameters: Vec<OptimizationParameterPair>,
+    pub probabilistic_sharpe_ratio: Option<f32>,
+    pub deflated_sharpe_ratio: Option<f32>,
+    pub objectives: Vec<ObjectivePair>,
+    pub pareto_rank: Option<usize>,
+    pub score: Option<f32>,
+    pub robustness_score: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ObjectivePair {
+    key: String,
+    value: Option<f32>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -64,40 +109,261 @@ pub struct BacktestStrategyResponse {
     pub name: Option<String>,
     pub description: Option<String>,
     pub backtests: Vec<BacktestResultResponse>,
+    // Ids of the non-dominated backtests of a multi-objective optimization
+    pub pareto_front: Vec<String>,
+    // Score surfaces of every pair of numeric parameters
+    pub robustness_heatmaps_charting_data: Vec<ChartingData>,
+    // Backtest ids from the best optimization target down, and from the most robust down
+    pub score_ranking: Vec<String>,
+    pub robustness_ranking: Vec<String>,
+    pub objective: Option<ObjectiveDefinition>,
+    // Best and mean score per optimizer iteration
+    pub convergence_charting_data: Vec<ChartingData>,
+    // Probability of backtest overfitting over every evaluated configuration
+    pub overfitting: Option<BacktestOverfittingResponse>,
+    // The optimization was stopped early, the backtests are the ones finished by then
+    pub cancelled: bool,
+    // Id `cancel_optimization` takes, `None` when nothing was optimized
+    pub run_id: Option<String>,
+    // Share of the score variance each parameter explains, most important first
+    pub parameter_importance: Vec<MetricPair>,
+    pub importance_charting_data: Vec<ChartingData>,
+    // Mean score along every numeric parameter
+    pub marginal_charting_data: Vec<ChartingData>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BacktestOverfittingResponse {
+    pub probability_of_overfitting: f32,
+    pub logits: Vec<f32>,
+    pub in_sample_performances: Vec<f32>,
+    pub out_of_sample_performances: Vec<f32>,
+    pub degradation_slope: Option<f32>,
+    pub degradation_intercept: Option<f32>,
+    pub probability_of_loss: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OptimizationProgressResponse {
+    pub run_id: String,
+    pub strategy_id: String,
+    pub progress: OptimizationProgress,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CancelOptimizationParams {
+    pub run_id: String,
+}
+
+// Starts a run whose optimizations report to the frontend as `optimization_progress`
+// events until `OPTIMIZATION_MONITOR.finish` is called, strategies take part in it
+// through `with_optimization_run`
+fn start_optimization_progress(app: &tauri::AppHandle, strategy: &Box<dyn IStrategy>) -> String {
+    let app_handle = app.clone();
+    let run_id = Uuid::new_v4().to_string();
+    let event_run_id = run_id.clone();
+    let strategy_id = strategy.id().to_string();
+
+    OPTIMIZATION_MONITOR.start(
+        &run_id,
+        Some(move |progress: OptimizationProgress| {
+            // A closed window only misses the progress, the optimization goes on
+            if let Err(error) = app_handle.emit(
+                "optimization_progress",
+                OptimizationProgressResponse {
+                    run_id: event_run_id.clone(),
+                    strategy_id: strategy_id.clone(),
+                    progress,
+                },
+            ) {
+                eprintln!("Couldn't report optimization progress: {}", error);
+            }
+        }),
+    );
+
+    run_id
 }
 
+// Optimizations run on the blocking pool, `cancel_optimization` and other commands are
+// served meanwhile
 #[tauri::command]
-pub fn backtest_strategy(
+pub async fn backtest_strategy(
+    app: tauri::AppHandle,
     params: BacktestStrategyParams,
 ) -> Result<BacktestStrategyResponse, tauri::Error> {
     let mut data_response = BacktestStrategyResponse {
         name: None,
         description: None,
         backtests: Vec::new(),
+        pareto_front: Vec::new(),
+        robustness_heatmaps_charting_data: Vec::new(),
+        score_ranking: Vec::new(),
+        robustness_ranking: Vec::new(),
+        objective: None,
+        convergence_charting_data: Vec::new(),
+        overfitting: None,
+        cancelled: false,
+        run_id: None,
+        parameter_importance: Vec::new(),
+        importance_charting_data: Vec::new(),
+        marginal_charting_data: Vec::new(),
     };
 
-    let strategy = (&*STRATEGIES)
-        .into_iter()
-        .find(|strategy| strategy.id() == params.id)
-        .unwrap();
+    let registered_strategy = registered_strategy(&params.id)?;
+    let strategy = &registered_strategy;
 
     data_response.name = Some(strategy.name().into());
     data_response.description = Some(strategy.description().into());
 
-    let optimization = strategy.optimize();
+    let objective_strategy = match &params.objective {
+        Some(objective_definition) => Some(
+            strategy
+                .with_objective_function(objective_function(strategy, objective_definition)?)
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::Unsupported,
+                        format!("{} does not support objective functions", strategy.name()),
+                    )
+                })?,
+        ),
+        None => None,
+    };
+
+    if objective_strategy.is_some() {
+        data_response.objective = params.objective.clone();
+    }
+
+    let schema = strategy.parameter_schema();
+
+    let parameters = match &params.parameters {
+        Some(parameter_pairs) => Some(parameter_map(strategy, &schema, parameter_pairs)?),
+        None => None,
+    };
+
+    let ranged_strategy = match &params.optimization_ranges {
+        Some(optimization_ranges) => {
+            for optimization_range in optimization_ranges {
+                schema_parameter(strategy, &schema, optimization_range.name())?;
+            }
+
+            Some(
+                objective_strategy
+                    .as_ref()
+                    .unwrap_or(strategy)
+                    .with_optimization_ranges(optimization_ranges.clone())
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::Unsupported,
+                            format!("{} has no optimization ranges to edit", strategy.name()),
+                        )
+                    })?,
+            )
+        }
+        None => None,
+    };
+
+    let configured_strategy = ranged_strategy
+        .as_ref()
+        .or(objective_strategy.as_ref())
+        .unwrap_or(strategy);
+
+    let optimizer_strategy = match params.optimizer {
+        Some(optimizer_kind) => Some(
+            configured_strategy
+                .with_optimizer_kind(optimizer_kind)
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::Unsupported,
+                        format!("{} can't change its optimizer", strategy.name()),
+                    )
+                })?,
+        ),
+        None => None,
+    };
+
+    let configured_strategy = optimizer_strategy.as_ref().unwrap_or(configured_strategy);
+
+    let objectives_strategy = match &params.objectives {
+        Some(objective_selections) => {
+            if objective_selections.len() < 2 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "A multi-objective optimization needs at least two objectives",
+                )
+                .into());
+            }
+
+            let metrics = reported_metrics(strategy)?;
+            let objectives = objective_selections
+                .iter()
+                .map(|objective_selection| objective_selection.objective(&metrics))
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            Some(
+                configured_strategy
+                    .with_optimization_objectives(objectives)
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::Unsupported,
+                            format!("{} can't be optimized on several objectives", strategy.name()),
+                        )
+                    })?,
+            )
+        }
+        None => None,
+    };
+
+    let configured_strategy = objectives_strategy.as_ref().unwrap_or(configured_strategy);
+
+    // A chosen configuration is backtested as is, without optimizing around it
+    let optimization = if parameters.is_some() {
+        None
+    } else {
+        let run_id = start_optimization_progress(&app, strategy);
+        // Only strategies without overrides lack a run, those are never configured
+        let run_strategy = configured_strategy.with_optimization_run(run_id.clone());
+        let unconfigured_strategy = registered_strategy.clone();
+
+        let optimization = tauri::async_runtime::spawn_blocking(move || {
+            let optimized_strategy: &Box<dyn IStrategy> = match &run_strategy {
+                Some(run_strategy) => run_strategy,
+                None => &unconfigured_strategy,
+            };
+
+            let mut optimization = optimized_strategy.optimize();
+
+            // Results served from the study have no series to chart or run PBO on
+            if let Some(optimization) = optimization.as_mut() {
+                restore_series(optimized_strategy, optimization);
+            }
+
+            optimization
+        })
+        .await;
+
+        data_response.cancelled = OPTIMIZATION_MONITOR
+            .finish(&run_id)
+            .map_or(false, |progress| progress.cancelled);
+        data_response.run_id = Some(run_id);
+
+        optimization?
+    };
 
     if optimization.is_none() {
-        let backtest_result = strategy.backtest(None).unwrap();
+        let backtest_result = configured_strategy
+            .backtest(parameters.as_ref())
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error.to_string()))?;
 
         let portfolio_growth_charting_data =
             strategy.render_portfolio_percentage_growth(&backtest_result);
         let percentage_growth_charting_data = strategy.render_percentage_growth(&backtest_result);
         let equity_growth_charting_data = strategy.render_equity_growth(&backtest_result);
+        let rolling_metrics_charting_data = strategy.render_rolling_metrics(&backtest_result);
         let mut metrics = Vec::new();
 
         for (key, value) in backtest_result.metrics() {
             metrics.push(MetricPair {
-                key: key.clone(),
+                key: key.key(),
                 value: value.to_owned(),
             });
         }
@@ -107,23 +373,70 @@ pub fn backtest_strategy(
             equity_growth_charting_data: equity_growth_charting_data,
             portfolio_growth_charting_data: portfolio_growth_charting_data,
             percentage_growth_charting_data: percentage_growth_charting_data,
+            rolling_metrics_charting_data: rolling_metrics_charting_data,
             data_blocks: vec![],
             metrics: metrics,
-            parameters: Vec::new(),
+            parameters: configured_strategy
+                .parameters_with_defaults(parameters.as_ref())
+                .into_iter()
+                .map(|(key, value)| OptimizationParameterPair { key, value })
+                .collect(),
+            probabilistic_sharpe_ratio: None,
+            deflated_sharpe_ratio: None,
+            objectives: Vec::new(),
+            pareto_rank: None,
+            score: parameters
+                .as_ref()
+                .map(|_| configured_strategy.score(&backtest_result)),
+            robustness_score: None,
         })
     } else {
-        for optimized_backtest_result in optimization.unwrap() {
+        let optimization = optimization.unwrap();
+        let mut backtest_ids: Vec<String> = vec![];
+
+        data_response.robustness_heatmaps_charting_data = score_surfaces(&optimization);
+        data_response.convergence_charting_data = render_convergence(&optimization);
+
+        let importances = parameter_importances(&optimization);
+        (
+            data_response.importance_charting_data,
+            data_response.marginal_charting_data,
+        ) = render_importances(&importances);
+
+        for parameter_importance in &importances {
+            data_response.parameter_importance.push(MetricPair {
+                key: parameter_importance.name.clone(),
+                value: parameter_importance.importance,
+            });
+        }
+
+        data_response.overfitting =
+            probability_of_backtest_overfitting(&optimization, 16).map(|report| {
+                BacktestOverfittingResponse {
+                    probability_of_overfitting: report.probability_of_overfitting,
+                    logits: report.logits,
+                    in_sample_performances: report.in_sample_performances,
+                    out_of_sample_performances: report.out_of_sample_performances,
+                    degradation_slope: report.degradation_slope,
+                    degradation_intercept: report.degradation_intercept,
+                    probability_of_loss: report.probability_of_loss,
+                }
+            });
+
+        for optimized_backtest_result in &optimization {
             let portfolio_growth_charting_data = strategy
                 .render_portfolio_percentage_growth(&optimized_backtest_result.backtest_result);
             let percentage_growth_charting_data =
                 strategy.render_percentage_growth(&optimized_backtest_result.backtest_result);
             let equity_growth_charting_data =
                 strategy.render_equity_growth(&optimized_backtest_result.backtest_result);
+            let rolling_metrics_charting_data =
+                strategy.render_rolling_metrics(&optimized_backtest_result.backtest_result);
             let mut metrics = Vec::new();
 
             for (key, value) in optimized_backtest_result.backtest_result.metrics() {
                 metrics.push(MetricPair {
-                    key: key.clone(),
+                    key: key.key(),
                     value: value.to_owned(),
                 });
             }
@@ -137,27 +450,61 @@ pub fn backtest_strategy(
                 })
             }
 
+            let mut objectives: Vec<ObjectivePair> = vec![];
+
+            for (key, value) in &optimized_backtest_result.objective_values {
+                objectives.push(ObjectivePair {
+                    key: key.to_owned(),
+                    value: value.to_owned(),
+                })
+            }
+
+            let id = Uuid::new_v4().to_string();
+
+            if optimized_backtest_result.pareto_rank == Some(0) {
+                data_response.pareto_front.push(id.clone());
+            }
+
+            backtest_ids.push(id.clone());
+
             data_response.backtests.push(BacktestResultResponse {
-                id: Uuid::new_v4().to_string(),
+                id: id,
                 equity_growth_charting_data: equity_growth_charting_data,
                 portfolio_growth_charting_data: portfolio_growth_charting_data,
                 percentage_growth_charting_data: percentage_growth_charting_data,
+                rolling_metrics_charting_data: rolling_metrics_charting_data,
                 data_blocks: vec![],
                 metrics: metrics,
                 parameters: optimized_parameters_pairs,
+                probabilistic_sharpe_ratio: optimized_backtest_result.probabilistic_sharpe_ratio,
+                deflated_sharpe_ratio: optimized_backtest_result.deflated_sharpe_ratio,
+                objectives: objectives,
+                pareto_rank: optimized_backtest_result.pareto_rank,
+                score: Some(optimized_backtest_result.score),
+                robustness_score: optimized_backtest_result.robustness_score,
             })
         }
+
+        data_response.score_ranking = rank_by_score(&optimization)
+            .into_iter()
+            .map(|index| backtest_ids[index].clone())
+            .collect();
+        data_response.robustness_ranking = rank_by_robustness(&optimization)
+            .into_iter()
+            .map(|index| backtest_ids[index].clone())
+            .collect();
     }
 
     let data_response_clone = data_response.clone();
+    let saved_strategy = registered_strategy.clone();
 
     thread::spawn(move || {
         let app_data_dir = get_app_data_dir().unwrap();
         let base_download_path = app_data_dir.join("saves");
         let save_id = format!(
             "{}_{}_{}",
-            strategy.name(),
-            strategy.composition().name(),
+            saved_strategy.name(),
+            saved_strategy.composition().name(),
             Utc::now().to_string().replace(":", "_")
         );
         
@@ -171,6 +518,369 @@ pub fn backtest_strategy(
     Ok(data_response)
 }
 
+// Strategies are reloaded from disk, an id the frontend holds may no longer exist
+fn registered_strategy(id: &str) -> Result<Arc<Box<dyn IStrategy>>, Error> {
+    strategies()
+        .into_iter()
+        .find(|strategy| strategy.id() == id)
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("Unknown strategy {}", id)))
+}
+
+// Objective variables must name a metric the strategy reports
+fn objective_function(
+    strategy: &Box<dyn IStrategy>,
+    objective_definition: &ObjectiveDefinition,
+) -> Result<ObjectiveFunction, Error> {
+    ObjectiveFunction::parse(objective_definition.clone(), &reported_metrics(strategy)?)
+}
+
+// A default backtest shows the metrics the strategy registers and the custom ones it adds
+fn reported_metrics(strategy: &Box<dyn IStrategy>) -> Result<Vec<Metric>, Error> {
+    let backtest_result = strategy
+        .backtest(None)
+        .map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
+    let mut metrics = MetricRegistry::default().reported_metrics();
+
+    for metric in backtest_result.metrics().keys() {
+        if !metrics.contains(metric) {
+            metrics.push(metric.clone());
+        }
+    }
+
+    Ok(metrics)
+}
+
+fn schema_parameter<'a>(
+    strategy: &Box<dyn IStrategy>,
+    schema: &'a [ParameterSchema],
+    name: &str,
+) -> Result<&'a ParameterSchema, Error> {
+    schema
+        .iter()
+        .find(|parameter| parameter.range.name() == name)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} has no parameter {}", strategy.name(), name),
+            )
+        })
+}
+
+// Explicit values must be declared in the schema and share the type of their default
+fn parameter_map(
+    strategy: &Box<dyn IStrategy>,
+    schema: &[ParameterSchema],
+    parameter_pairs: &[OptimizationParameterPair],
+) -> Result<HashMap<String, CompositionDataType>, Error> {
+    let mut parameters: HashMap<String, CompositionDataType> = HashMap::new();
+
+    for parameter_pair in parameter_pairs {
+        let parameter = schema_parameter(strategy, schema, &parameter_pair.key)?;
+
+        if std::mem::discriminant(&parameter.default)
+            != std::mem::discriminant(&parameter_pair.value)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Parameter {} expects a value like {:?}",
+                    parameter_pair.key, parameter.default
+                ),
+            ));
+        }
+
+        parameters.insert(parameter_pair.key.clone(), parameter_pair.value.clone());
+    }
+
+    Ok(parameters)
+}
+
+#[tauri::command]
+pub fn get_strategy_parameters(
+    params: BacktestStrategyParams,
+) -> Result<Vec<ParameterSchema>, tauri::Error> {
+    let registered_strategy = registered_strategy(&params.id)?;
+    let strategy = &registered_strategy;
+
+    Ok(strategy.parameter_schema())
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StudyRecordResponse {
+    pub parameters: Vec<OptimizationParameterPair>,
+    pub score: Option<f32>,
+    pub metrics: Vec<MetricPair>,
+}
+
+#[tauri::command]
+pub fn get_optimization_study(
+    params: BacktestStrategyParams,
+) -> Result<Vec<StudyRecordResponse>, tauri::Error> {
+    let registered_strategy = registered_strategy(&params.id)?;
+    let strategy = &registered_strategy;
+
+    let mut study_records: Vec<StudyRecordResponse> = vec![];
+
+    for study_record in STUDY_STORE.records(strategy) {
+        let mut metrics = Vec::new();
+
+        for (key, value) in &study_record.metrics {
+            metrics.push(MetricPair {
+                key: key.key(),
+                value: value.to_owned(),
+            });
+        }
+
+        let mut parameters: Vec<OptimizationParameterPair> = vec![];
+
+        for (key, value) in study_record.parameters {
+            parameters.push(OptimizationParameterPair { key, value })
+        }
+
+        study_records.push(StudyRecordResponse {
+            parameters: parameters,
+            score: study_record.score,
+            metrics: metrics,
+        });
+    }
+
+    Ok(study_records)
+}
+
+// Saved backtests go stale once the strategy's code changes, clearing forces a fresh study
+#[tauri::command]
+pub fn clear_optimization_study(params: BacktestStrategyParams) -> Result<(), tauri::Error> {
+    let registered_strategy = registered_strategy(&params.id)?;
+    let strategy = &registered_strategy;
+
+    STUDY_STORE.clear(strategy)?;
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CrossValidateStrategyParams {
+    pub id: String,
+    pub objective: Option<ObjectiveDefinition>,
+    // Folds for purged k-fold, groups for combinatorial purged CV
+    pub folds: usize,
+    // Groups tested per split, `None` for plain purged k-fold
+    pub test_groups: Option<usize>,
+    pub purge: usize,
+    pub embargo: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RowRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CrossValidationFoldResponse {
+    pub train: Vec<RowRange>,
+    pub test: Vec<RowRange>,
+    pub parameters: Vec<OptimizationParameterPair>,
+    // One per train range
+    pub in_sample: Vec<BacktestResultResponse>,
+    pub out_of_sample: Vec<BacktestResultResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CrossValidationPathResponse {
+    pub total_return: f32,
+    pub sharpe_ratio: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CrossValidationResponse {
+    pub folds: Vec<CrossValidationFoldResponse>,
+    pub paths: Vec<CrossValidationPathResponse>,
+    pub in_sample_mean_score: Option<f32>,
+    pub out_of_sample_mean_score: Option<f32>,
+    pub out_of_sample_score_deviation: f32,
+    pub score_efficiency: Option<f32>,
+    pub out_of_sample_mean_metrics: Vec<MetricPair>,
+    // Folds the cancelled optimization didn't reach are missing
+    pub cancelled: bool,
+    pub run_id: String,
+}
+
+#[tauri::command]
+pub async fn cross_validate_strategy(
+    app: tauri::AppHandle,
+    params: CrossValidateStrategyParams,
+) -> Result<CrossValidationResponse, tauri::Error> {
+    let registered_strategy = registered_strategy(&params.id)?;
+    let strategy = &registered_strategy;
+
+    let objective_strategy = match &params.objective {
+        Some(objective_definition) => Some(
+            strategy
+                .with_objective_function(objective_function(strategy, objective_definition)?)
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::Unsupported,
+                        format!("{} does not support objective functions", strategy.name()),
+                    )
+                })?,
+        ),
+        None => None,
+    };
+    let strategy = objective_strategy.as_ref().unwrap_or(strategy);
+
+    let rows = strategy.composed_data().len();
+    let splits = match params.test_groups {
+        Some(test_groups) => {
+            CombinatorialPurgedCV::new(params.folds, test_groups, params.purge, params.embargo)
+                .splits(rows)?
+        }
+        None => PurgedKFold::new(params.folds, params.purge, params.embargo).splits(rows)?,
+    };
+
+    let run_id = start_optimization_progress(&app, strategy);
+    let run_strategy = strategy
+        .with_optimization_run(run_id.clone())
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::Unsupported,
+                format!("{} cannot be backtested on partial data", strategy.name()),
+            )
+        });
+
+    let report = match run_strategy {
+        Ok(run_strategy) => {
+            tauri::async_runtime::spawn_blocking(move || cross_validate(&run_strategy, splits))
+                .await
+        }
+        Err(error) => Ok(Err(error)),
+    };
+    let cancelled = OPTIMIZATION_MONITOR
+        .finish(&run_id)
+        .map_or(false, |progress| progress.cancelled);
+    let report = report??;
+    let mut folds: Vec<CrossValidationFoldResponse> = vec![];
+
+    for fold in &report.folds {
+        let mut parameters: Vec<OptimizationParameterPair> = vec![];
+
+        for (key, value) in &fold.optimized_parameters {
+            parameters.push(OptimizationParameterPair {
+                key: key.to_owned(),
+                value: value.to_owned(),
+            })
+        }
+
+        folds.push(CrossValidationFoldResponse {
+            train: fold
+                .split
+                .train
+                .iter()
+                .map(|range| RowRange {
+                    start: range.start,
+                    end: range.end,
+                })
+                .collect(),
+            test: fold
+                .split
+                .test
+                .iter()
+                .map(|range| RowRange {
+                    start: range.start,
+                    end: range.end,
+                })
+                .collect(),
+            parameters: parameters,
+            in_sample: fold
+                .in_sample
+                .iter()
+                .zip(fold.in_sample_scores.iter())
+                .map(|(backtest_result, score)| {
+                    backtest_result_response(strategy, backtest_result, Some(*score))
+                })
+                .collect(),
+            out_of_sample: fold
+                .out_of_sample
+                .iter()
+                .zip(fold.out_of_sample_scores.iter())
+                .map(|(backtest_result, score)| {
+                    backtest_result_response(strategy, backtest_result, Some(*score))
+                })
+                .collect(),
+        });
+    }
+
+    let mut out_of_sample_mean_metrics = Vec::new();
+
+    for (key, value) in &report.statistics.out_of_sample_mean_metrics {
+        out_of_sample_mean_metrics.push(MetricPair {
+            key: key.key(),
+            value: value.to_owned(),
+        });
+    }
+
+    Ok(CrossValidationResponse {
+        folds: folds,
+        paths: report
+            .paths
+            .iter()
+            .map(|path| CrossValidationPathResponse {
+                total_return: path.total_return,
+                sharpe_ratio: path.sharpe_ratio,
+            })
+            .collect(),
+        in_sample_mean_score: Some(report.statistics.in_sample_mean_score)
+            .filter(|score| !score.is_nan()),
+        out_of_sample_mean_score: Some(report.statistics.out_of_sample_mean_score)
+            .filter(|score| !score.is_nan()),
+        out_of_sample_score_deviation: report.statistics.out_of_sample_score_deviation,
+        score_efficiency: report.statistics.score_efficiency,
+        out_of_sample_mean_metrics: out_of_sample_mean_metrics,
+        cancelled: cancelled,
+        run_id: run_id,
+    })
+}
+
+fn backtest_result_response(
+    strategy: &Box<dyn IStrategy>,
+    backtest_result: &BacktestResult,
+    score: Option<f32>,
+) -> BacktestResultResponse {
+    let mut metrics = Vec::new();
+
+    for (key, value) in backtest_result.metrics() {
+        metrics.push(MetricPair {
+            key: key.key(),
+            value: value.to_owned(),
+        });
+    }
+
+    BacktestResultResponse {
+        id: Uuid::new_v4().to_string(),
+        equity_growth_charting_data: strategy.render_equity_growth(backtest_result),
+        portfolio_growth_charting_data: strategy
+            .render_portfolio_percentage_growth(backtest_result),
+        percentage_growth_charting_data: strategy.render_percentage_growth(backtest_result),
+        rolling_metrics_charting_data: strategy.render_rolling_metrics(backtest_result),
+        data_blocks: vec![],
+        metrics: metrics,
+        parameters: Vec::new(),
+        probabilistic_sharpe_ratio: None,
+        deflated_sharpe_ratio: None,
+        objectives: Vec::new(),
+        pareto_rank: None,
+        score: score,
+        robustness_score: None,
+    }
+}
+
+// Stops the optimization run, false when it isn't running
+#[tauri::command]
+pub fn cancel_optimization(params: CancelOptimizationParams) -> Result<bool, tauri::Error> {
+    Ok(OPTIMIZATION_MONITOR.cancel(&params.run_id))
+}
+
 // #[tauri::command]
 // pub async fn optimize_strategy() -> Result<_, _> {
 //     Ok(())
@@ -38,6 +38,12 @@ pub fn run() {
                 "raw/news",
                 "saves",
                 "stories",
+                "studies",
+                "strategies",
+                "plugins",
+                "scripts",
+                "scripts/strategies",
+                "scripts/compositions",
             ];
 
             for directory in directories {
@@ -45,6 +51,8 @@ pub fn run() {
                 std::fs::create_dir_all(&full_path).unwrap();
             }
 
+            user::scripting::watch_scripts();
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -59,7 +67,12 @@ pub fn run() {
             get_compositions,
             get_composition_data,
             get_strategies,
-            backtest_strategy
+            backtest_strategy,
+            get_optimization_study,
+            clear_optimization_study,
+            cross_validate_strategy,
+            cancel_optimization,
+            get_strategy_parameters
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,512 @@
+use crate::{
+    library::engines::optimizers::{grid::OptimizedBacktestResult, study::parameter_hash},
+    user::{
+        composer::CompositionDataType,
+        strategies::{BacktestResult, IStrategy, Metric},
+    },
+};
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+    ops::Range,
+};
+
+// Rows a strategy is optimized on and the rows its best parameters are then backtested
+// on, every test range is backtested on its own
+#[derive(Clone, Debug)]
+pub struct CrossValidationSplit {
+    pub train: Vec<Range<usize>>,
+    pub test: Vec<Range<usize>>,
+    // Index of the group of every test range, for combinatorial paths
+    pub test_groups: Vec<usize>,
+}
+
+// Contiguous folds, each one tested once while the strategy is optimized on the others.
+// `purge` rows before a test range are dropped from training since their trades can
+// reach into it, `embargo` rows after it since indicators there are still warmed by it
+pub struct PurgedKFold {
+    pub folds: usize,
+    pub purge: usize,
+    pub embargo: usize,
+}
+
+impl PurgedKFold {
+    pub fn new(folds: usize, purge: usize, embargo: usize) -> Self {
+        return Self {
+            folds,
+            purge,
+            embargo,
+        };
+    }
+
+    pub fn splits(&self, rows: usize) -> Result<Vec<CrossValidationSplit>, Error> {
+        validate_groups("folds", self.folds, rows)?;
+
+        let groups = group_ranges(rows, self.folds);
+
+        Ok((0..groups.len())
+            .map(|fold| split(&groups, &[fold], rows, self.purge, self.embargo))
+            .collect())
+    }
+}
+
+// Combinatorial purged cross-validation: the rows are cut in `groups` and every choice of
+// `test_groups` of them is tested, which yields several complete out-of-sample paths
+pub struct CombinatorialPurgedCV {
+    pub groups: usize,
+    pub test_groups: usize,
+    pub purge: usize,
+    pub embargo: usize,
+}
+
+impl CombinatorialPurgedCV {
+    pub fn new(groups: usize, test_groups: usize, purge: usize, embargo: usize) -> Self {
+        return Self {
+            groups,
+            test_groups,
+            purge,
+            embargo,
+        };
+    }
+
+    pub fn splits(&self, rows: usize) -> Result<Vec<CrossValidationSplit>, Error> {
+        validate_groups("groups", self.groups, rows)?;
+
+        // Testing every group at once would leave nothing to train on
+        if self.test_groups < 1 || self.test_groups >= self.groups {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Test groups must be between 1 and {}, got {}",
+                    self.groups - 1,
+                    self.test_groups
+                ),
+            ));
+        }
+
+        let groups = group_ranges(rows, self.groups);
+
+        Ok(combinations(groups.len(), self.test_groups)
+            .into_iter()
+            .map(|test_groups| split(&groups, &test_groups, rows, self.purge, self.embargo))
+            .collect())
+    }
+}
+
+/// Backtest paths covering every group once: path `p` takes each group from the `p`-th
+/// split testing it. Each path lists `(split index, test range index)` in group order.
+pub fn backtest_paths(splits: &[CrossValidationSplit]) -> Vec<Vec<(usize, usize)>> {
+    let mut group_tests: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+
+    for (split_index, split) in splits.iter().enumerate() {
+        for (test_index, &group) in split.test_groups.iter().enumerate() {
+            group_tests
+                .entry(group)
+                .or_default()
+                .push((split_index, test_index));
+        }
+    }
+
+    let mut groups: Vec<usize> = group_tests.keys().copied().collect();
+    groups.sort();
+
+    let path_count = group_tests
+        .values()
+        .map(|tests| tests.len())
+        .min()
+        .unwrap_or(0);
+
+    (0..path_count)
+        .map(|path| {
+            groups
+                .iter()
+                .map(|group| group_tests[group][path])
+                .collect()
+        })
+        .collect()
+}
+
+pub struct FoldResult {
+    pub split: CrossValidationSplit,
+    pub optimized_parameters: HashMap<String, CompositionDataType>,
+    // One backtest of the best parameters per train range of the split
+    pub in_sample: Vec<BacktestResult>,
+    pub in_sample_scores: Vec<f32>,
+    // Train range scores weighted by their number of rows
+    pub in_sample_score: f32,
+    // One backtest per test range of the split
+    pub out_of_sample: Vec<BacktestResult>,
+    pub out_of_sample_scores: Vec<f32>,
+}
+
+pub struct CrossValidationPath {
+    pub tests: Vec<(usize, usize)>,
+    // Per-row returns of the path's tests chained in time order
+    pub total_return: f32,
+    pub sharpe_ratio: Option<f32>,
+}
+
+pub struct CrossValidationStatistics {
+    pub in_sample_mean_score: f32,
+    pub out_of_sample_mean_score: f32,
+    pub out_of_sample_score_deviation: f32,
+    // Out-of-sample over in-sample mean score, below 1 when optimization overfits
+    pub score_efficiency: Option<f32>,
+    pub out_of_sample_mean_metrics: HashMap<Metric, f32>,
+}
+
+pub struct CrossValidationReport {
+    pub folds: Vec<FoldResult>,
+    pub paths: Vec<CrossValidationPath>,
+    pub statistics: CrossValidationStatistics,
+}
+
+/// Optimizes the strategy on the training rows of every split with its own optimizer,
+/// then backtests the best parameters on each test range.
+pub fn cross_validate(
+    strategy: &Box<dyn IStrategy>,
+    splits: Vec<CrossValidationSplit>,
+) -> Result<CrossValidationReport, Error> {
+    let composed_data = strategy.composed_data();
+    let mut folds: Vec<FoldResult> = vec![];
+
+    for split in splits {
+        let train_strategies = split
+            .train
+            .iter()
+            .map(|range| slice_strategy(strategy, &composed_data, range.clone()))
+            .collect::<Result<Vec<Box<dyn IStrategy>>, Error>>()?;
+
+        // Train ranges are optimized one by one, joined together the indicators and
+        // open trades would run across the purged rows between them
+        let mut candidates: Vec<HashMap<String, CompositionDataType>> = vec![];
+
+        for train_strategy in &train_strategies {
+            let optimization = train_strategy.optimize().ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{} is not optimizable", strategy.name()),
+                )
+            })?;
+
+            if let Some(best) = best_result(optimization) {
+                let hash = parameter_hash(&best.optimized_parameters);
+
+                if !candidates
+                    .iter()
+                    .any(|candidate| parameter_hash(candidate) == hash)
+                {
+                    candidates.push(best.optimized_parameters);
+                }
+            }
+        }
+
+        // The parameters kept are those scoring best over every train range
+        let mut best: Option<(
+            HashMap<String, CompositionDataType>,
+            Vec<BacktestResult>,
+            f32,
+        )> = None;
+
+        for candidate in candidates {
+            let mut in_sample: Vec<BacktestResult> = vec![];
+
+            for train_strategy in &train_strategies {
+                let backtest_result = train_strategy
+                    .backtest(Some(&candidate))
+                    .map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
+                in_sample.push(backtest_result);
+            }
+
+            let score = weighted_score(strategy, &split.train, &in_sample);
+            let is_better = match &best {
+                Some((_, _, best_score)) => score_order(score)
+                    .total_cmp(&score_order(*best_score))
+                    .is_gt(),
+                None => true,
+            };
+
+            if is_better {
+                best = Some((candidate, in_sample, score));
+            }
+        }
+
+        let (optimized_parameters, in_sample, in_sample_score) = match best {
+            Some(best) => best,
+            None => continue,
+        };
+
+        let mut out_of_sample: Vec<BacktestResult> = vec![];
+
+        for test_range in &split.test {
+            let test_strategy = slice_strategy(strategy, &composed_data, test_range.clone())?;
+
+            let backtest_result = test_strategy
+                .backtest(Some(&optimized_parameters))
+                .map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
+            out_of_sample.push(backtest_result);
+        }
+
+        folds.push(FoldResult {
+            split,
+            optimized_parameters,
+            in_sample_scores: in_sample
+                .iter()
+                .map(|backtest_result| strategy.score(backtest_result))
+                .collect(),
+            in_sample,
+            in_sample_score,
+            out_of_sample_scores: out_of_sample
+                .iter()
+                .map(|backtest_result| strategy.score(backtest_result))
+                .collect(),
+            out_of_sample,
+        });
+    }
+
+    let splits: Vec<CrossValidationSplit> = folds.iter().map(|fold| fold.split.clone()).collect();
+
+    let paths = backtest_paths(&splits)
+        .into_iter()
+        .map(|tests| {
+            let returns: Vec<f32> = tests
+                .iter()
+                .flat_map(|&(fold, test)| folds[fold].out_of_sample[test].equity_returns())
+                .collect();
+
+            CrossValidationPath {
+                total_return: returns.iter().fold(1.0, |total, r| total * (1.0 + r)) - 1.0,
+                sharpe_ratio: sharpe_ratio(&returns),
+                tests,
+            }
+        })
+        .collect();
+
+    let statistics = statistics(&folds);
+
+    Ok(CrossValidationReport {
+        folds,
+        paths,
+        statistics,
+    })
+}
+
+fn statistics(folds: &[FoldResult]) -> CrossValidationStatistics {
+    let in_sample_scores: Vec<f32> = folds
+        .iter()
+        .map(|fold| fold.in_sample_score)
+        .filter(|score| !score.is_nan())
+        .collect();
+    let out_of_sample_scores: Vec<f32> = folds
+        .iter()
+        .flat_map(|fold| fold.out_of_sample_scores.iter().copied())
+        .filter(|score| !score.is_nan())
+        .collect();
+
+    let in_sample_mean_score = mean(&in_sample_scores);
+    let out_of_sample_mean_score = mean(&out_of_sample_scores);
+
+    let mut metrics_values: HashMap<Metric, Vec<f32>> = HashMap::new();
+
+    for backtest_result in folds.iter().flat_map(|fold| fold.out_of_sample.iter()) {
+        for (metric, value) in backtest_result.metrics() {
+            if !value.is_nan() {
+                metrics_values
+                    .entry(metric.clone())
+                    .or_default()
+                    .push(*value);
+            }
+        }
+    }
+
+    CrossValidationStatistics {
+        in_sample_mean_score,
+        out_of_sample_mean_score,
+        out_of_sample_score_deviation: deviation(&out_of_sample_scores),
+        score_efficiency: if in_sample_mean_score.is_finite() && in_sample_mean_score != 0.0 {
+            Some(out_of_sample_mean_score / in_sample_mean_score)
+        } else {
+            None
+        },
+        out_of_sample_mean_metrics: metrics_values
+            .into_iter()
+            .map(|(metric, values)| (metric, mean(&values)))
+            .collect(),
+    }
+}
+
+// Strategy backtesting a single contiguous range, never rows stitched across a gap
+fn slice_strategy(
+    strategy: &Box<dyn IStrategy>,
+    composed_data: &[Vec<CompositionDataType>],
+    range: Range<usize>,
+) -> Result<Box<dyn IStrategy>, Error> {
+    strategy
+        .with_composed_data(composed_data[range].to_vec())
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::Unsupported,
+                format!("{} cannot be backtested on partial data", strategy.name()),
+            )
+        })
+}
+
+fn best_result(optimization: Vec<OptimizedBacktestResult>) -> Option<OptimizedBacktestResult> {
+    optimization
+        .into_iter()
+        .max_by(|a, b| score_order(a.score).total_cmp(&score_order(b.score)))
+}
+
+// NaN is the worst score
+fn score_order(score: f32) -> f32 {
+    if score.is_nan() {
+        f32::NEG_INFINITY
+    } else {
+        score
+    }
+}
+
+// Mean of the scores weighted by the rows of their range, NaN if any range is
+fn weighted_score(
+    strategy: &Box<dyn IStrategy>,
+    ranges: &[Range<usize>],
+    backtest_results: &[BacktestResult],
+) -> f32 {
+    let rows: usize = ranges.iter().map(|range| range.len()).sum();
+
+    if rows == 0 {
+        return f32::NAN;
+    }
+
+    ranges
+        .iter()
+        .zip(backtest_results)
+        .map(|(range, backtest_result)| strategy.score(backtest_result) * range.len() as f32)
+        .sum::<f32>()
+        / rows as f32
+}
+
+fn validate_groups(label: &str, groups: usize, rows: usize) -> Result<(), Error> {
+    if groups < 2 || groups > rows {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Cross-validation needs between 2 and {} {}, got {}",
+                rows, label, groups
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+// `groups` contiguous ranges of near equal length covering `0..rows`
+fn group_ranges(rows: usize, groups: usize) -> Vec<Range<usize>> {
+    (0..groups)
+        .map(|group| (group * rows / groups)..((group + 1) * rows / groups))
+        .collect()
+}
+
+fn split(
+    groups: &[Range<usize>],
+    test_groups: &[usize],
+    rows: usize,
+    purge: usize,
+    embargo: usize,
+) -> CrossValidationSplit {
+    let test: Vec<Range<usize>> = test_groups
+        .iter()
+        .map(|&group| groups[group].clone())
+        .collect();
+
+    // Rows excluded from training: the test ranges widened by purge and embargo
+    let mut excluded: Vec<Range<usize>> = test
+        .iter()
+        .map(|range| range.start.saturating_sub(purge)..(range.end + embargo).min(rows))
+        .collect();
+    excluded.sort_by_key(|range| range.start);
+
+    let mut train: Vec<Range<usize>> = vec![];
+    let mut cursor = 0;
+
+    for range in excluded {
+        if range.start > cursor {
+            train.push(cursor..range.start);
+        }
+
+        cursor = cursor.max(range.end);
+    }
+
+    if cursor < rows {
+        train.push(cursor..rows);
+    }
+
+    CrossValidationSplit {
+        train,
+        test,
+        test_groups: test_groups.to_vec(),
+    }
+}
+
+/// Every `k` sized subset of `0..n` in lexicographic order.
+pub fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut subsets: Vec<Vec<usize>> = vec![];
+    let mut current: Vec<usize> = (0..k).collect();
+
+    if k == 0 || k > n {
+        return subsets;
+    }
+
+    loop {
+        subsets.push(current.clone());
+
+        let mut index = k;
+        while index > 0 && current[index - 1] == n - k + index - 1 {
+            index -= 1;
+        }
+
+        if index == 0 {
+            return subsets;
+        }
+
+        current[index - 1] += 1;
+        for next in index..k {
+            current[next] = current[next - 1] + 1;
+        }
+    }
+}
+
+fn mean(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        return f32::NAN;
+    }
+
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+fn deviation(values: &[f32]) -> f32 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+
+    let mean = mean(values);
+
+    (values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f32>()
+        / (values.len() - 1) as f32)
+        .sqrt()
+}
+
+fn sharpe_ratio(returns: &[f32]) -> Option<f32> {
+    let deviation = deviation(returns);
+
+    if returns.len() < 2 || deviation == 0.0 {
+        return None;
+    }
+
+    Some(mean(returns) / deviation)
+}
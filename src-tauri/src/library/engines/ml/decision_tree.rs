@@ -0,0 +1,182 @@
+use crate::utils::random::SeededRng;
+
+#[derive(Clone, Debug)]
+enum Node {
+    Split {
+        feature: usize,
+        threshold: f32,
+        left: usize,
+        right: usize,
+    },
+    Leaf {
+        value: f32,
+    },
+}
+
+// Regression tree splitting where the squared error drops the most, the base learner of
+// random forests and gradient boosting
+#[derive(Clone, Debug)]
+pub struct DecisionTree {
+    max_depth: usize,
+    min_samples_leaf: usize,
+    // Features drawn as split candidates at every node, all of them when `None`
+    max_features: Option<usize>,
+    nodes: Vec<Node>,
+}
+
+impl DecisionTree {
+    pub fn new(max_depth: usize, min_samples_leaf: usize, max_features: Option<usize>) -> Self {
+        return Self {
+            max_depth,
+            min_samples_leaf: min_samples_leaf.max(1),
+            max_features,
+            nodes: Vec::new(),
+        };
+    }
+
+    /// Fits the tree on the given rows of `features`, rows can repeat for bootstraps.
+    pub fn fit(
+        &mut self,
+        features: &[Vec<f32>],
+        targets: &[f32],
+        rows: &[usize],
+        rng: &mut SeededRng,
+    ) {
+        self.nodes.clear();
+        self.grow(features, targets, rows.to_vec(), 0, rng);
+    }
+
+    pub fn predict(&self, features: &[f32]) -> f32 {
+        match self.nodes[self.leaf(features)] {
+            Node::Leaf { value } => value,
+            Node::Split { .. } => unreachable!(),
+        }
+    }
+
+    /// Index of the leaf the row falls in.
+    pub fn leaf(&self, features: &[f32]) -> usize {
+        let mut index = 0;
+
+        loop {
+            match self.nodes[index] {
+                Node::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                } => {
+                    index = if features[feature] <= threshold {
+                        left
+                    } else {
+                        right
+                    };
+                }
+                Node::Leaf { .. } => return index,
+            }
+        }
+    }
+
+    pub fn set_leaf_value(&mut self, leaf: usize, value: f32) {
+        if let Node::Leaf { value: leaf_value } = &mut self.nodes[leaf] {
+            *leaf_value = value;
+        }
+    }
+
+    fn grow(
+        &mut self,
+        features: &[Vec<f32>],
+        targets: &[f32],
+        rows: Vec<usize>,
+        depth: usize,
+        rng: &mut SeededRng,
+    ) -> usize {
+        let mean = rows.iter().map(|&row| targets[row]).sum::<f32>() / rows.len().max(1) as f32;
+
+        let index = self.nodes.len();
+        self.nodes.push(Node::Leaf { value: mean });
+
+        if depth >= self.max_depth || rows.len() < 2 * self.min_samples_leaf {
+            return index;
+        }
+
+        let (feature, threshold) = match self.best_split(features, targets, &rows, rng) {
+            Some(split) => split,
+            None => return index,
+        };
+
+        let (left_rows, right_rows): (Vec<usize>, Vec<usize>) = rows
+            .iter()
+            .partition(|&&row| features[row][feature] <= threshold);
+
+        let left = self.grow(features, targets, left_rows, depth + 1, rng);
+        let right = self.grow(features, targets, right_rows, depth + 1, rng);
+
+        self.nodes[index] = Node::Split {
+            feature,
+            threshold,
+            left,
+            right,
+        };
+
+        index
+    }
+
+    // Minimizing the squared error of both sides is maximizing the sum of their
+    // `sum² / count`, which a sweep over the sorted rows computes with running sums
+    fn best_split(
+        &self,
+        features: &[Vec<f32>],
+        targets: &[f32],
+        rows: &[usize],
+        rng: &mut SeededRng,
+    ) -> Option<(usize, f32)> {
+        let mut candidates: Vec<usize> = (0..features[rows[0]].len()).collect();
+
+        if let Some(max_features) = self.max_features {
+            rng.shuffle(&mut candidates);
+            candidates.truncate(max_features.max(1));
+        }
+
+        let total_sum: f32 = rows.iter().map(|&row| targets[row]).sum();
+        let mut best_score = total_sum * total_sum / rows.len() as f32;
+        let mut best_split: Option<(usize, f32)> = None;
+
+        let mut sorted_rows = rows.to_vec();
+
+        for feature in candidates {
+            sorted_rows.sort_by(|&a, &b| features[a][feature].total_cmp(&features[b][feature]));
+
+            let mut left_sum = 0.0;
+
+            for position in 0..sorted_rows.len() - 1 {
+                left_sum += targets[sorted_rows[position]];
+
+                let left_count = position + 1;
+                let right_count = sorted_rows.len() - left_count;
+
+                if left_count < self.min_samples_leaf || right_count < self.min_samples_leaf {
+                    continue;
+                }
+
+                let value = features[sorted_rows[position]][feature];
+                let next_value = features[sorted_rows[position + 1]][feature];
+
+                // Rows with the same value can't be told apart
+                if value == next_value {
+                    continue;
+                }
+
+                let right_sum = total_sum - left_sum;
+                let score = left_sum * left_sum / left_count as f32
+                    + right_sum * right_sum / right_count as f32;
+
+                if score > best_score + 1e-6 {
+                    best_score = score;
+                    best_split = Some((feature, (value + next_value) / 2.0));
+                }
+            }
+        }
+
+        best_split
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{
+    library::engines::optimizers::space::numeric_value,
+    user::composer::{CompositionDataType, IComposition},
+};
+use std::{collections::VecDeque, error::Error};
+
+#[derive(Clone, Debug)]
+pub enum Feature {
+    // Value of a composition field as is
+    Field(String),
+    // Composition field relative to the price, `field / price - 1`, for fields on the
+    // price's scale like bands or moving averages
+    RelativeField(String),
+    // Return of the price over the last `lag` rows
+    Return(usize),
+    // Standard deviation of the one row returns of the last `window` rows
+    Volatility(usize),
+}
+
+// Features built for every row of a composition from that row and the ones before it
+#[derive(Clone, Debug)]
+pub struct FeatureSet {
+    pub price_field: String,
+    pub features: Vec<Feature>,
+}
+
+impl FeatureSet {
+    pub fn new(price_field: &str, features: Vec<Feature>) -> Self {
+        return Self {
+            price_field: price_field.into(),
+            features,
+        };
+    }
+
+    /// Prices the features and labels are computed from.
+    pub fn prices(
+        &self,
+        composition: &dyn IComposition,
+        composition_data: &[Vec<CompositionDataType>],
+    ) -> Result<Vec<f32>, Box<dyn Error>> {
+        let price_position = field_position(composition, &self.price_field)?;
+
+        composition_data
+            .iter()
+            .map(|composition_point| {
+                numeric_value(&composition_point[price_position])
+                    .ok_or_else(|| format!("Missing {} price", self.price_field).into())
+            })
+            .collect()
+    }
+
+    /// A row of features for every composition row, `None` while a feature is missing or
+    /// still needs earlier rows.
+    pub fn build(
+        &self,
+        composition: &dyn IComposition,
+        composition_data: &[Vec<CompositionDataType>],
+    ) -> Result<Vec<Option<Vec<f32>>>, Box<dyn Error>> {
+        let prices = self.prices(composition, composition_data)?;
+
+        let returns: Vec<f32> = (0..prices.len())
+            .map(|row| {
+                if row == 0 || prices[row - 1] == 0.0 {
+                    0.0
+                } else {
+                    prices[row] / prices[row - 1] - 1.0
+                }
+            })
+            .collect();
+
+        let columns = self
+            .features
+            .iter()
+            .map(|feature| -> Result<Vec<Option<f32>>, Box<dyn Error>> {
+                let column = match feature {
+                    Feature::Field(field) => {
+                        let position = field_position(composition, field)?;
+
+                        composition_data
+                            .iter()
+                            .map(|composition_point| numeric_value(&composition_point[position]))
+                            .collect()
+                    }
+                    Feature::RelativeField(field) => {
+                        let position = field_position(composition, field)?;
+
+                        composition_data
+                            .iter()
+                            .zip(&prices)
+                            .map(|(composition_point, price)| {
+                                numeric_value(&composition_point[position])
+                                    .filter(|_| *price != 0.0)
+                                    .map(|value| value / price - 1.0)
+                            })
+                            .collect()
+                    }
+                    Feature::Return(lag) => (0..prices.len())
+                        .map(|row| {
+                            if row < *lag || prices[row - lag] == 0.0 {
+                                None
+                            } else {
+                                Some(prices[row] / prices[row - lag] - 1.0)
+                            }
+                        })
+                        .collect(),
+                    Feature::Volatility(window) => {
+                        let mut window_returns: VecDeque<f32> = VecDeque::with_capacity(*window);
+
+                        returns
+                            .iter()
+                            .enumerate()
+                            .map(|(row, row_return)| {
+                                if row == 0 {
+                                    return None;
+                                }
+
+                                window_returns.push_back(*row_return);
+                                if window_returns.len() > *window {
+                                    window_returns.pop_front();
+                                }
+
+                                if window_returns.len() < *window.max(&2) {
+                                    return None;
+                                }
+
+                                let mean = window_returns.iter().sum::<f32>()
+                                    / window_returns.len() as f32;
+                                let variance = window_returns
+                                    .iter()
+                                    .map(|window_return| (window_return - mean).powi(2))
+                                    .sum::<f32>()
+                                    / (window_returns.len() - 1) as f32;
+
+                                Some(variance.sqrt())
+                            })
+                            .collect()
+                    }
+                };
+
+                Ok(column)
+            })
+            .collect::<Result<Vec<Vec<Option<f32>>>, Box<dyn Error>>>()?;
+
+        let rows = (0..composition_data.len())
+            .map(|row| columns.iter().map(|column| column[row]).collect())
+            .collect();
+
+        Ok(rows)
+    }
+}
+
+fn field_position(composition: &dyn IComposition, field: &str) -> Result<usize, Box<dyn Error>> {
+    composition
+        .composition_fields()
+        .get(field)
+        .copied()
+        .ok_or_else(|| format!("{} has no field {}", composition.name(), field).into())
+}
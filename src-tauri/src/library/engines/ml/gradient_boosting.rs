@@ -0,0 +1,106 @@
+use super::{decision_tree::DecisionTree, sigmoid, IModel};
+use crate::utils::random::SeededRng;
+use std::collections::HashMap;
+
+// Gradient boosted trees on the log loss: every tree fits the residuals of the ones
+// before it and its leaves take a Newton step
+#[derive(Clone, Debug)]
+pub struct GradientBoosting {
+    rounds: usize,
+    learning_rate: f32,
+    max_depth: usize,
+    min_samples_leaf: usize,
+    base_score: f32,
+    trees: Vec<DecisionTree>,
+}
+
+impl GradientBoosting {
+    pub fn new(
+        rounds: usize,
+        learning_rate: f32,
+        max_depth: usize,
+        min_samples_leaf: usize,
+    ) -> Self {
+        return Self {
+            rounds,
+            learning_rate,
+            max_depth,
+            min_samples_leaf,
+            base_score: 0.0,
+            trees: Vec::new(),
+        };
+    }
+
+    fn raw_score(&self, features: &[f32]) -> f32 {
+        self.base_score
+            + self
+                .trees
+                .iter()
+                .map(|tree| self.learning_rate * tree.predict(features))
+                .sum::<f32>()
+    }
+}
+
+impl Default for GradientBoosting {
+    fn default() -> Self {
+        return Self::new(30, 0.1, 3, 20);
+    }
+}
+
+impl IModel for GradientBoosting {
+    fn fit(&mut self, features: &[Vec<f32>], labels: &[f32]) {
+        self.trees.clear();
+
+        if features.is_empty() {
+            return;
+        }
+
+        let positive_ratio =
+            (labels.iter().sum::<f32>() / labels.len() as f32).clamp(1e-3, 1.0 - 1e-3);
+        self.base_score = (positive_ratio / (1.0 - positive_ratio)).ln();
+
+        let rows: Vec<usize> = (0..features.len()).collect();
+        let mut scores = vec![self.base_score; features.len()];
+        // Trees consider every feature, the generator is never drawn from
+        let mut rng = SeededRng::new(0);
+
+        for _ in 0..self.rounds {
+            let probabilities: Vec<f32> = scores.iter().map(|score| sigmoid(*score)).collect();
+            let residuals: Vec<f32> = labels
+                .iter()
+                .zip(&probabilities)
+                .map(|(label, probability)| label - probability)
+                .collect();
+
+            let mut tree = DecisionTree::new(self.max_depth, self.min_samples_leaf, None);
+            tree.fit(features, &residuals, &rows, &mut rng);
+
+            // Newton step per leaf, the sum of gradients over the sum of hessians
+            let mut leaf_sums: HashMap<usize, (f32, f32)> = HashMap::new();
+            let leaves: Vec<usize> = features.iter().map(|row| tree.leaf(row)).collect();
+
+            for (row, &leaf) in leaves.iter().enumerate() {
+                let probability = probabilities[row];
+                let leaf_sum = leaf_sums.entry(leaf).or_insert((0.0, 0.0));
+
+                leaf_sum.0 += residuals[row];
+                leaf_sum.1 += probability * (1.0 - probability);
+            }
+
+            for (&leaf, &(gradient_sum, hessian_sum)) in &leaf_sums {
+                tree.set_leaf_value(leaf, gradient_sum / (hessian_sum + 1e-6));
+            }
+
+            for (score, &leaf) in scores.iter_mut().zip(&leaves) {
+                let (gradient_sum, hessian_sum) = leaf_sums[&leaf];
+                *score += self.learning_rate * gradient_sum / (hessian_sum + 1e-6);
+            }
+
+            self.trees.push(tree);
+        }
+    }
+
+    fn predict(&self, features: &[f32]) -> f32 {
+        sigmoid(self.raw_score(features))
+    }
+}
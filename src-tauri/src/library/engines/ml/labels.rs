@@ -0,0 +1,117 @@
+use std::error::Error;
+
+// Label of a row and the row it is known at, a model deciding at a row can only have
+// trained on labels ending at or before it
+#[derive(Clone, Copy, Debug)]
+pub struct Label {
+    pub value: f32,
+    pub end: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Labeling {
+    // 1.0 when the price is higher `horizon` rows later
+    ForwardReturn {
+        horizon: usize,
+    },
+    // 1.0 when the price rises by `profit_taking` before it falls by `stop_loss` within
+    // `horizon` rows. When neither barrier is touched the return at the horizon decides
+    TripleBarrier {
+        horizon: usize,
+        profit_taking: f32,
+        stop_loss: f32,
+    },
+}
+
+impl Labeling {
+    pub const CATEGORIES: [&'static str; 2] = ["forward_return", "triple_barrier"];
+
+    /// Labeling of the given category, barriers are symmetric.
+    pub fn from_category(
+        category: &str,
+        horizon: usize,
+        barrier: f32,
+    ) -> Result<Self, Box<dyn Error>> {
+        let horizon = horizon.max(1);
+
+        match category {
+            "forward_return" => Ok(Self::ForwardReturn { horizon }),
+            "triple_barrier" => Ok(Self::TripleBarrier {
+                horizon,
+                profit_taking: barrier,
+                stop_loss: barrier,
+            }),
+            _ => Err(format!("Unknown labeling {}", category).into()),
+        }
+    }
+
+    /// A label for every row of `prices`, `None` for the rows whose horizon runs past
+    /// the end.
+    pub fn labels(&self, prices: &[f32]) -> Vec<Option<Label>> {
+        match *self {
+            Self::ForwardReturn { horizon } => forward_return_labels(prices, horizon),
+            Self::TripleBarrier {
+                horizon,
+                profit_taking,
+                stop_loss,
+            } => triple_barrier_labels(prices, horizon, profit_taking, stop_loss),
+        }
+    }
+}
+
+pub fn forward_return_labels(prices: &[f32], horizon: usize) -> Vec<Option<Label>> {
+    (0..prices.len())
+        .map(|row| {
+            let end = row + horizon;
+
+            if end >= prices.len() {
+                return None;
+            }
+
+            Some(Label {
+                value: if prices[end] > prices[row] { 1.0 } else { 0.0 },
+                end,
+            })
+        })
+        .collect()
+}
+
+pub fn triple_barrier_labels(
+    prices: &[f32],
+    horizon: usize,
+    profit_taking: f32,
+    stop_loss: f32,
+) -> Vec<Option<Label>> {
+    (0..prices.len())
+        .map(|row| {
+            let vertical_barrier = row + horizon;
+
+            if vertical_barrier >= prices.len() {
+                return None;
+            }
+
+            let open_price = prices[row];
+            let upper_barrier = open_price * (1.0 + profit_taking);
+            let lower_barrier = open_price * (1.0 - stop_loss);
+
+            for end in row + 1..=vertical_barrier {
+                if prices[end] >= upper_barrier {
+                    return Some(Label { value: 1.0, end });
+                }
+
+                if prices[end] <= lower_barrier {
+                    return Some(Label { value: 0.0, end });
+                }
+            }
+
+            Some(Label {
+                value: if prices[vertical_barrier] > open_price {
+                    1.0
+                } else {
+                    0.0
+                },
+                end: vertical_barrier,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,117 @@
+use super::{sigmoid, IModel};
+
+// Logistic regression fitted by full batch gradient descent on standardized features,
+// with an L2 penalty on the weights
+#[derive(Clone, Debug)]
+pub struct LogisticRegression {
+    learning_rate: f32,
+    epochs: usize,
+    l2: f32,
+    weights: Vec<f32>,
+    bias: f32,
+    means: Vec<f32>,
+    deviations: Vec<f32>,
+}
+
+impl LogisticRegression {
+    pub fn new(learning_rate: f32, epochs: usize, l2: f32) -> Self {
+        return Self {
+            learning_rate,
+            epochs,
+            l2,
+            weights: Vec::new(),
+            bias: 0.0,
+            means: Vec::new(),
+            deviations: Vec::new(),
+        };
+    }
+
+    fn standardize(&self, features: &[f32]) -> Vec<f32> {
+        features
+            .iter()
+            .zip(self.means.iter().zip(&self.deviations))
+            .map(|(value, (mean, deviation))| (value - mean) / deviation)
+            .collect()
+    }
+
+    fn linear(&self, standardized: &[f32]) -> f32 {
+        self.bias
+            + self
+                .weights
+                .iter()
+                .zip(standardized)
+                .map(|(weight, value)| weight * value)
+                .sum::<f32>()
+    }
+}
+
+impl Default for LogisticRegression {
+    fn default() -> Self {
+        return Self::new(0.1, 200, 1e-3);
+    }
+}
+
+impl IModel for LogisticRegression {
+    fn fit(&mut self, features: &[Vec<f32>], labels: &[f32]) {
+        if features.is_empty() {
+            return;
+        }
+
+        let feature_count = features[0].len();
+        let row_count = features.len() as f32;
+
+        self.means = (0..feature_count)
+            .map(|feature| features.iter().map(|row| row[feature]).sum::<f32>() / row_count)
+            .collect();
+        self.deviations = (0..feature_count)
+            .map(|feature| {
+                let mean = self.means[feature];
+                let variance = features
+                    .iter()
+                    .map(|row| (row[feature] - mean).powi(2))
+                    .sum::<f32>()
+                    / row_count;
+
+                // Constant features are left centered at zero
+                if variance > 0.0 {
+                    variance.sqrt()
+                } else {
+                    1.0
+                }
+            })
+            .collect();
+
+        let standardized: Vec<Vec<f32>> =
+            features.iter().map(|row| self.standardize(row)).collect();
+
+        self.weights = vec![0.0; feature_count];
+        self.bias = 0.0;
+
+        for _ in 0..self.epochs {
+            let mut weight_gradients = vec![0.0; feature_count];
+            let mut bias_gradient = 0.0;
+
+            for (row, label) in standardized.iter().zip(labels) {
+                let error = sigmoid(self.linear(row)) - label;
+
+                for (weight_gradient, value) in weight_gradients.iter_mut().zip(row) {
+                    *weight_gradient += error * value;
+                }
+                bias_gradient += error;
+            }
+
+            for (weight, weight_gradient) in self.weights.iter_mut().zip(&weight_gradients) {
+                *weight -= self.learning_rate * (weight_gradient / row_count + self.l2 * *weight);
+            }
+            self.bias -= self.learning_rate * bias_gradient / row_count;
+        }
+    }
+
+    fn predict(&self, features: &[f32]) -> f32 {
+        if self.weights.is_empty() {
+            return 0.5;
+        }
+
+        sigmoid(self.linear(&self.standardize(features)))
+    }
+}
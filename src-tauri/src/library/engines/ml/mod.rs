@@ -0,0 +1,55 @@
+pub mod decision_tree;
+pub mod features;
+pub mod gradient_boosting;
+pub mod labels;
+pub mod logistic_regression;
+pub mod pipeline;
+pub mod random_forest;
+pub mod walk_forward;
+
+use gradient_boosting::GradientBoosting;
+use logistic_regression::LogisticRegression;
+use random_forest::RandomForest;
+use std::error::Error;
+
+// Binary classifier over feature rows, labels are 1.0 when the price goes up and 0.0
+// when it doesn't
+pub trait IModel: Send + Sync {
+    fn fit(&mut self, features: &[Vec<f32>], labels: &[f32]);
+    // Probability the row's label is 1.0
+    fn predict(&self, features: &[f32]) -> f32;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModelKind {
+    LogisticRegression,
+    GradientBoosting,
+    RandomForest,
+}
+
+impl ModelKind {
+    pub const CATEGORIES: [&'static str; 3] =
+        ["logistic_regression", "gradient_boosting", "random_forest"];
+
+    pub fn from_category(category: &str) -> Result<Self, Box<dyn Error>> {
+        match category {
+            "logistic_regression" => Ok(Self::LogisticRegression),
+            "gradient_boosting" => Ok(Self::GradientBoosting),
+            "random_forest" => Ok(Self::RandomForest),
+            _ => Err(format!("Unknown model {}", category).into()),
+        }
+    }
+
+    /// Untrained model with the default hyperparameters of its kind.
+    pub fn create(&self, seed: u64) -> Box<dyn IModel> {
+        match self {
+            Self::LogisticRegression => Box::new(LogisticRegression::default()),
+            Self::GradientBoosting => Box::new(GradientBoosting::default()),
+            Self::RandomForest => Box::new(RandomForest::new(50, 6, 10, seed)),
+        }
+    }
+}
+
+pub fn sigmoid(value: f32) -> f32 {
+    1.0 / (1.0 + (-value).exp())
+}
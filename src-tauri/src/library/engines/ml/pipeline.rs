@@ -0,0 +1,37 @@
+use super::{features::FeatureSet, labels::Labeling, walk_forward::WalkForward, ModelKind};
+use crate::user::composer::{CompositionDataType, IComposition};
+use std::error::Error;
+
+// Features and labels built from a composition, a model of `model` kind retrained along
+// them and its out-of-sample predictions turned into a signal
+#[derive(Clone, Debug)]
+pub struct MlPipeline {
+    pub feature_set: FeatureSet,
+    pub labeling: Labeling,
+    pub model: ModelKind,
+    pub walk_forward: WalkForward,
+    pub seed: u64,
+}
+
+impl MlPipeline {
+    /// Signal in -1..1 for every row, `2p - 1` of the predicted probability the price
+    /// goes up. `None` until the first model is trained.
+    pub fn signals(
+        &self,
+        composition: &dyn IComposition,
+        composition_data: &[Vec<CompositionDataType>],
+    ) -> Result<Vec<Option<f32>>, Box<dyn Error>> {
+        let features = self.feature_set.build(composition, composition_data)?;
+        let prices = self.feature_set.prices(composition, composition_data)?;
+        let labels = self.labeling.labels(&prices);
+
+        let predictions = self
+            .walk_forward
+            .predict(&features, &labels, || self.model.create(self.seed));
+
+        Ok(predictions
+            .into_iter()
+            .map(|prediction| prediction.map(|probability| 2.0 * probability - 1.0))
+            .collect())
+    }
+}
@@ -0,0 +1,66 @@
+use super::{decision_tree::DecisionTree, IModel};
+use crate::utils::random::SeededRng;
+
+// Trees fitted on bootstrap samples of the rows, each split drawing from the square root
+// of the features. Leaves hold the share of up labels, so the forest's mean is a
+// probability
+#[derive(Clone, Debug)]
+pub struct RandomForest {
+    tree_count: usize,
+    max_depth: usize,
+    min_samples_leaf: usize,
+    seed: u64,
+    trees: Vec<DecisionTree>,
+}
+
+impl RandomForest {
+    pub fn new(tree_count: usize, max_depth: usize, min_samples_leaf: usize, seed: u64) -> Self {
+        return Self {
+            tree_count,
+            max_depth,
+            min_samples_leaf,
+            seed,
+            trees: Vec::new(),
+        };
+    }
+}
+
+impl IModel for RandomForest {
+    fn fit(&mut self, features: &[Vec<f32>], labels: &[f32]) {
+        self.trees.clear();
+
+        if features.is_empty() {
+            return;
+        }
+
+        let mut rng = SeededRng::new(self.seed);
+        let max_features = (features[0].len() as f32).sqrt().ceil() as usize;
+
+        for _ in 0..self.tree_count {
+            let rows: Vec<usize> = (0..features.len())
+                .map(|_| rng.range_usize(0, features.len()))
+                .collect();
+
+            let mut tree =
+                DecisionTree::new(self.max_depth, self.min_samples_leaf, Some(max_features));
+            tree.fit(features, labels, &rows, &mut rng);
+
+            self.trees.push(tree);
+        }
+    }
+
+    fn predict(&self, features: &[f32]) -> f32 {
+        if self.trees.is_empty() {
+            return 0.5;
+        }
+
+        let probability = self
+            .trees
+            .iter()
+            .map(|tree| tree.predict(features))
+            .sum::<f32>()
+            / self.trees.len() as f32;
+
+        probability.clamp(0.0, 1.0)
+    }
+}
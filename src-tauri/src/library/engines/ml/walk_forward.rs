@@ -0,0 +1,81 @@
+use super::{labels::Label, IModel};
+use rayon::prelude::*;
+
+// Retrains a model every `retrain_every` rows on the last `train_window` rows whose
+// labels are already known, and predicts the rows up to the next retraining with it
+#[derive(Clone, Copy, Debug)]
+pub struct WalkForward {
+    pub train_window: usize,
+    pub retrain_every: usize,
+    // Fewer labeled rows than this in the window leaves its rows without predictions
+    pub min_train_rows: usize,
+}
+
+impl WalkForward {
+    pub fn new(train_window: usize, retrain_every: usize, min_train_rows: usize) -> Self {
+        return Self {
+            train_window,
+            retrain_every: retrain_every.max(1),
+            min_train_rows: min_train_rows.max(1),
+        };
+    }
+
+    /// Out-of-sample probability of an up label for every row, `None` before the first
+    /// model is trained and for rows without features.
+    pub fn predict(
+        &self,
+        features: &[Option<Vec<f32>>],
+        labels: &[Option<Label>],
+        create_model: impl Fn() -> Box<dyn IModel> + Sync,
+    ) -> Vec<Option<f32>> {
+        let retrain_rows: Vec<usize> = (self.min_train_rows..features.len())
+            .step_by(self.retrain_every)
+            .collect();
+
+        // Windows are trained independently of each other
+        let blocks: Vec<(usize, Vec<Option<f32>>)> = retrain_rows
+            .into_par_iter()
+            .map(|retrain_row| {
+                let block_end = (retrain_row + self.retrain_every).min(features.len());
+
+                let (train_features, train_labels): (Vec<Vec<f32>>, Vec<f32>) =
+                    (retrain_row.saturating_sub(self.train_window)..retrain_row)
+                        .filter_map(|row| match (&features[row], labels[row]) {
+                            (Some(row_features), Some(label)) if label.end <= retrain_row => {
+                                Some((row_features.clone(), label.value))
+                            }
+                            _ => None,
+                        })
+                        .unzip();
+
+                if train_labels.len() < self.min_train_rows {
+                    return (retrain_row, vec![None; block_end - retrain_row]);
+                }
+
+                let mut model = create_model();
+                model.fit(&train_features, &train_labels);
+
+                let predictions = features[retrain_row..block_end]
+                    .iter()
+                    .map(|row_features| {
+                        row_features
+                            .as_ref()
+                            .map(|row_features| model.predict(row_features))
+                    })
+                    .collect();
+
+                (retrain_row, predictions)
+            })
+            .collect();
+
+        let mut predictions: Vec<Option<f32>> = vec![None; features.len()];
+
+        for (retrain_row, block) in blocks {
+            for (offset, prediction) in block.into_iter().enumerate() {
+                predictions[retrain_row + offset] = prediction;
+            }
+        }
+
+        predictions
+    }
+}
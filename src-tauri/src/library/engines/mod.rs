@@ -1,3 +1,5 @@
 pub struct OptimizationStrategy {}
 
+pub mod cross_validation;
+pub mod ml;
 pub mod optimizers;
\ No newline at end of file
@@ -1,21 +1,399 @@
 use crate::{
     library::engines::optimizers::{
+        evaluate_combination, finalize_results,
         grid::{OptimizationParameter, OptimizedBacktestResult},
+        progress::OPTIMIZATION_MONITOR,
+        space::OptimizationSpace,
+        study::data_fingerprint,
         Optimizer,
     },
-    user::strategies::{IStrategy},
+    user::strategies::IStrategy,
+    utils::{
+        formulas::{
+            linear_algebra::{cholesky, cholesky_solve, forward_substitution},
+            statistics::distribution::{normal_cdf, normal_pdf},
+        },
+        random::SeededRng,
+    },
 };
-use std::io::Error;
+use rayon::prelude::*;
+use std::{collections::HashSet, io::Error};
+
+const LENGTH_SCALES: [f64; 5] = [0.05, 0.1, 0.2, 0.4, 0.8];
+const OBSERVATION_NOISE: f64 = 1e-4;
+
+#[derive(Clone, Copy, Debug)]
+pub enum AcquisitionFunction {
+    // Expected gain over the best score so far, `xi` trades exploitation for exploration
+    ExpectedImprovement { xi: f32 },
+    // Optimistic bound `mean + kappa * deviation` of the surrogate
+    UpperConfidenceBound { kappa: f32 },
+}
 
-pub struct BeysianGridOptimizer {}
+pub struct BeysianGridOptimizer {
+    pub budget: usize,
+    pub initial_samples: usize,
+    pub batch_size: usize,
+    pub candidates: usize,
+    pub acquisition: AcquisitionFunction,
+    pub seed: u64,
+}
+
+impl BeysianGridOptimizer {
+    pub fn new(budget: usize, seed: u64) -> Self {
+        return Self {
+            budget,
+            initial_samples: (budget / 4).max(4),
+            batch_size: rayon::current_num_threads().clamp(1, 8),
+            candidates: 512,
+            acquisition: AcquisitionFunction::ExpectedImprovement { xi: 0.01 },
+            seed,
+        };
+    }
+}
 
 impl Optimizer for BeysianGridOptimizer {
     fn optimize(
+        &self,
         strategy: &Box<dyn IStrategy>,
-        hyperparameters: &[OptimizationParameter],
+        space: &OptimizationSpace,
     ) -> Result<Vec<OptimizedBacktestResult>, Error> {
-        let backtest_results: Vec<OptimizedBacktestResult> = vec![];
+        space.validate()?;
+
+        let mut backtest_results: Vec<OptimizedBacktestResult> = vec![];
+
+        if space.is_empty() {
+            return Ok(backtest_results);
+        }
+
+        let data_fingerprint = data_fingerprint(strategy);
+
+        let budget = self.budget.min(space.total_combinations());
+
+        OPTIMIZATION_MONITOR.plan_evaluations(strategy, budget);
+
+        let mut rng = SeededRng::new(self.seed);
+        let mut seen: HashSet<Vec<usize>> = HashSet::new();
+        let mut observations: Vec<(Vec<f64>, f64)> = vec![];
+
+        let mut batch =
+            Self::random_batch(space, &mut seen, &mut rng, self.initial_samples.min(budget));
+
+        while !batch.is_empty() && !OPTIMIZATION_MONITOR.is_cancelled(strategy) {
+            let evaluations: Vec<(Vec<usize>, Option<OptimizedBacktestResult>)> = batch
+                .into_par_iter()
+                .map(|point| {
+                    let evaluation = space.resolve(&mut point.clone()).and_then(|combination| {
+                        evaluate_combination(strategy, data_fingerprint, combination)
+                    });
+
+                    (point, evaluation)
+                })
+                .collect();
+
+            for (point, evaluation) in evaluations {
+                if let Some(optimized_backtest_result) = evaluation {
+                    if optimized_backtest_result.score.is_finite() {
+                        observations.push((
+                            Self::unit_point(&space.parameters, &point),
+                            optimized_backtest_result.score as f64,
+                        ));
+                    }
+
+                    backtest_results.push(optimized_backtest_result);
+                }
+            }
+
+            let remaining = budget - seen.len();
+            batch = self.propose_batch(
+                space,
+                &observations,
+                &mut seen,
+                &mut rng,
+                self.batch_size.max(1).min(remaining),
+            );
+        }
+
+        finalize_results(strategy, &mut backtest_results);
 
         Ok(backtest_results)
     }
 }
+
+impl BeysianGridOptimizer {
+    // Kriging believer: every picked point is assumed to score the surrogate's mean so
+    // the rest of the batch spreads out instead of piling on the same optimum
+    fn propose_batch(
+        &self,
+        space: &OptimizationSpace,
+        observations: &[(Vec<f64>, f64)],
+        seen: &mut HashSet<Vec<usize>>,
+        rng: &mut SeededRng,
+        batch_size: usize,
+    ) -> Vec<Vec<usize>> {
+        if observations.is_empty() {
+            return Self::random_batch(space, seen, rng, batch_size);
+        }
+
+        let mut batch: Vec<Vec<usize>> = vec![];
+
+        let mut believed_observations = observations.to_vec();
+
+        while batch.len() < batch_size {
+            let surrogate = match GaussianProcess::fit(&believed_observations) {
+                Some(surrogate) => surrogate,
+                None => break,
+            };
+
+            let candidates = self.candidate_points(space, &believed_observations, seen, rng);
+
+            let best_candidate = candidates
+                .into_iter()
+                .map(|point| {
+                    let unit_point = Self::unit_point(&space.parameters, &point);
+                    let (mean, deviation) = surrogate.predict(&unit_point);
+                    let acquisition = surrogate.acquisition(self.acquisition, mean, deviation);
+                    (point, unit_point, mean, acquisition)
+                })
+                .max_by(|a, b| a.3.total_cmp(&b.3));
+
+            let (point, unit_point, mean) = match best_candidate {
+                Some((point, unit_point, mean, _)) => (point, unit_point, mean),
+                None => break,
+            };
+
+            seen.insert(point.clone());
+            believed_observations.push((unit_point, surrogate.unstandardize(mean)));
+            batch.push(point);
+        }
+
+        batch
+    }
+
+    fn candidate_points(
+        &self,
+        space: &OptimizationSpace,
+        observations: &[(Vec<f64>, f64)],
+        seen: &HashSet<Vec<usize>>,
+        rng: &mut SeededRng,
+    ) -> Vec<Vec<usize>> {
+        let mut candidates: HashSet<Vec<usize>> = HashSet::new();
+
+        // Grid neighbours of the incumbent, random draws rarely land right next to it
+        if let Some((best_unit_point, _)) = observations.iter().max_by(|a, b| a.1.total_cmp(&b.1)) {
+            let best_point: Vec<usize> = space
+                .parameters
+                .iter()
+                .zip(best_unit_point.iter())
+                .map(|(param, &unit)| param.index_at(unit as f32))
+                .collect();
+
+            for (param_index, param) in space.parameters.iter().enumerate() {
+                for offset in [-1i64, 1] {
+                    let index = best_point[param_index] as i64 + offset;
+
+                    if index < 0 || index >= param.cardinality() as i64 {
+                        continue;
+                    }
+
+                    let mut neighbour = best_point.clone();
+                    neighbour[param_index] = index as usize;
+
+                    if space.resolve(&mut neighbour).is_some() && !seen.contains(&neighbour) {
+                        candidates.insert(neighbour);
+                    }
+                }
+            }
+        }
+
+        let mut attempts = 0;
+        while candidates.len() < self.candidates && attempts < self.candidates * 4 {
+            attempts += 1;
+
+            let mut point = space.sample_point(rng);
+
+            if space.resolve(&mut point).is_some() && !seen.contains(&point) {
+                candidates.insert(point);
+            }
+        }
+
+        let mut candidates: Vec<Vec<usize>> = candidates.into_iter().collect();
+        // Keeps runs with the same seed reproducible regardless of hashing order
+        candidates.sort();
+
+        candidates
+    }
+
+    // Feasible points drawn uniformly, the draws are bounded since constraints may leave
+    // fewer feasible points than requested
+    fn random_batch(
+        space: &OptimizationSpace,
+        seen: &mut HashSet<Vec<usize>>,
+        rng: &mut SeededRng,
+        batch_size: usize,
+    ) -> Vec<Vec<usize>> {
+        let mut batch: Vec<Vec<usize>> = vec![];
+        let mut attempts = 0;
+
+        while batch.len() < batch_size && attempts < batch_size.saturating_mul(100) {
+            attempts += 1;
+
+            let mut point = space.sample_point(rng);
+
+            if space.resolve(&mut point).is_some() && seen.insert(point.clone()) {
+                batch.push(point);
+            }
+        }
+
+        batch
+    }
+
+    fn unit_point(hyperparameters: &[OptimizationParameter], point: &[usize]) -> Vec<f64> {
+        hyperparameters
+            .iter()
+            .zip(point.iter())
+            .map(|(param, &index)| param.unit_at(index) as f64)
+            .collect()
+    }
+}
+
+// Zero mean GP with a squared exponential kernel over standardized scores, the length
+// scale is picked by maximizing the log marginal likelihood
+struct GaussianProcess {
+    points: Vec<Vec<f64>>,
+    lower: Vec<Vec<f64>>,
+    alpha: Vec<f64>,
+    length_scale: f64,
+    score_mean: f64,
+    score_deviation: f64,
+    best_standardized_score: f64,
+}
+
+impl GaussianProcess {
+    fn fit(observations: &[(Vec<f64>, f64)]) -> Option<Self> {
+        if observations.is_empty() {
+            return None;
+        }
+
+        let n = observations.len() as f64;
+        let score_mean = observations.iter().map(|(_, score)| score).sum::<f64>() / n;
+        let score_variance = observations
+            .iter()
+            .map(|(_, score)| (score - score_mean).powi(2))
+            .sum::<f64>()
+            / n;
+        let score_deviation = if score_variance > 0.0 {
+            score_variance.sqrt()
+        } else {
+            1.0
+        };
+
+        let points: Vec<Vec<f64>> = observations
+            .iter()
+            .map(|(point, _)| point.clone())
+            .collect();
+        let scores: Vec<f64> = observations
+            .iter()
+            .map(|(_, score)| (score - score_mean) / score_deviation)
+            .collect();
+
+        let mut best_fit: Option<(f64, Vec<Vec<f64>>, Vec<f64>, f64)> = None;
+
+        for length_scale in LENGTH_SCALES {
+            let covariance: Vec<Vec<f64>> = points
+                .iter()
+                .enumerate()
+                .map(|(i, a)| {
+                    points
+                        .iter()
+                        .enumerate()
+                        .map(|(j, b)| {
+                            let noise = if i == j { OBSERVATION_NOISE } else { 0.0 };
+                            Self::kernel(a, b, length_scale) + noise
+                        })
+                        .collect()
+                })
+                .collect();
+
+            let lower = match cholesky(&covariance) {
+                Some(lower) => lower,
+                None => continue,
+            };
+
+            let alpha = cholesky_solve(&lower, &scores);
+
+            let log_marginal_likelihood = -0.5
+                * scores
+                    .iter()
+                    .zip(alpha.iter())
+                    .map(|(score, a)| score * a)
+                    .sum::<f64>()
+                - (0..lower.len()).map(|i| lower[i][i].ln()).sum::<f64>();
+
+            let is_better = match &best_fit {
+                Some((best_likelihood, _, _, _)) => log_marginal_likelihood > *best_likelihood,
+                None => true,
+            };
+
+            if is_better {
+                best_fit = Some((log_marginal_likelihood, lower, alpha, length_scale));
+            }
+        }
+
+        let (_, lower, alpha, length_scale) = best_fit?;
+
+        let best_standardized_score = scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        return Some(Self {
+            points,
+            lower,
+            alpha,
+            length_scale,
+            score_mean,
+            score_deviation,
+            best_standardized_score,
+        });
+    }
+
+    fn kernel(a: &[f64], b: &[f64], length_scale: f64) -> f64 {
+        let squared_distance: f64 = a.iter().zip(b.iter()).map(|(x, y)| (x - y).powi(2)).sum();
+
+        (-0.5 * squared_distance / (length_scale * length_scale)).exp()
+    }
+
+    // Standardized posterior mean and standard deviation at `point`
+    fn predict(&self, point: &[f64]) -> (f64, f64) {
+        let covariances: Vec<f64> = self
+            .points
+            .iter()
+            .map(|observed_point| Self::kernel(point, observed_point, self.length_scale))
+            .collect();
+
+        let mean = covariances
+            .iter()
+            .zip(self.alpha.iter())
+            .map(|(covariance, a)| covariance * a)
+            .sum::<f64>();
+
+        let v = forward_substitution(&self.lower, &covariances);
+        let variance = 1.0 - v.iter().map(|x| x * x).sum::<f64>();
+
+        (mean, variance.max(1e-12).sqrt())
+    }
+
+    fn acquisition(&self, acquisition: AcquisitionFunction, mean: f64, deviation: f64) -> f64 {
+        match acquisition {
+            AcquisitionFunction::ExpectedImprovement { xi } => {
+                let improvement = mean - self.best_standardized_score - xi as f64;
+                let z = improvement / deviation;
+
+                improvement * normal_cdf(z as f32) as f64 + deviation * normal_pdf(z as f32) as f64
+            }
+            AcquisitionFunction::UpperConfidenceBound { kappa } => mean + kappa as f64 * deviation,
+        }
+    }
+
+    fn unstandardize(&self, standardized_score: f64) -> f64 {
+        standardized_score * self.score_deviation + self.score_mean
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{
+    library::engines::optimizers::{
+        evaluate_unit_points, finalize_results, grid::OptimizedBacktestResult,
+        progress::OPTIMIZATION_MONITOR, space::OptimizationSpace, study::data_fingerprint,
+        Optimizer,
+    },
+    user::strategies::IStrategy,
+    utils::{formulas::linear_algebra::symmetric_eigen, random::SeededRng},
+};
+use std::{collections::HashMap, io::Error};
+
+// (mu/mu_w, lambda)-CMA-ES over the unit hypercube, candidates are clamped into the cube
+// to be backtested while the distribution is updated from the unclamped samples
+pub struct CmaEsOptimizer {
+    pub generations: usize,
+    // Candidates per generation, `None` for the usual `4 + 3 ln(n)`
+    pub population_size: Option<usize>,
+    // Initial step size in the unit hypercube
+    pub sigma: f32,
+    pub seed: u64,
+}
+
+impl CmaEsOptimizer {
+    pub fn new(generations: usize, seed: u64) -> Self {
+        return Self {
+            generations,
+            population_size: None,
+            sigma: 0.3,
+            seed,
+        };
+    }
+}
+
+impl Optimizer for CmaEsOptimizer {
+    fn optimize(
+        &self,
+        strategy: &Box<dyn IStrategy>,
+        space: &OptimizationSpace,
+    ) -> Result<Vec<OptimizedBacktestResult>, Error> {
+        space.validate()?;
+
+        let mut backtest_results: Vec<OptimizedBacktestResult> = vec![];
+
+        if space.is_empty() {
+            return Ok(backtest_results);
+        }
+
+        let data_fingerprint = data_fingerprint(strategy);
+
+        let n = space.parameters.len();
+        let n_f = n as f64;
+        let lambda = self
+            .population_size
+            .unwrap_or(4 + (3.0 * n_f.ln()).floor() as usize)
+            .max(2);
+        let mu = lambda / 2;
+
+        let raw_weights: Vec<f64> = (0..mu)
+            .map(|i| (mu as f64 + 0.5).ln() - ((i + 1) as f64).ln())
+            .collect();
+        let weights_sum: f64 = raw_weights.iter().sum();
+        let weights: Vec<f64> = raw_weights.iter().map(|w| w / weights_sum).collect();
+        let mu_eff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let c_sigma = (mu_eff + 2.0) / (n_f + mu_eff + 5.0);
+        let d_sigma = 1.0 + 2.0 * (((mu_eff - 1.0) / (n_f + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+        let c_c = (4.0 + mu_eff / n_f) / (n_f + 4.0 + 2.0 * mu_eff / n_f);
+        let c_1 = 2.0 / ((n_f + 1.3).powi(2) + mu_eff);
+        let c_mu =
+            (1.0 - c_1).min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((n_f + 2.0).powi(2) + mu_eff));
+        let chi_n = n_f.sqrt() * (1.0 - 1.0 / (4.0 * n_f) + 1.0 / (21.0 * n_f * n_f));
+
+        OPTIMIZATION_MONITOR.plan_evaluations(strategy, lambda * self.generations);
+
+        let mut rng = SeededRng::new(self.seed);
+        let mut scores: HashMap<Vec<usize>, f32> = HashMap::new();
+
+        let mut mean: Vec<f64> = vec![0.5; n];
+        let mut sigma = self.sigma as f64;
+        let mut covariance: Vec<Vec<f64>> = identity(n);
+        let mut path_sigma: Vec<f64> = vec![0.0; n];
+        let mut path_c: Vec<f64> = vec![0.0; n];
+
+        for generation in 0..self.generations {
+            if OPTIMIZATION_MONITOR.is_cancelled(strategy) {
+                break;
+            }
+
+            let (eigenvalues, eigenvectors) = symmetric_eigen(&covariance);
+            let deviations: Vec<f64> = eigenvalues.iter().map(|v| v.max(1e-20).sqrt()).collect();
+
+            // y = B * D * z, candidates are mean + sigma * y
+            let steps: Vec<Vec<f64>> = (0..lambda)
+                .map(|_| {
+                    let scaled: Vec<f64> = deviations
+                        .iter()
+                        .map(|deviation| deviation * rng.normal() as f64)
+                        .collect();
+
+                    mat_vec(&eigenvectors, &scaled)
+                })
+                .collect();
+
+            let unit_points: Vec<Vec<f32>> = steps
+                .iter()
+                .map(|step| {
+                    mean.iter()
+                        .zip(step.iter())
+                        .map(|(m, y)| (m + sigma * y).clamp(0.0, 1.0) as f32)
+                        .collect()
+                })
+                .collect();
+
+            let generation_scores = evaluate_unit_points(
+                strategy,
+                data_fingerprint,
+                space,
+                &unit_points,
+                generation,
+                &mut scores,
+                &mut backtest_results,
+            );
+
+            let mut ranking: Vec<usize> = (0..lambda).collect();
+            ranking.sort_by(|&a, &b| {
+                fitness(generation_scores[b]).total_cmp(&fitness(generation_scores[a]))
+            });
+
+            let mut weighted_step: Vec<f64> = vec![0.0; n];
+            for (weight, &index) in weights.iter().zip(ranking.iter()) {
+                for i in 0..n {
+                    weighted_step[i] += weight * steps[index][i];
+                }
+            }
+
+            for i in 0..n {
+                // Keeping the mean inside the cube stops the search from drifting where
+                // every candidate clamps onto the same boundary point
+                mean[i] = (mean[i] + sigma * weighted_step[i]).clamp(0.0, 1.0);
+            }
+
+            // C^-1/2 * y_w = B * D^-1 * B^T * y_w
+            let whitened: Vec<f64> = {
+                let projected = mat_t_vec(&eigenvectors, &weighted_step);
+                let scaled: Vec<f64> = projected
+                    .iter()
+                    .zip(deviations.iter())
+                    .map(|(p, d)| p / d)
+                    .collect();
+                mat_vec(&eigenvectors, &scaled)
+            };
+
+            let sigma_factor = (c_sigma * (2.0 - c_sigma) * mu_eff).sqrt();
+            for i in 0..n {
+                path_sigma[i] = (1.0 - c_sigma) * path_sigma[i] + sigma_factor * whitened[i];
+            }
+
+            let path_sigma_norm = norm(&path_sigma);
+            let h_sigma = if path_sigma_norm
+                / (1.0 - (1.0 - c_sigma).powi(2 * (generation as i32 + 1))).sqrt()
+                < (1.4 + 2.0 / (n_f + 1.0)) * chi_n
+            {
+                1.0
+            } else {
+                0.0
+            };
+
+            let c_factor = (c_c * (2.0 - c_c) * mu_eff).sqrt();
+            for i in 0..n {
+                path_c[i] = (1.0 - c_c) * path_c[i] + h_sigma * c_factor * weighted_step[i];
+            }
+
+            for i in 0..n {
+                for j in 0..n {
+                    let rank_one = path_c[i] * path_c[j]
+                        + (1.0 - h_sigma) * c_c * (2.0 - c_c) * covariance[i][j];
+                    let rank_mu: f64 = weights
+                        .iter()
+                        .zip(ranking.iter())
+                        .map(|(weight, &index)| weight * steps[index][i] * steps[index][j])
+                        .sum();
+
+                    covariance[i][j] =
+                        (1.0 - c_1 - c_mu) * covariance[i][j] + c_1 * rank_one + c_mu * rank_mu;
+                }
+            }
+
+            sigma *= ((c_sigma / d_sigma) * (path_sigma_norm / chi_n - 1.0)).exp();
+            sigma = sigma.clamp(1e-4, 1.0);
+        }
+
+        finalize_results(strategy, &mut backtest_results);
+
+        Ok(backtest_results)
+    }
+}
+
+fn fitness(score: f32) -> f32 {
+    if score.is_nan() {
+        f32::NEG_INFINITY
+    } else {
+        score
+    }
+}
+
+fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect()
+}
+
+fn mat_vec(matrix: &[Vec<f64>], vector: &[f64]) -> Vec<f64> {
+    matrix
+        .iter()
+        .map(|row| row.iter().zip(vector.iter()).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+fn mat_t_vec(matrix: &[Vec<f64>], vector: &[f64]) -> Vec<f64> {
+    (0..matrix.len())
+        .map(|j| (0..matrix.len()).map(|i| matrix[i][j] * vector[i]).sum())
+        .collect()
+}
+
+fn norm(vector: &[f64]) -> f64 {
+    vector.iter().map(|v| v * v).sum::<f64>().sqrt()
+}
@@ -0,0 +1,67 @@
+use crate::{
+    library::engines::optimizers::grid::OptimizedBacktestResult,
+    utils::classes::charting::{ChartingData, LineChartingData, LineData},
+};
+use std::collections::BTreeMap;
+
+/// Best score so far and mean score of every iteration as line charts, the iteration
+/// index standing in for the time axis. Empty when the optimizer doesn't track iterations.
+pub fn render_convergence(
+    optimized_backtest_results: &[OptimizedBacktestResult],
+) -> Vec<ChartingData> {
+    let mut iterations_scores: BTreeMap<usize, Vec<f32>> = BTreeMap::new();
+
+    for optimized_backtest_result in optimized_backtest_results {
+        if let Some(iteration) = optimized_backtest_result.iteration {
+            let scores = iterations_scores.entry(iteration).or_default();
+
+            if !optimized_backtest_result.score.is_nan() {
+                scores.push(optimized_backtest_result.score);
+            }
+        }
+    }
+
+    if iterations_scores.is_empty() {
+        return vec![];
+    }
+
+    let mut best_score_data: Vec<Option<LineData>> = vec![];
+    let mut mean_score_data: Vec<Option<LineData>> = vec![];
+    let mut best_score = f32::NEG_INFINITY;
+
+    for (iteration, scores) in iterations_scores {
+        if scores.is_empty() {
+            continue;
+        }
+
+        best_score = scores.iter().copied().fold(best_score, f32::max);
+
+        best_score_data.push(Some(LineData {
+            time: iteration as i64,
+            value: best_score,
+            color: None,
+        }));
+        mean_score_data.push(Some(LineData {
+            time: iteration as i64,
+            value: scores.iter().sum::<f32>() / scores.len() as f32,
+            color: None,
+        }));
+    }
+
+    vec![
+        ChartingData::LineChartingData(LineChartingData {
+            chart_type: "line".into(),
+            height: None,
+            data: best_score_data,
+            pane: None,
+            title: Some("Best score".into()),
+        }),
+        ChartingData::LineChartingData(LineChartingData {
+            chart_type: "line".into(),
+            height: None,
+            data: mean_score_data,
+            pane: None,
+            title: Some("Iteration mean score".into()),
+        }),
+    ]
+}
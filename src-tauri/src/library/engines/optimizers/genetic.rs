@@ -0,0 +1,396 @@
+use crate::{
+    library::engines::optimizers::{
+        evaluate_combination, finalize_results,
+        grid::{OptimizationParameter, OptimizedBacktestResult},
+        pareto::{crowding_distances, non_dominated_sort, Objective},
+        progress::OPTIMIZATION_MONITOR,
+        space::OptimizationSpace,
+        study::data_fingerprint,
+        Optimizer,
+    },
+    user::strategies::IStrategy,
+    utils::random::SeededRng,
+};
+use rayon::prelude::*;
+use std::{
+    collections::{HashMap, HashSet},
+    io::Error,
+};
+
+// A genome holds one gene per hyperparameter, the step index for numeric parameters
+// and the category index for categoric ones
+type Genome = Vec<usize>;
+
+pub struct GeneticOptimizer {
+    pub population_size: usize,
+    pub generations: usize,
+    pub tournament_size: usize,
+    pub crossover_rate: f32,
+    pub mutation_rate: f32,
+    pub elitism: usize,
+    pub seed: u64,
+}
+
+impl GeneticOptimizer {
+    pub fn new(population_size: usize, generations: usize, seed: u64) -> Self {
+        return Self {
+            population_size,
+            generations,
+            tournament_size: 3,
+            crossover_rate: 0.9,
+            mutation_rate: 0.1,
+            elitism: 2,
+            seed,
+        };
+    }
+}
+
+impl Optimizer for GeneticOptimizer {
+    fn optimize(
+        &self,
+        strategy: &Box<dyn IStrategy>,
+        space: &OptimizationSpace,
+    ) -> Result<Vec<OptimizedBacktestResult>, Error> {
+        space.validate()?;
+
+        let mut backtest_results: Vec<OptimizedBacktestResult> = vec![];
+
+        if space.is_empty() || self.population_size == 0 {
+            return Ok(backtest_results);
+        }
+
+        let data_fingerprint = data_fingerprint(strategy);
+
+        // Several objectives switch selection to NSGA-II, the score alone drives it otherwise
+        let objectives = match strategy.optimization_objectives() {
+            objectives if objectives.len() > 1 => objectives,
+            _ => vec![],
+        };
+
+        let mut rng = SeededRng::new(self.seed);
+        // Genomes are only backtested once, offspring identical to an earlier individual reuse its fitness
+        let mut fitness: HashMap<Genome, Vec<f32>> = HashMap::new();
+
+        // Infeasible individuals are kept when no feasible one is found, selection weeds them out
+        let mut population: Vec<Genome> = (0..self.population_size)
+            .map(|_| {
+                space
+                    .sample_feasible_point(&mut rng, 100)
+                    .unwrap_or_else(|| space.sample_point(&mut rng))
+            })
+            .collect();
+
+        for generation in 0..=self.generations {
+            Self::evaluate_population(
+                strategy,
+                data_fingerprint,
+                space,
+                &objectives,
+                &population,
+                &mut fitness,
+                &mut backtest_results,
+            );
+
+            if generation == self.generations || OPTIMIZATION_MONITOR.is_cancelled(strategy) {
+                break;
+            }
+
+            population = if objectives.is_empty() {
+                self.next_generation(space, &population, &fitness, &mut rng)
+            } else {
+                self.next_generation_nsga(space, &population, &fitness, &mut rng)
+            };
+        }
+
+        finalize_results(strategy, &mut backtest_results);
+
+        Ok(backtest_results)
+    }
+}
+
+impl GeneticOptimizer {
+    // Fitness is a vector of maximized values, the score alone for single objective runs.
+    // Planned per generation, NSGA-II populations carry the survivors along with the offspring
+    fn evaluate_population(
+        strategy: &Box<dyn IStrategy>,
+        data_fingerprint: u64,
+        space: &OptimizationSpace,
+        objectives: &[Objective],
+        population: &[Genome],
+        fitness: &mut HashMap<Genome, Vec<f32>>,
+        backtest_results: &mut Vec<OptimizedBacktestResult>,
+    ) {
+        OPTIMIZATION_MONITOR.plan_evaluations(strategy, population.len());
+
+        let mut pending: HashSet<&Genome> = HashSet::new();
+        let unevaluated: Vec<&Genome> = population
+            .iter()
+            .filter(|genome| !fitness.contains_key(*genome) && pending.insert(*genome))
+            .collect();
+
+        OPTIMIZATION_MONITOR.complete_evaluations(
+            strategy,
+            population.len() - unevaluated.len(),
+            None,
+        );
+
+        let evaluations: Vec<(Genome, Option<OptimizedBacktestResult>)> = unevaluated
+            .into_par_iter()
+            .map(|genome| {
+                let mut resolved_genome = genome.clone();
+                let evaluation = match space.resolve(&mut resolved_genome) {
+                    Some(combination) => {
+                        evaluate_combination(strategy, data_fingerprint, combination)
+                    }
+                    // Infeasible genomes aren't backtested but still count as evaluated
+                    None => {
+                        OPTIMIZATION_MONITOR.complete_evaluations(strategy, 1, None);
+                        None
+                    }
+                };
+
+                (genome.clone(), evaluation)
+            })
+            .collect();
+
+        for (genome, evaluation) in evaluations {
+            match evaluation {
+                Some(optimized_backtest_result) => {
+                    let genome_fitness = if objectives.is_empty() {
+                        let score = if optimized_backtest_result.score.is_nan() {
+                            f32::NEG_INFINITY
+                        } else {
+                            optimized_backtest_result.score
+                        };
+
+                        vec![score]
+                    } else {
+                        objectives
+                            .iter()
+                            .map(|objective| {
+                                objective.oriented_value(&optimized_backtest_result.backtest_result)
+                            })
+                            .collect()
+                    };
+
+                    fitness.insert(genome, genome_fitness);
+                    backtest_results.push(optimized_backtest_result);
+                }
+                None => {
+                    fitness.insert(genome, vec![f32::NEG_INFINITY; objectives.len().max(1)]);
+                }
+            }
+        }
+    }
+
+    fn next_generation(
+        &self,
+        space: &OptimizationSpace,
+        population: &[Genome],
+        fitness: &HashMap<Genome, Vec<f32>>,
+        rng: &mut SeededRng,
+    ) -> Vec<Genome> {
+        let scores: Vec<f32> = population
+            .iter()
+            .map(|genome| {
+                fitness
+                    .get(genome)
+                    .map(|genome_fitness| genome_fitness[0])
+                    .unwrap_or(f32::NEG_INFINITY)
+            })
+            .collect();
+
+        let mut ranking: Vec<usize> = (0..population.len()).collect();
+        ranking.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+
+        let mut next_population: Vec<Genome> = ranking
+            .iter()
+            .take(self.elitism.min(self.population_size))
+            .map(|&index| population[index].clone())
+            .collect();
+
+        let offspring_count = self.population_size - next_population.len();
+        next_population.extend(self.offspring(
+            space,
+            population,
+            |a, b| scores[a] > scores[b],
+            offspring_count,
+            rng,
+        ));
+
+        next_population
+    }
+
+    // Keeps the best `population_size` distinct genomes by Pareto rank then crowding
+    // distance, and appends as many offspring; the survivors compete again with the
+    // offspring once those are evaluated, which is NSGA-II's elitist replacement
+    fn next_generation_nsga(
+        &self,
+        space: &OptimizationSpace,
+        population: &[Genome],
+        fitness: &HashMap<Genome, Vec<f32>>,
+        rng: &mut SeededRng,
+    ) -> Vec<Genome> {
+        let mut seen: HashSet<&Genome> = HashSet::new();
+        let candidates: Vec<&Genome> = population
+            .iter()
+            .filter(|genome| seen.insert(*genome))
+            .collect();
+
+        let values: Vec<Vec<f32>> = candidates
+            .iter()
+            .map(|&genome| fitness.get(genome).cloned().unwrap_or_default())
+            .collect();
+
+        let mut survivors: Vec<Genome> = vec![];
+        let mut ranks: Vec<usize> = vec![];
+        let mut crowding: Vec<f32> = vec![];
+
+        for (rank, front) in non_dominated_sort(&values).into_iter().enumerate() {
+            if survivors.len() >= self.population_size {
+                break;
+            }
+
+            let distances = crowding_distances(&values, &front);
+            let mut members: Vec<(usize, f32)> = front.into_iter().zip(distances).collect();
+            members.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            for (index, distance) in members
+                .into_iter()
+                .take(self.population_size - survivors.len())
+            {
+                survivors.push(candidates[index].clone());
+                ranks.push(rank);
+                crowding.push(distance);
+            }
+        }
+
+        let offspring = self.offspring(
+            space,
+            &survivors,
+            |a, b| ranks[a] < ranks[b] || (ranks[a] == ranks[b] && crowding[a] > crowding[b]),
+            self.population_size,
+            rng,
+        );
+
+        survivors.extend(offspring);
+
+        survivors
+    }
+
+    fn offspring(
+        &self,
+        space: &OptimizationSpace,
+        parents: &[Genome],
+        is_better: impl Fn(usize, usize) -> bool,
+        count: usize,
+        rng: &mut SeededRng,
+    ) -> Vec<Genome> {
+        let mut children: Vec<Genome> = vec![];
+
+        if parents.is_empty() {
+            return children;
+        }
+
+        while children.len() < count {
+            let first_parent = &parents[self.tournament(parents.len(), &is_better, rng)];
+            let second_parent = &parents[self.tournament(parents.len(), &is_better, rng)];
+
+            let (mut first_child, mut second_child) = if rng.chance(self.crossover_rate) {
+                Self::uniform_crossover(first_parent, second_parent, rng)
+            } else {
+                (first_parent.clone(), second_parent.clone())
+            };
+
+            self.mutate(&space.parameters, &mut first_child, rng);
+            self.mutate(&space.parameters, &mut second_child, rng);
+
+            // Canonical genomes let children differing only in inactive genes share a fitness
+            space.resolve(&mut first_child);
+            space.resolve(&mut second_child);
+
+            children.push(first_child);
+            if children.len() < count {
+                children.push(second_child);
+            }
+        }
+
+        children
+    }
+
+    fn tournament(
+        &self,
+        population_size: usize,
+        is_better: &impl Fn(usize, usize) -> bool,
+        rng: &mut SeededRng,
+    ) -> usize {
+        let mut winner = rng.range_usize(0, population_size);
+
+        for _ in 1..self.tournament_size.max(1) {
+            let contender = rng.range_usize(0, population_size);
+
+            if is_better(contender, winner) {
+                winner = contender;
+            }
+        }
+
+        winner
+    }
+
+    fn uniform_crossover(
+        first_parent: &Genome,
+        second_parent: &Genome,
+        rng: &mut SeededRng,
+    ) -> (Genome, Genome) {
+        let mut first_child = first_parent.clone();
+        let mut second_child = second_parent.clone();
+
+        for gene_index in 0..first_child.len() {
+            if rng.chance(0.5) {
+                std::mem::swap(&mut first_child[gene_index], &mut second_child[gene_index]);
+            }
+        }
+
+        (first_child, second_child)
+    }
+
+    fn mutate(
+        &self,
+        hyperparameters: &[OptimizationParameter],
+        genome: &mut Genome,
+        rng: &mut SeededRng,
+    ) {
+        for (gene, param) in genome.iter_mut().zip(hyperparameters.iter()) {
+            if !rng.chance(self.mutation_rate) {
+                continue;
+            }
+
+            let cardinality = param.cardinality();
+
+            match param {
+                // Numeric genes creep to nearby steps so good regions are refined, not abandoned
+                OptimizationParameter::Numeric(_) => {
+                    let spread = (cardinality as f32 / 10.0).max(1.0);
+                    let offset = (rng.normal() * spread).round() as i64;
+                    let offset = if offset == 0 {
+                        if rng.chance(0.5) {
+                            1
+                        } else {
+                            -1
+                        }
+                    } else {
+                        offset
+                    };
+
+                    *gene = (*gene as i64 + offset).clamp(0, cardinality as i64 - 1) as usize;
+                }
+                OptimizationParameter::Boolean(_) | OptimizationParameter::Categoric(_) => {
+                    if cardinality > 1 {
+                        let other = rng.range_usize(0, cardinality - 1);
+                        *gene = if other >= *gene { other + 1 } else { other };
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,19 @@
 use crate::library::engines::optimizers::async_trait;
 use crate::user::strategies::Metric;
 use crate::{
-    library::engines::optimizers::Optimizer,
+    library::engines::optimizers::{
+        evaluate_combination, finalize_results, progress::OPTIMIZATION_MONITOR,
+        space::OptimizationSpace, study::data_fingerprint, Optimizer,
+    },
     user::{
         composer::CompositionDataType,
+        library::{
+            formulas::{
+                deflated_sharpe_ratio::DeflatedSharpeRatio,
+                probabilistic_sharpe_ratio::ProbabilisticSharpeRatio,
+            },
+            IInjectable,
+        },
         strategies::{BacktestResult, IStrategy},
     },
 };
@@ -14,26 +24,89 @@ use uuid::Uuid;
 
 pub enum OptimizationKind {
     NUMERIC,
+    BOOLEAN,
     CATEGORIC,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumericKind {
+    // Whole values, passed to the backtest as `CompositionDataType::Usize`
+    Integer,
+    // Passed to the backtest as `CompositionDataType::F32`
+    Float,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct NumericOptimizationParameter {
     pub name: String,
     pub start: f32,
     pub end: f32,
     pub step: f32,
+    pub kind: NumericKind,
+    // Samplers draw values uniformly in log space, for ranges spanning orders of magnitude
+    #[serde(default)]
+    pub log_scale: bool,
+}
+
+impl NumericOptimizationParameter {
+    /// Number of values on the `start..end` grid spaced by `step`.
+    pub fn steps(&self) -> usize {
+        if self.step <= 0.0 {
+            return 1;
+        }
+
+        ((self.end - self.start) / self.step).ceil().max(1.0) as usize
+    }
+
+    pub fn value_at_step(&self, step_index: usize) -> f32 {
+        self.start + step_index.min(self.steps() - 1) as f32 * self.step
+    }
+
+    /// Maps a point of the unit interval onto the closest grid step.
+    pub fn step_at(&self, unit: f32) -> usize {
+        let steps = self.steps();
+        ((unit.clamp(0.0, 1.0) * steps as f32) as usize).min(steps - 1)
+    }
+
+    /// Center of a grid step in the unit interval.
+    pub fn unit_at_step(&self, step_index: usize) -> f32 {
+        (step_index as f32 + 0.5) / self.steps() as f32
+    }
+
+    /// Maps a uniform draw of the unit interval onto a grid step, honouring `log_scale`.
+    pub fn sample_step(&self, unit: f32) -> usize {
+        if !self.log_scale || self.start <= 0.0 || self.step <= 0.0 {
+            return self.step_at(unit);
+        }
+
+        let value = self.start * (self.end / self.start).powf(unit.clamp(0.0, 1.0));
+
+        (((value - self.start) / self.step).floor().max(0.0) as usize).min(self.steps() - 1)
+    }
+
+    pub fn to_composition(&self, value: f32) -> CompositionDataType {
+        match self.kind {
+            NumericKind::Integer => CompositionDataType::Usize(value.round().max(0.0) as usize),
+            NumericKind::Float => CompositionDataType::F32(value),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BooleanOptimizationParameter {
+    pub name: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CategoricOptimizationParameter {
     pub name: String,
     pub categories: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum OptimizationParameter {
     Numeric(NumericOptimizationParameter),
+    Boolean(BooleanOptimizationParameter),
     Categoric(CategoricOptimizationParameter),
 }
 
@@ -47,6 +120,15 @@ impl OptimizationParameter {
         }
     }
 
+    pub fn extract_boolean(
+        optimization_parameter: &OptimizationParameter,
+    ) -> &BooleanOptimizationParameter {
+        match optimization_parameter {
+            OptimizationParameter::Boolean(p) => p,
+            _ => panic!("Wrong optimization parameter type"),
+        }
+    }
+
     pub fn extract_categoric(
         optimization_parameter: &OptimizationParameter,
     ) -> &CategoricOptimizationParameter {
@@ -55,45 +137,161 @@ impl OptimizationParameter {
             _ => panic!("Wrong optimization parameter type"),
         }
     }
+
+    pub fn name(&self) -> &str {
+        match self {
+            OptimizationParameter::Numeric(p) => &p.name,
+            OptimizationParameter::Boolean(p) => &p.name,
+            OptimizationParameter::Categoric(p) => &p.name,
+        }
+    }
+
+    /// Number of distinct values the parameter can take.
+    pub fn cardinality(&self) -> usize {
+        match self {
+            OptimizationParameter::Numeric(p) => p.steps(),
+            OptimizationParameter::Boolean(_) => 2,
+            OptimizationParameter::Categoric(p) => p.categories.len(),
+        }
+    }
+
+    /// Maps a point of the unit interval onto the closest value index.
+    pub fn index_at(&self, unit: f32) -> usize {
+        let cardinality = self.cardinality().max(1);
+        ((unit.clamp(0.0, 1.0) * cardinality as f32) as usize).min(cardinality - 1)
+    }
+
+    /// Center of a value index in the unit interval.
+    pub fn unit_at(&self, index: usize) -> f32 {
+        (index as f32 + 0.5) / self.cardinality().max(1) as f32
+    }
+
+    /// Maps a uniform draw of the unit interval onto a value index.
+    pub fn sample_index(&self, unit: f32) -> usize {
+        match self {
+            OptimizationParameter::Numeric(p) => p.sample_step(unit),
+            _ => self.index_at(unit),
+        }
+    }
+
+    pub fn value_at(&self, index: usize) -> CompositionDataType {
+        match self {
+            OptimizationParameter::Numeric(p) => p.to_composition(p.value_at_step(index)),
+            OptimizationParameter::Boolean(_) => CompositionDataType::Bool(index != 0),
+            OptimizationParameter::Categoric(p) => {
+                CompositionDataType::String(p.categories[index.min(p.categories.len() - 1)].clone())
+            }
+        }
+    }
 }
 
 pub struct GridOptimizer {}
 
+impl GridOptimizer {
+    pub fn new() -> Self {
+        return Self {};
+    }
+}
+
 #[derive(Debug)]
 pub struct OptimizedBacktestResult {
     pub backtest_result: BacktestResult,
     pub optimized_parameters: HashMap<String, CompositionDataType>,
     pub score: f32,
+    pub probabilistic_sharpe_ratio: Option<f32>,
+    pub deflated_sharpe_ratio: Option<f32>,
+    // Filled when the strategy declares several optimization objectives
+    pub objective_values: Vec<(String, Option<f32>)>,
+    pub pareto_rank: Option<usize>,
+    // Mean score of the result and its neighbours on the parameter grid
+    pub robustness_score: Option<f32>,
+    // Iteration of the optimizer that evaluated it, for convergence charts
+    pub iteration: Option<usize>,
+}
+
+impl OptimizedBacktestResult {
+    /// Attaches PSR and DSR to every result, treating each of them as one trial
+    /// of the same optimization run.
+    pub fn attach_sharpe_significance(optimized_backtest_results: &mut [OptimizedBacktestResult]) {
+        let trials = optimized_backtest_results.len();
+
+        let probabilistic_sharpe_ratios: Vec<ProbabilisticSharpeRatio> = optimized_backtest_results
+            .iter()
+            .map(|optimized_backtest_result| {
+                let mut probabilistic_sharpe_ratio = ProbabilisticSharpeRatio::new(None);
+
+                for trade in optimized_backtest_result.backtest_result.trades() {
+                    probabilistic_sharpe_ratio.allocate(trade.pl_portfolio() / 100.0);
+                }
+
+                probabilistic_sharpe_ratio
+            })
+            .collect();
+
+        let trials_sharpes: Vec<f32> = probabilistic_sharpe_ratios
+            .iter()
+            .filter_map(|probabilistic_sharpe_ratio| probabilistic_sharpe_ratio.sharpe())
+            .collect();
+
+        let trials_sharpe_variance = if trials_sharpes.len() < 2 {
+            0.0
+        } else {
+            let n = trials_sharpes.len() as f32;
+            let mean = trials_sharpes.iter().sum::<f32>() / n;
+            trials_sharpes
+                .iter()
+                .map(|&sharpe| (sharpe - mean).powi(2))
+                .sum::<f32>()
+                / (n - 1.0)
+        };
+
+        for (optimized_backtest_result, mut probabilistic_sharpe_ratio) in
+            optimized_backtest_results
+                .iter_mut()
+                .zip(probabilistic_sharpe_ratios.into_iter())
+        {
+            optimized_backtest_result.probabilistic_sharpe_ratio =
+                probabilistic_sharpe_ratio.get_data();
+
+            optimized_backtest_result.deflated_sharpe_ratio = match (
+                probabilistic_sharpe_ratio.sharpe(),
+                probabilistic_sharpe_ratio.skewness(),
+                probabilistic_sharpe_ratio.kurtosis(),
+            ) {
+                (Some(sharpe), Some(skewness), Some(kurtosis)) => DeflatedSharpeRatio::compute(
+                    sharpe,
+                    probabilistic_sharpe_ratio.count(),
+                    skewness,
+                    kurtosis,
+                    trials,
+                    trials_sharpe_variance,
+                ),
+                _ => None,
+            };
+        }
+    }
 }
 
 impl Optimizer for GridOptimizer {
     fn optimize(
+        &self,
         strategy: &Box<dyn IStrategy>,
-        hyperparameters: &[OptimizationParameter],
+        space: &OptimizationSpace,
     ) -> Result<Vec<OptimizedBacktestResult>, Error> {
-        let combinations = Self::generate_combinations(hyperparameters);
+        space.validate()?;
 
-        let backtest_results: Vec<OptimizedBacktestResult> = combinations
+        let data_fingerprint = data_fingerprint(strategy);
+
+        let combinations = Self::generate_combinations(space);
+
+        OPTIMIZATION_MONITOR.plan_evaluations(strategy, combinations.len());
+
+        let mut backtest_results: Vec<OptimizedBacktestResult> = combinations
             .into_par_iter()
-            .filter_map(|combination| {
-                eprintln!("{:?}", combination);
-                strategy
-                    .backtest(Some(&combination))
-                    .map(|backtest_result| {
-                        let score = strategy.optimization_target(&backtest_result);
-                        OptimizedBacktestResult {
-                            backtest_result,
-                            optimized_parameters: combination,
-                            score,
-                        }
-                    })
-                    .ok()
-            })
+            .filter_map(|combination| evaluate_combination(strategy, data_fingerprint, combination))
             .collect();
 
-        for b in &backtest_results {
-            println!("{:?}", b.backtest_result.metrics());
-        }
+        finalize_results(strategy, &mut backtest_results);
 
         Ok(backtest_results)
     }
@@ -101,61 +299,45 @@ impl Optimizer for GridOptimizer {
 
 impl GridOptimizer {
     fn generate_combinations(
-        hyperparameters: &[OptimizationParameter],
+        space: &OptimizationSpace,
     ) -> Vec<HashMap<String, CompositionDataType>> {
-        let numeric_params: Vec<&NumericOptimizationParameter> = hyperparameters
-            .iter()
-            .map(|param| OptimizationParameter::extract_numeric(param))
-            .collect();
-
-        let total_combinations = numeric_params
-            .iter()
-            .map(|param| {
-                let steps = (param.end - param.start).ceil() as usize;
-                steps.max(1)
-            })
-            .product();
+        let mut combinations = Vec::new();
 
-        let mut combinations = Vec::with_capacity(total_combinations);
-
-        Self::generate_recursive(&numeric_params, 0, &mut HashMap::new(), &mut combinations);
+        Self::generate_recursive(space, 0, &mut HashMap::new(), &mut combinations);
 
         return combinations;
     }
 
     fn generate_recursive(
-        numeric_params: &Vec<&NumericOptimizationParameter>,
+        space: &OptimizationSpace,
         param_index: usize,
         current_combination: &mut HashMap<String, CompositionDataType>,
         combinations: &mut Vec<HashMap<String, CompositionDataType>>,
     ) {
-        if param_index == numeric_params.len() {
-            combinations.push(current_combination.clone());
+        if param_index == space.parameters.len() {
+            if space.is_feasible(current_combination) {
+                combinations.push(current_combination.clone());
+            }
             return;
         }
 
-        let current_param = numeric_params[param_index];
-
-        let mut value = current_param.start;
-        while value < current_param.end {
-            let composition_value = if value.fract() == 0.0 {
-                CompositionDataType::Usize(value as usize)
-            } else {
-                CompositionDataType::F32(value)
-            };
+        // Inactive conditional parameters do not branch the grid
+        if !space.is_active(param_index, current_combination) {
+            Self::generate_recursive(space, param_index + 1, current_combination, combinations);
+            return;
+        }
 
-            current_combination.insert(current_param.name.clone(), composition_value);
+        let current_param = &space.parameters[param_index];
 
-            Self::generate_recursive(
-                numeric_params,
-                param_index + 1,
-                current_combination,
-                combinations,
+        for value_index in 0..current_param.cardinality() {
+            current_combination.insert(
+                current_param.name().to_string(),
+                current_param.value_at(value_index),
             );
 
-            value += current_param.step;
+            Self::generate_recursive(space, param_index + 1, current_combination, combinations);
         }
 
-        current_combination.remove(&current_param.name);
+        current_combination.remove(current_param.name());
     }
 }
@@ -0,0 +1,256 @@
+use crate::{
+    library::engines::optimizers::{
+        evaluate_combination, evaluate_combination_unrecorded, finalize_results,
+        grid::OptimizedBacktestResult, progress::OPTIMIZATION_MONITOR, space::OptimizationSpace,
+        study::data_fingerprint, Optimizer,
+    },
+    user::strategies::IStrategy,
+    utils::random::SeededRng,
+};
+use rayon::prelude::*;
+use std::{
+    collections::HashSet,
+    io::{Error, ErrorKind},
+};
+
+// Evaluates `candidates` random combinations on the first `min_fraction` of the
+// composition, keeps the best `1 / eta` of them and multiplies the data by `eta` at
+// each rung until the survivors run on the whole composition
+pub struct SuccessiveHalvingOptimizer {
+    pub candidates: usize,
+    pub eta: usize,
+    pub min_fraction: f32,
+    pub seed: u64,
+}
+
+impl SuccessiveHalvingOptimizer {
+    pub fn new(candidates: usize, seed: u64) -> Self {
+        return Self {
+            candidates,
+            eta: 3,
+            min_fraction: 1.0 / 9.0,
+            seed,
+        };
+    }
+}
+
+impl Optimizer for SuccessiveHalvingOptimizer {
+    fn optimize(
+        &self,
+        strategy: &Box<dyn IStrategy>,
+        space: &OptimizationSpace,
+    ) -> Result<Vec<OptimizedBacktestResult>, Error> {
+        space.validate()?;
+
+        if space.is_empty() || self.candidates == 0 {
+            return Ok(vec![]);
+        }
+
+        let mut rng = SeededRng::new(self.seed);
+        let points = sample_points(space, &mut rng, self.candidates, &mut HashSet::new());
+
+        let mut backtest_results = successive_halving(
+            strategy,
+            space,
+            points,
+            self.eta.max(2),
+            &rung_fractions(self.min_fraction, self.eta.max(2)),
+        )?;
+
+        finalize_results(strategy, &mut backtest_results);
+
+        Ok(backtest_results)
+    }
+}
+
+// Runs successive halving brackets trading the number of candidates against the data
+// they start on, from many candidates on little data down to a plain random search
+// on the whole composition, so an unlucky early cut does not lose a late bloomer
+pub struct HyperbandOptimizer {
+    pub max_rungs: usize,
+    pub eta: usize,
+    pub seed: u64,
+}
+
+impl HyperbandOptimizer {
+    pub fn new(max_rungs: usize, seed: u64) -> Self {
+        return Self {
+            max_rungs,
+            eta: 3,
+            seed,
+        };
+    }
+}
+
+impl Optimizer for HyperbandOptimizer {
+    fn optimize(
+        &self,
+        strategy: &Box<dyn IStrategy>,
+        space: &OptimizationSpace,
+    ) -> Result<Vec<OptimizedBacktestResult>, Error> {
+        space.validate()?;
+
+        let mut backtest_results: Vec<OptimizedBacktestResult> = vec![];
+
+        if space.is_empty() {
+            return Ok(backtest_results);
+        }
+
+        let eta = self.eta.max(2);
+        let mut rng = SeededRng::new(self.seed);
+        let mut seen: HashSet<Vec<usize>> = HashSet::new();
+
+        for bracket in (0..=self.max_rungs).rev() {
+            if OPTIMIZATION_MONITOR.is_cancelled(strategy) {
+                break;
+            }
+
+            let candidates = ((self.max_rungs + 1) as f32 / (bracket + 1) as f32
+                * (eta as f32).powi(bracket as i32))
+            .ceil() as usize;
+            let min_fraction = (eta as f32).powi(-(bracket as i32));
+
+            let points = sample_points(space, &mut rng, candidates, &mut seen);
+
+            if points.is_empty() {
+                continue;
+            }
+
+            backtest_results.extend(successive_halving(
+                strategy,
+                space,
+                points,
+                eta,
+                &rung_fractions(min_fraction, eta),
+            )?);
+        }
+
+        finalize_results(strategy, &mut backtest_results);
+
+        Ok(backtest_results)
+    }
+}
+
+/// Data fractions of every rung, growing by `eta` from `min_fraction` up to the whole composition.
+pub fn rung_fractions(min_fraction: f32, eta: usize) -> Vec<f32> {
+    let mut fractions: Vec<f32> = vec![];
+    let mut fraction = min_fraction.clamp(f32::EPSILON, 1.0);
+
+    while fraction < 1.0 - 1e-4 {
+        fractions.push(fraction);
+        fraction *= eta as f32;
+    }
+
+    fractions.push(1.0);
+
+    fractions
+}
+
+/// Scores `points` on growing prefixes of the composition and keeps the top `1 / eta`
+/// at each rung, only the survivors of the last rung are backtested on the whole data
+/// and returned.
+pub fn successive_halving(
+    strategy: &Box<dyn IStrategy>,
+    space: &OptimizationSpace,
+    points: Vec<Vec<usize>>,
+    eta: usize,
+    fractions: &[f32],
+) -> Result<Vec<OptimizedBacktestResult>, Error> {
+    let composed_data = strategy.composed_data();
+    let mut survivors: Vec<Vec<usize>> = points;
+
+    for (rung, &fraction) in fractions.iter().enumerate() {
+        let is_last_rung = rung == fractions.len() - 1;
+
+        let rung_strategy: Box<dyn IStrategy> = if is_last_rung {
+            strategy.with_composed_data(composed_data.clone())
+        } else {
+            let rows = ((composed_data.len() as f32 * fraction).ceil() as usize)
+                .clamp(1, composed_data.len().max(1));
+
+            strategy.with_composed_data(composed_data[..rows.min(composed_data.len())].to_vec())
+        }
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::Unsupported,
+                format!("{} cannot be backtested on partial data", strategy.name()),
+            )
+        })?;
+
+        // Only the last rung runs on the whole data, partial rungs are left out of the study
+        let rung_fingerprint = if is_last_rung {
+            Some(data_fingerprint(&rung_strategy))
+        } else {
+            None
+        };
+
+        // The survivors of the next rung are only known once this one is scored
+        OPTIMIZATION_MONITOR.plan_evaluations(strategy, survivors.len());
+
+        let mut evaluations: Vec<(Vec<usize>, Option<OptimizedBacktestResult>)> = survivors
+            .into_par_iter()
+            .map(|mut point| {
+                let evaluation =
+                    space
+                        .resolve(&mut point)
+                        .and_then(|combination| match rung_fingerprint {
+                            Some(rung_fingerprint) => {
+                                evaluate_combination(&rung_strategy, rung_fingerprint, combination)
+                            }
+                            None => evaluate_combination_unrecorded(&rung_strategy, combination),
+                        });
+
+                (point, evaluation)
+            })
+            .collect();
+
+        if is_last_rung {
+            return Ok(evaluations
+                .into_iter()
+                .filter_map(|(_, evaluation)| evaluation)
+                .collect());
+        }
+
+        let score_of = |evaluation: &Option<OptimizedBacktestResult>| match evaluation {
+            Some(optimized_backtest_result) if !optimized_backtest_result.score.is_nan() => {
+                optimized_backtest_result.score
+            }
+            _ => f32::NEG_INFINITY,
+        };
+
+        evaluations.sort_by(|a, b| score_of(&b.1).total_cmp(&score_of(&a.1)));
+
+        let keep = (evaluations.len() / eta).max(1);
+
+        survivors = evaluations
+            .into_iter()
+            .take(keep)
+            .map(|(point, _)| point)
+            .collect();
+    }
+
+    Ok(vec![])
+}
+
+fn sample_points(
+    space: &OptimizationSpace,
+    rng: &mut SeededRng,
+    count: usize,
+    seen: &mut HashSet<Vec<usize>>,
+) -> Vec<Vec<usize>> {
+    let count = count.min(space.total_combinations());
+    let mut points: Vec<Vec<usize>> = vec![];
+    let mut attempts = 0;
+
+    while points.len() < count && attempts < count.saturating_mul(20) {
+        attempts += 1;
+
+        let mut point = space.sample_point(rng);
+
+        if space.resolve(&mut point).is_some() && seen.insert(point.clone()) {
+            points.push(point);
+        }
+    }
+
+    points
+}
@@ -0,0 +1,247 @@
+use crate::{
+    library::engines::optimizers::{
+        grid::OptimizedBacktestResult, robustness::is_categorical, space::numeric_value,
+    },
+    user::composer::CompositionDataType,
+    utils::classes::charting::{
+        ChartingData, HistogramChartingData, HistogramData, LineChartingData, LineData,
+    },
+};
+use std::collections::BTreeMap;
+
+// Numeric parameters with more distinct values than this are split in equally filled
+// bins, so sampled optimizers don't get one noisy group per sampled value
+const MAX_LEVELS: usize = 20;
+
+pub struct ParameterImportance {
+    pub name: String,
+    // Share of the score variance explained by the parameter alone, the first order
+    // term of a functional ANOVA over the evaluated configurations. Estimated with ω²,
+    // plain eta² grows with the number of levels even when they explain nothing
+    pub importance: f32,
+    // Mean score per value (or bin of values) of the parameter, in value order for
+    // numeric parameters
+    pub marginal_scores: Vec<(String, f32)>,
+    pub numeric: bool,
+}
+
+/// Importance of every optimized parameter from most to least important, over the
+/// results with a finite score. Empty with less than two distinct scores.
+pub fn parameter_importances(
+    optimized_backtest_results: &[OptimizedBacktestResult],
+) -> Vec<ParameterImportance> {
+    let results: Vec<&OptimizedBacktestResult> = optimized_backtest_results
+        .iter()
+        .filter(|optimized_backtest_result| optimized_backtest_result.score.is_finite())
+        .collect();
+
+    if results.len() < 2 {
+        return vec![];
+    }
+
+    let scores: Vec<f32> = results.iter().map(|result| result.score).collect();
+    let mean = scores.iter().sum::<f32>() / scores.len() as f32;
+    let total_variance: f32 = scores.iter().map(|score| (score - mean).powi(2)).sum();
+
+    if total_variance == 0.0 {
+        return vec![];
+    }
+
+    let mut names: Vec<&String> = results
+        .iter()
+        .flat_map(|result| result.optimized_parameters.keys())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    let mut importances: Vec<ParameterImportance> = names
+        .into_iter()
+        .map(|name| {
+            let values: Vec<Option<&CompositionDataType>> = results
+                .iter()
+                .map(|result| result.optimized_parameters.get(name))
+                .collect();
+
+            let numeric = values.iter().all(|value| {
+                value.map_or(false, |v| !is_categorical(v) && numeric_value(v).is_some())
+            });
+
+            let levels = if numeric {
+                numeric_levels(&values)
+            } else {
+                categorical_levels(&values)
+            };
+
+            let mut marginal_scores: Vec<(String, f32)> = vec![];
+            let mut explained_variance = 0.0;
+            let level_count = levels.len();
+
+            for (label, members) in levels {
+                let level_mean =
+                    members.iter().map(|&index| scores[index]).sum::<f32>() / members.len() as f32;
+
+                explained_variance += members.len() as f32 * (level_mean - mean).powi(2);
+                marginal_scores.push((label, level_mean));
+            }
+
+            ParameterImportance {
+                name: name.clone(),
+                importance: omega_squared(
+                    explained_variance,
+                    total_variance,
+                    level_count,
+                    results.len(),
+                ),
+                marginal_scores,
+                numeric,
+            }
+        })
+        .collect();
+
+    importances.sort_by(|a, b| b.importance.total_cmp(&a.importance));
+
+    importances
+}
+
+/// A histogram of the importances in importance order and one marginal score line per
+/// numeric parameter, the rank and the value's position standing in for the time axis.
+pub fn render_importances(
+    importances: &[ParameterImportance],
+) -> (Vec<ChartingData>, Vec<ChartingData>) {
+    let importance_charting_data = if importances.is_empty() {
+        vec![]
+    } else {
+        vec![ChartingData::HistogramChartingData(HistogramChartingData {
+            chart_type: "histogram".into(),
+            height: None,
+            data: importances
+                .iter()
+                .enumerate()
+                .map(|(rank, parameter_importance)| HistogramData {
+                    time: rank as i64,
+                    value: parameter_importance.importance,
+                    color: None,
+                })
+                .collect(),
+            pane: Some(0),
+            title: Some("Parameter importance".into()),
+        })]
+    };
+
+    let marginal_charting_data = importances
+        .iter()
+        .filter(|parameter_importance| parameter_importance.numeric)
+        .map(|parameter_importance| {
+            ChartingData::LineChartingData(LineChartingData {
+                chart_type: "line".into(),
+                height: None,
+                data: parameter_importance
+                    .marginal_scores
+                    .iter()
+                    .enumerate()
+                    .map(|(position, (_, score))| {
+                        Some(LineData {
+                            time: position as i64,
+                            value: *score,
+                            color: None,
+                        })
+                    })
+                    .collect(),
+                pane: None,
+                title: Some(format!("{} marginal score", parameter_importance.name)),
+            })
+        })
+        .collect();
+
+    (importance_charting_data, marginal_charting_data)
+}
+
+// ω² = (SS between - (k - 1) MS within) / (SS total + MS within), 0 when every result
+// is its own level and the within level variance can't be estimated
+fn omega_squared(
+    explained_variance: f32,
+    total_variance: f32,
+    levels: usize,
+    results: usize,
+) -> f32 {
+    if levels < 2 || results <= levels {
+        return 0.0;
+    }
+
+    let within_mean_square = (total_variance - explained_variance) / (results - levels) as f32;
+
+    ((explained_variance - (levels - 1) as f32 * within_mean_square)
+        / (total_variance + within_mean_square))
+        .clamp(0.0, 1.0)
+}
+
+// Result indices per distinct value, or per bin of close values when there are many
+fn numeric_levels(values: &[Option<&CompositionDataType>]) -> Vec<(String, Vec<usize>)> {
+    let mut indexed_values: Vec<(f32, usize)> = values
+        .iter()
+        .enumerate()
+        .filter_map(|(index, value)| value.and_then(numeric_value).map(|v| (v, index)))
+        .collect();
+    indexed_values.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut distinct: Vec<(f32, Vec<usize>)> = vec![];
+
+    for (value, index) in indexed_values {
+        match distinct.last_mut() {
+            Some((last_value, members)) if *last_value == value => members.push(index),
+            _ => distinct.push((value, vec![index])),
+        }
+    }
+
+    if distinct.len() <= MAX_LEVELS {
+        return distinct
+            .into_iter()
+            .map(|(value, members)| (value.to_string(), members))
+            .collect();
+    }
+
+    // Bins never split a value, they close once they hold their share of the results
+    let bin_size = (values.len() as f32 / MAX_LEVELS as f32).ceil() as usize;
+    let mut bins: Vec<(String, Vec<usize>)> = vec![];
+    let mut bin: Vec<usize> = vec![];
+    let mut bin_start = distinct[0].0;
+
+    for (value, members) in &distinct {
+        if bin.is_empty() {
+            bin_start = *value;
+        }
+
+        bin.extend(members);
+
+        if bin.len() >= bin_size {
+            bins.push((
+                format!("{}..{}", bin_start, value),
+                std::mem::take(&mut bin),
+            ));
+        }
+    }
+
+    if !bin.is_empty() {
+        let last_value = distinct.last().unwrap().0;
+        bins.push((format!("{}..{}", bin_start, last_value), bin));
+    }
+
+    bins
+}
+
+fn categorical_levels(values: &[Option<&CompositionDataType>]) -> Vec<(String, Vec<usize>)> {
+    let mut levels: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+    for (index, value) in values.iter().enumerate() {
+        let label = match value {
+            Some(CompositionDataType::String(value)) => value.clone(),
+            Some(CompositionDataType::Bool(value)) => value.to_string(),
+            Some(value) => numeric_value(value).map_or("None".into(), |v| v.to_string()),
+            None => "None".into(),
+        };
+
+        levels.entry(label).or_default().push(index);
+    }
+
+    levels.into_iter().collect()
+}
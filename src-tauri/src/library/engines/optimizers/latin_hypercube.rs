@@ -0,0 +1,75 @@
+use crate::{
+    library::engines::optimizers::{
+        evaluate_points, finalize_results, grid::OptimizedBacktestResult, space::OptimizationSpace,
+        study::data_fingerprint, Optimizer,
+    },
+    user::strategies::IStrategy,
+    utils::random::SeededRng,
+};
+use std::io::Error;
+
+// Splits every parameter's unit interval into `budget` strata and draws exactly once
+// from each, so the samples cover every range evenly even with a small budget
+pub struct LatinHypercubeOptimizer {
+    pub budget: usize,
+    pub seed: u64,
+}
+
+impl LatinHypercubeOptimizer {
+    pub fn new(budget: usize, seed: u64) -> Self {
+        return Self { budget, seed };
+    }
+}
+
+impl Optimizer for LatinHypercubeOptimizer {
+    fn optimize(
+        &self,
+        strategy: &Box<dyn IStrategy>,
+        space: &OptimizationSpace,
+    ) -> Result<Vec<OptimizedBacktestResult>, Error> {
+        space.validate()?;
+
+        if space.is_empty() || self.budget == 0 {
+            return Ok(vec![]);
+        }
+
+        let data_fingerprint = data_fingerprint(strategy);
+
+        let mut rng = SeededRng::new(self.seed);
+        let mut points: Vec<Vec<usize>> =
+            vec![Vec::with_capacity(space.parameters.len()); self.budget];
+
+        for param in &space.parameters {
+            let mut strata: Vec<usize> = (0..self.budget).collect();
+            rng.shuffle(&mut strata);
+
+            for (point, stratum) in points.iter_mut().zip(strata.into_iter()) {
+                let unit = (stratum as f32 + rng.next_f32()) / self.budget as f32;
+                point.push(param.sample_index(unit));
+            }
+        }
+
+        // Infeasible samples are replaced by random feasible ones, the stratification
+        // no longer holds for them but the budget is still spent
+        let mut feasible_points: Vec<Vec<usize>> = points
+            .into_iter()
+            .filter_map(|mut point| space.resolve(&mut point).map(|_| point))
+            .collect();
+
+        let missing_points = self.budget - feasible_points.len();
+        for _ in 0..missing_points {
+            match space.sample_feasible_point(&mut rng, 100) {
+                Some(point) => feasible_points.push(point),
+                None => break,
+            }
+        }
+
+        // Points snapped onto the same grid steps are only backtested once
+        let mut backtest_results =
+            evaluate_points(strategy, data_fingerprint, space, feasible_points);
+
+        finalize_results(strategy, &mut backtest_results);
+
+        Ok(backtest_results)
+    }
+}
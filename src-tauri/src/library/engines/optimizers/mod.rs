@@ -1,17 +1,293 @@
 use crate::{
-    library::engines::optimizers::grid::{OptimizationParameter, OptimizedBacktestResult},
-    user::strategies::{BacktestResult, IStrategy},
+    library::engines::optimizers::{
+        beysian::BeysianGridOptimizer,
+        cma_es::CmaEsOptimizer,
+        genetic::GeneticOptimizer,
+        grid::{GridOptimizer, OptimizedBacktestResult},
+        hyperband::{HyperbandOptimizer, SuccessiveHalvingOptimizer},
+        latin_hypercube::LatinHypercubeOptimizer,
+        pareto::assign_pareto_ranks,
+        particle_swarm::ParticleSwarmOptimizer,
+        progress::OPTIMIZATION_MONITOR,
+        random_search::RandomSearchOptimizer,
+        robustness::attach_robustness_scores,
+        simulated_annealing::SimulatedAnnealingOptimizer,
+        space::OptimizationSpace,
+        study::STUDY_STORE,
+    },
+    user::{composer::CompositionDataType, strategies::IStrategy},
 };
 use async_trait::async_trait;
-use std::io::Error;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Error,
+};
 
 pub trait Optimizer {
     fn optimize(
+        &self,
         strategy: &Box<dyn IStrategy>,
-        hyperparameters: &[OptimizationParameter],
+        space: &OptimizationSpace,
     ) -> Result<Vec<OptimizedBacktestResult>, Error>;
 }
 
+// Optimizer picked from the frontend instead of the one a strategy's `optimize` runs,
+// the sampling ones get a budget of about 48 backtests like the runtime strategies
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum OptimizerKind {
+    Grid,
+    RandomSearch,
+    SimulatedAnnealing,
+    ParticleSwarm,
+    CmaEs,
+    Bayesian,
+    Genetic,
+    LatinHypercube,
+    SuccessiveHalving,
+    Hyperband,
+}
+
+impl OptimizerKind {
+    pub fn optimizer(&self) -> Box<dyn Optimizer> {
+        match self {
+            OptimizerKind::Grid => Box::new(GridOptimizer::new()),
+            OptimizerKind::RandomSearch => Box::new(RandomSearchOptimizer::new(48, 42)),
+            OptimizerKind::SimulatedAnnealing => {
+                Box::new(SimulatedAnnealingOptimizer::new(4, 12, 42))
+            }
+            OptimizerKind::ParticleSwarm => Box::new(ParticleSwarmOptimizer::new(8, 6, 42)),
+            OptimizerKind::CmaEs => Box::new(CmaEsOptimizer::new(8, 42)),
+            OptimizerKind::Bayesian => Box::new(BeysianGridOptimizer::new(48, 42)),
+            OptimizerKind::Genetic => Box::new(GeneticOptimizer::new(12, 3, 42)),
+            OptimizerKind::LatinHypercube => Box::new(LatinHypercubeOptimizer::new(48, 42)),
+            OptimizerKind::SuccessiveHalving => Box::new(SuccessiveHalvingOptimizer::new(81, 42)),
+            OptimizerKind::Hyperband => Box::new(HyperbandOptimizer::new(3, 42)),
+        }
+    }
+}
+
+/// Backtests a single combination and scores it with the strategy's objective,
+/// combinations already in the strategy's study are read back instead of backtested.
+/// `data_fingerprint` is the one of the rows the strategy backtests on, computed once
+/// per run. Nothing is evaluated once the strategy's optimization run is cancelled.
+pub fn evaluate_combination(
+    strategy: &Box<dyn IStrategy>,
+    data_fingerprint: u64,
+    combination: HashMap<String, CompositionDataType>,
+) -> Option<OptimizedBacktestResult> {
+    evaluate(strategy, Some(data_fingerprint), combination)
+}
+
+/// Backtests a single combination without reading or recording the strategy's study,
+/// for evaluations on partial data that would otherwise fill the study with results
+/// nothing reads back.
+pub fn evaluate_combination_unrecorded(
+    strategy: &Box<dyn IStrategy>,
+    combination: HashMap<String, CompositionDataType>,
+) -> Option<OptimizedBacktestResult> {
+    evaluate(strategy, None, combination)
+}
+
+// The study is only used when the data fingerprint is known
+fn evaluate(
+    strategy: &Box<dyn IStrategy>,
+    data_fingerprint: Option<u64>,
+    combination: HashMap<String, CompositionDataType>,
+) -> Option<OptimizedBacktestResult> {
+    if OPTIMIZATION_MONITOR.is_cancelled(strategy) {
+        return None;
+    }
+
+    let studied_result = data_fingerprint
+        .and_then(|data_fingerprint| STUDY_STORE.lookup(strategy, data_fingerprint, &combination));
+
+    let backtest_result = match studied_result {
+        Some(backtest_result) => backtest_result,
+        None => match strategy.backtest(Some(&combination)) {
+            Ok(backtest_result) => {
+                if let Some(data_fingerprint) = data_fingerprint {
+                    STUDY_STORE.record(strategy, data_fingerprint, &combination, &backtest_result);
+                }
+                backtest_result
+            }
+            Err(_) => {
+                OPTIMIZATION_MONITOR.complete_evaluations(strategy, 1, None);
+                return None;
+            }
+        },
+    };
+
+    let score = strategy.score(&backtest_result);
+
+    OPTIMIZATION_MONITOR.complete_evaluations(strategy, 1, Some(score));
+
+    Some(OptimizedBacktestResult {
+        backtest_result,
+        optimized_parameters: combination,
+        score,
+        probabilistic_sharpe_ratio: None,
+        deflated_sharpe_ratio: None,
+        objective_values: vec![],
+        pareto_rank: None,
+        robustness_score: None,
+        iteration: None,
+    })
+}
+
+/// Attaches the statistics that depend on the whole set of trials, run once an
+/// optimizer is done evaluating.
+pub fn finalize_results(
+    strategy: &Box<dyn IStrategy>,
+    optimized_backtest_results: &mut [OptimizedBacktestResult],
+) {
+    OptimizedBacktestResult::attach_sharpe_significance(optimized_backtest_results);
+    attach_robustness_scores(optimized_backtest_results, 1);
+
+    let objectives = strategy.optimization_objectives();
+
+    if objectives.len() > 1 {
+        assign_pareto_ranks(optimized_backtest_results, &objectives);
+    }
+}
+
+/// Backtests again the results read back from the strategy's study, which keep no
+/// equity or benchmark series, for what reads the series: PBO and the rolling metrics.
+/// Results are left as read once the strategy's optimization run is cancelled.
+pub fn restore_series(
+    strategy: &Box<dyn IStrategy>,
+    optimized_backtest_results: &mut [OptimizedBacktestResult],
+) {
+    optimized_backtest_results
+        .par_iter_mut()
+        .filter(|optimized_backtest_result| {
+            optimized_backtest_result.backtest_result.is_from_study()
+        })
+        .for_each(|optimized_backtest_result| {
+            if OPTIMIZATION_MONITOR.is_cancelled(strategy) {
+                return;
+            }
+
+            if let Ok(backtest_result) =
+                strategy.backtest(Some(&optimized_backtest_result.optimized_parameters))
+            {
+                optimized_backtest_result.backtest_result = backtest_result;
+            }
+        });
+}
+
+/// Resolves the given points against the space and backtests the feasible ones in
+/// parallel, skipping duplicates and failed backtests.
+pub fn evaluate_points(
+    strategy: &Box<dyn IStrategy>,
+    data_fingerprint: u64,
+    space: &OptimizationSpace,
+    points: Vec<Vec<usize>>,
+) -> Vec<OptimizedBacktestResult> {
+    let mut seen: HashSet<Vec<usize>> = HashSet::new();
+    let mut combinations: Vec<HashMap<String, CompositionDataType>> = vec![];
+
+    for mut point in points {
+        if let Some(combination) = space.resolve(&mut point) {
+            if seen.insert(point) {
+                combinations.push(combination);
+            }
+        }
+    }
+
+    OPTIMIZATION_MONITOR.plan_evaluations(strategy, combinations.len());
+
+    combinations
+        .into_par_iter()
+        .filter_map(|combination| evaluate_combination(strategy, data_fingerprint, combination))
+        .collect()
+}
+
+/// Decodes points of the unit hypercube into value indices, backtests the ones not seen
+/// yet in parallel and returns the score of every point, NaN when it is infeasible or
+/// its backtest failed. Used by the optimizers searching a continuous space.
+pub fn evaluate_unit_points(
+    strategy: &Box<dyn IStrategy>,
+    data_fingerprint: u64,
+    space: &OptimizationSpace,
+    unit_points: &[Vec<f32>],
+    iteration: usize,
+    scores: &mut HashMap<Vec<usize>, f32>,
+    backtest_results: &mut Vec<OptimizedBacktestResult>,
+) -> Vec<f32> {
+    let points: Vec<(Vec<usize>, Option<HashMap<String, CompositionDataType>>)> = unit_points
+        .iter()
+        .map(|unit_point| {
+            let mut point: Vec<usize> = space
+                .parameters
+                .iter()
+                .zip(unit_point.iter())
+                .map(|(param, &unit)| param.sample_index(unit))
+                .collect();
+            let combination = space.resolve(&mut point);
+
+            (point, combination)
+        })
+        .collect();
+
+    let mut pending: HashSet<&Vec<usize>> = HashSet::new();
+    let unevaluated: Vec<(&Vec<usize>, &HashMap<String, CompositionDataType>)> = points
+        .iter()
+        .filter_map(|(point, combination)| combination.as_ref().map(|c| (point, c)))
+        .filter(|(point, _)| !scores.contains_key(*point) && pending.insert(*point))
+        .collect();
+
+    // Infeasible and already scored points still use up the optimizer's planned budget
+    OPTIMIZATION_MONITOR.complete_evaluations(strategy, points.len() - unevaluated.len(), None);
+
+    let evaluations: Vec<(Vec<usize>, Option<OptimizedBacktestResult>)> = unevaluated
+        .into_par_iter()
+        .map(|(point, combination)| {
+            (
+                point.clone(),
+                evaluate_combination(strategy, data_fingerprint, combination.clone()),
+            )
+        })
+        .collect();
+
+    for (point, evaluation) in evaluations {
+        match evaluation {
+            Some(mut optimized_backtest_result) => {
+                optimized_backtest_result.iteration = Some(iteration);
+                scores.insert(point, optimized_backtest_result.score);
+                backtest_results.push(optimized_backtest_result);
+            }
+            None => {
+                scores.insert(point, f32::NAN);
+            }
+        }
+    }
+
+    points
+        .iter()
+        .map(|(point, combination)| match combination {
+            Some(_) => scores.get(point).copied().unwrap_or(f32::NAN),
+            None => f32::NAN,
+        })
+        .collect()
+}
+
 pub mod beysian;
+pub mod cma_es;
+pub mod convergence;
 pub mod genetic;
 pub mod grid;
+pub mod hyperband;
+pub mod importance;
+pub mod latin_hypercube;
+pub mod objective_function;
+pub mod overfitting;
+pub mod pareto;
+pub mod particle_swarm;
+pub mod progress;
+pub mod random_search;
+pub mod robustness;
+pub mod simulated_annealing;
+pub mod space;
+pub mod study;
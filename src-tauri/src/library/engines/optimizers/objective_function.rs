@@ -0,0 +1,363 @@
+use crate::{
+    library::engines::optimizers::pareto::{Objective, ObjectiveDirection},
+    user::strategies::{BacktestResult, Metric},
+};
+use serde::{Deserialize, Serialize};
+use std::io::{Error, ErrorKind};
+
+// What the frontend sends, e.g. `sharpe - 0.5 * max_drawdown` with at least 30 trades
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ObjectiveDefinition {
+    pub expression: String,
+    pub min_trades: Option<usize>,
+}
+
+// One objective of a multi-objective optimization picked from the frontend, `metric`
+// is named like the variables of an objective expression
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ObjectiveSelection {
+    pub metric: String,
+    pub direction: ObjectiveDirection,
+}
+
+impl ObjectiveSelection {
+    pub fn objective(&self, metrics: &[Metric]) -> Result<Objective, Error> {
+        match resolve_variable(&self.metric, metrics)? {
+            Variable::Metric(metric) => Ok(Objective::metric(metric, self.direction)),
+            Variable::TradeCount => Ok(Objective::trade_count(self.direction)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Variable {
+    Metric(Metric),
+    TradeCount,
+}
+
+#[derive(Clone, Debug)]
+enum Expression {
+    Number(f32),
+    Variable(Variable),
+    Negate(Box<Expression>),
+    Binary(char, Box<Expression>, Box<Expression>),
+    Function(String, Vec<Expression>),
+}
+
+impl Expression {
+    fn evaluate(&self, backtest_result: &BacktestResult) -> f32 {
+        match self {
+            Expression::Number(value) => *value,
+            Expression::Variable(Variable::Metric(metric)) => backtest_result
+                .metrics()
+                .get(metric)
+                .copied()
+                .unwrap_or(f32::NAN),
+            Expression::Variable(Variable::TradeCount) => backtest_result.trades().len() as f32,
+            Expression::Negate(operand) => -operand.evaluate(backtest_result),
+            Expression::Binary(operator, left, right) => {
+                let left = left.evaluate(backtest_result);
+                let right = right.evaluate(backtest_result);
+
+                match operator {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    '/' => left / right,
+                    _ => left.powf(right),
+                }
+            }
+            Expression::Function(name, arguments) => {
+                let arguments: Vec<f32> = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(backtest_result))
+                    .collect();
+
+                match name.as_str() {
+                    "abs" => arguments[0].abs(),
+                    "sqrt" => arguments[0].sqrt(),
+                    "ln" => arguments[0].ln(),
+                    "min" => arguments.iter().copied().fold(f32::INFINITY, f32::min),
+                    _ => arguments.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+                }
+            }
+        }
+    }
+}
+
+// Score computed at runtime from the backtest metrics instead of a compiled
+// `IStrategy::optimization_target`
+#[derive(Clone, Debug)]
+pub struct ObjectiveFunction {
+    pub definition: ObjectiveDefinition,
+    expression: Expression,
+}
+
+impl ObjectiveFunction {
+    /// Parses the expression, its variables must name one of `metrics`.
+    pub fn parse(definition: ObjectiveDefinition, metrics: &[Metric]) -> Result<Self, Error> {
+        let mut parser = Parser {
+            tokens: tokenize(&definition.expression)?,
+            metrics,
+            position: 0,
+        };
+
+        let expression = parser.expression()?;
+
+        if let Some(token) = parser.peek() {
+            return Err(invalid_input(format!("Unexpected {:?}", token)));
+        }
+
+        return Ok(Self {
+            definition,
+            expression,
+        });
+    }
+
+    /// NaN, the worst score for every optimizer, when the backtest has fewer trades than
+    /// required or the expression can't be computed.
+    pub fn evaluate(&self, backtest_result: &BacktestResult) -> f32 {
+        if let Some(min_trades) = self.definition.min_trades {
+            if backtest_result.trades().len() < min_trades {
+                return f32::NAN;
+            }
+        }
+
+        let score = self.expression.evaluate(backtest_result);
+
+        if score.is_finite() {
+            score
+        } else {
+            f32::NAN
+        }
+    }
+}
+
+const FUNCTIONS: [(&str, usize); 5] = [("abs", 1), ("sqrt", 1), ("ln", 1), ("min", 2), ("max", 2)];
+
+// `max_drawdown`, `MaxDrawdown` and `maxdrawdown` all name the same metric, ratios can
+// drop their suffix (`sharpe`, `sortino`) and custom metrics are named without their
+// spaces (`contribution_sma_200`). Only metrics the strategy reports can be used, any
+// other would score every configuration NaN
+fn resolve_variable(identifier: &str, metrics: &[Metric]) -> Result<Variable, Error> {
+    let normalized = normalize(identifier);
+
+    if normalized == "trades" || normalized == "tradecount" {
+        return Ok(Variable::TradeCount);
+    }
+
+    for metric in metrics {
+        let key = normalize(&metric.key());
+
+        if key == normalized || key == format!("{}ratio", normalized) {
+            return Ok(Variable::Metric(metric.clone()));
+        }
+    }
+
+    Err(invalid_input(format!(
+        "Unknown metric {}, the strategy reports {}",
+        identifier,
+        metrics
+            .iter()
+            .map(|metric| metric.key())
+            .collect::<Vec<String>>()
+            .join(", ")
+    )))
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|character| character.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f32),
+    Identifier(String),
+    Operator(char),
+    OpenParenthesis,
+    CloseParenthesis,
+    Comma,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, Error> {
+    let characters: Vec<char> = source.chars().collect();
+    let mut tokens: Vec<Token> = vec![];
+    let mut index = 0;
+
+    while index < characters.len() {
+        let character = characters[index];
+
+        if character.is_whitespace() {
+            index += 1;
+            continue;
+        }
+
+        if character.is_ascii_digit() || character == '.' {
+            let start = index;
+
+            while index < characters.len()
+                && (characters[index].is_ascii_digit() || characters[index] == '.')
+            {
+                index += 1;
+            }
+
+            let literal: String = characters[start..index].iter().collect();
+            let value = literal
+                .parse::<f32>()
+                .map_err(|_| invalid_input(format!("Invalid number {}", literal)))?;
+
+            tokens.push(Token::Number(value));
+            continue;
+        }
+
+        if character.is_alphabetic() || character == '_' {
+            let start = index;
+
+            while index < characters.len()
+                && (characters[index].is_alphanumeric() || characters[index] == '_')
+            {
+                index += 1;
+            }
+
+            tokens.push(Token::Identifier(characters[start..index].iter().collect()));
+            continue;
+        }
+
+        tokens.push(match character {
+            '+' | '-' | '*' | '/' | '^' => Token::Operator(character),
+            '(' => Token::OpenParenthesis,
+            ')' => Token::CloseParenthesis,
+            ',' => Token::Comma,
+            _ => return Err(invalid_input(format!("Unexpected character {}", character))),
+        });
+        index += 1;
+    }
+
+    Ok(tokens)
+}
+
+// Precedence climbing over `+ -`, `* /`, unary minus then right associative `^`
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    metrics: &'a [Metric],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), Error> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            token => Err(invalid_input(format!(
+                "Expected {:?}, found {:?}",
+                expected, token
+            ))),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, Error> {
+        let mut left = self.term()?;
+
+        while let Some(Token::Operator(operator @ ('+' | '-'))) = self.peek().cloned() {
+            self.position += 1;
+            left = Expression::Binary(operator, Box::new(left), Box::new(self.term()?));
+        }
+
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expression, Error> {
+        let mut left = self.unary()?;
+
+        while let Some(Token::Operator(operator @ ('*' | '/'))) = self.peek().cloned() {
+            self.position += 1;
+            left = Expression::Binary(operator, Box::new(left), Box::new(self.unary()?));
+        }
+
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expression, Error> {
+        if let Some(Token::Operator('-')) = self.peek() {
+            self.position += 1;
+            return Ok(Expression::Negate(Box::new(self.unary()?)));
+        }
+
+        let base = self.primary()?;
+
+        if let Some(Token::Operator('^')) = self.peek() {
+            self.position += 1;
+            return Ok(Expression::Binary(
+                '^',
+                Box::new(base),
+                Box::new(self.unary()?),
+            ));
+        }
+
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expression, Error> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expression::Number(value)),
+            Some(Token::OpenParenthesis) => {
+                let expression = self.expression()?;
+                self.expect(Token::CloseParenthesis)?;
+                Ok(expression)
+            }
+            Some(Token::Identifier(identifier)) => {
+                if self.peek() != Some(&Token::OpenParenthesis) {
+                    return Ok(Expression::Variable(resolve_variable(
+                        &identifier,
+                        self.metrics,
+                    )?));
+                }
+
+                self.position += 1;
+
+                let name = identifier.to_lowercase();
+                let arity = FUNCTIONS
+                    .iter()
+                    .find(|(function, _)| *function == name)
+                    .map(|(_, arity)| *arity)
+                    .ok_or_else(|| invalid_input(format!("Unknown function {}", identifier)))?;
+
+                let mut arguments: Vec<Expression> = vec![self.expression()?];
+
+                while self.peek() == Some(&Token::Comma) {
+                    self.position += 1;
+                    arguments.push(self.expression()?);
+                }
+
+                self.expect(Token::CloseParenthesis)?;
+
+                if arguments.len() < arity || (arity == 1 && arguments.len() > 1) {
+                    return Err(invalid_input(format!(
+                        "{} takes {} argument(s)",
+                        name, arity
+                    )));
+                }
+
+                Ok(Expression::Function(name, arguments))
+            }
+            Some(token) => Err(invalid_input(format!("Unexpected {:?}", token))),
+            None => Err(invalid_input("Unexpected end of expression".into())),
+        }
+    }
+}
+
+fn invalid_input(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
@@ -0,0 +1,212 @@
+use crate::library::engines::{
+    cross_validation::combinations, optimizers::grid::OptimizedBacktestResult,
+};
+use std::collections::{BTreeSet, HashMap};
+
+// Combinatorially symmetric cross-validation (Bailey, Borwein, Lopez de Prado, Zhu): the
+// per-period returns of every configuration are cut in blocks, every half of the blocks
+// picks an in-sample winner by sharpe ratio and the other half ranks it out-of-sample
+pub struct BacktestOverfittingReport {
+    // Share of the splits where the in-sample winner ranks below the out-of-sample median
+    pub probability_of_overfitting: f32,
+    // ln(w / (1 - w)) of the winner's relative out-of-sample rank w, one per split
+    pub logits: Vec<f32>,
+    // Sharpe ratio of the in-sample winner in and out of sample, one pair per split
+    pub in_sample_performances: Vec<f32>,
+    pub out_of_sample_performances: Vec<f32>,
+    // Least squares fit of the out-of-sample sharpe on the in-sample one, a slope well
+    // below 1 means the in-sample edge mostly doesn't carry over
+    pub degradation_slope: Option<f32>,
+    pub degradation_intercept: Option<f32>,
+    // Share of the splits where the in-sample winner loses money out-of-sample
+    pub probability_of_loss: f32,
+}
+
+/// Runs CSCV over the evaluated configurations with `blocks` blocks, which is rounded
+/// down to an even number. `None` with less than two configurations, too few periods or
+/// when a configuration was read back from a study without its series.
+pub fn probability_of_backtest_overfitting(
+    optimized_backtest_results: &[OptimizedBacktestResult],
+    blocks: usize,
+) -> Option<BacktestOverfittingReport> {
+    // Dropping those configurations would run CSCV on a subset of the trials
+    if optimized_backtest_results
+        .iter()
+        .any(|optimized_backtest_result| optimized_backtest_result.backtest_result.is_from_study())
+    {
+        return None;
+    }
+
+    let blocks = blocks - blocks % 2;
+    let returns = returns_matrix(optimized_backtest_results);
+    let periods = returns
+        .first()
+        .map_or(0, |config_returns| config_returns.len());
+
+    if returns.len() < 2 || blocks < 2 || periods < blocks * 2 {
+        return None;
+    }
+
+    // Sum, sum of squares and count of every block of every configuration, so a split
+    // only adds up block statistics instead of walking the returns again
+    let block_statistics: Vec<Vec<(f64, f64, usize)>> = returns
+        .iter()
+        .map(|config_returns| {
+            (0..blocks)
+                .map(|block| {
+                    let block_returns =
+                        &config_returns[block * periods / blocks..(block + 1) * periods / blocks];
+
+                    block_returns
+                        .iter()
+                        .fold((0.0, 0.0, 0), |(sum, squares, count), r| {
+                            let r = *r as f64;
+                            (sum + r, squares + r * r, count + 1)
+                        })
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut logits: Vec<f32> = vec![];
+    let mut in_sample_performances: Vec<f32> = vec![];
+    let mut out_of_sample_performances: Vec<f32> = vec![];
+
+    for in_sample_blocks in combinations(blocks, blocks / 2) {
+        let out_of_sample_blocks: Vec<usize> = (0..blocks)
+            .filter(|block| !in_sample_blocks.contains(block))
+            .collect();
+
+        let in_sample: Vec<f32> = block_statistics
+            .iter()
+            .map(|statistics| sharpe_ratio(statistics, &in_sample_blocks))
+            .collect();
+        let out_of_sample: Vec<f32> = block_statistics
+            .iter()
+            .map(|statistics| sharpe_ratio(statistics, &out_of_sample_blocks))
+            .collect();
+
+        let winner = (0..in_sample.len())
+            .max_by(|&a, &b| in_sample[a].total_cmp(&in_sample[b]))
+            .unwrap();
+
+        // Relative rank in (0, 1), ties share the mean of their ranks
+        let below = out_of_sample
+            .iter()
+            .filter(|&&performance| performance < out_of_sample[winner])
+            .count();
+        let equal = out_of_sample
+            .iter()
+            .filter(|&&performance| performance == out_of_sample[winner])
+            .count();
+        let rank = below as f32 + (equal as f32 + 1.0) / 2.0;
+        let relative_rank = rank / (out_of_sample.len() as f32 + 1.0);
+
+        logits.push((relative_rank / (1.0 - relative_rank)).ln());
+        in_sample_performances.push(in_sample[winner]);
+        out_of_sample_performances.push(out_of_sample[winner]);
+    }
+
+    let splits = logits.len() as f32;
+    let (degradation_slope, degradation_intercept) =
+        match linear_fit(&in_sample_performances, &out_of_sample_performances) {
+            Some((slope, intercept)) => (Some(slope), Some(intercept)),
+            None => (None, None),
+        };
+
+    Some(BacktestOverfittingReport {
+        probability_of_overfitting: logits.iter().filter(|&&logit| logit <= 0.0).count() as f32
+            / splits,
+        probability_of_loss: out_of_sample_performances
+            .iter()
+            .filter(|&&performance| performance < 0.0)
+            .count() as f32
+            / splits,
+        logits,
+        in_sample_performances,
+        out_of_sample_performances,
+        degradation_slope,
+        degradation_intercept,
+    })
+}
+
+// One row of per-period returns per configuration on the union of their timestamps,
+// periods a configuration has no equity for (e.g. its indicators warming up) are flat
+fn returns_matrix(optimized_backtest_results: &[OptimizedBacktestResult]) -> Vec<Vec<f32>> {
+    let configs_returns: Vec<HashMap<i64, f32>> = optimized_backtest_results
+        .iter()
+        .map(|optimized_backtest_result| {
+            let equity_series = optimized_backtest_result.backtest_result.equity_series();
+
+            equity_series
+                .iter()
+                .skip(1)
+                .zip(optimized_backtest_result.backtest_result.equity_returns())
+                .map(|(equity_point, r)| (equity_point.timestamp, r))
+                .collect()
+        })
+        .filter(|config_returns: &HashMap<i64, f32>| !config_returns.is_empty())
+        .collect();
+
+    let timestamps: BTreeSet<i64> = configs_returns
+        .iter()
+        .flat_map(|config_returns| config_returns.keys().copied())
+        .collect();
+
+    configs_returns
+        .iter()
+        .map(|config_returns| {
+            timestamps
+                .iter()
+                .map(|timestamp| config_returns.get(timestamp).copied().unwrap_or(0.0))
+                .collect()
+        })
+        .collect()
+}
+
+fn sharpe_ratio(block_statistics: &[(f64, f64, usize)], blocks: &[usize]) -> f32 {
+    let (sum, squares, count) = blocks
+        .iter()
+        .fold((0.0, 0.0, 0), |(sum, squares, count), &b| {
+            let (block_sum, block_squares, block_count) = block_statistics[b];
+            (
+                sum + block_sum,
+                squares + block_squares,
+                count + block_count,
+            )
+        });
+
+    if count < 2 {
+        return 0.0;
+    }
+
+    let mean = sum / count as f64;
+    let variance = (squares - count as f64 * mean * mean) / (count - 1) as f64;
+
+    if variance <= 0.0 {
+        return 0.0;
+    }
+
+    (mean / variance.sqrt()) as f32
+}
+
+fn linear_fit(x: &[f32], y: &[f32]) -> Option<(f32, f32)> {
+    let n = x.len() as f32;
+    let x_mean = x.iter().sum::<f32>() / n;
+    let y_mean = y.iter().sum::<f32>() / n;
+
+    let covariance: f32 = x
+        .iter()
+        .zip(y.iter())
+        .map(|(x, y)| (x - x_mean) * (y - y_mean))
+        .sum();
+    let variance: f32 = x.iter().map(|x| (x - x_mean).powi(2)).sum();
+
+    if x.len() < 2 || variance == 0.0 {
+        return None;
+    }
+
+    let slope = covariance / variance;
+
+    Some((slope, y_mean - slope * x_mean))
+}
@@ -0,0 +1,191 @@
+use crate::{
+    library::engines::optimizers::grid::OptimizedBacktestResult,
+    user::strategies::{BacktestResult, Metric},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObjectiveDirection {
+    Maximize,
+    Minimize,
+}
+
+pub type ObjectiveEvaluator = Arc<dyn Fn(&BacktestResult) -> Option<f32> + Send + Sync>;
+
+#[derive(Clone)]
+pub struct Objective {
+    pub name: String,
+    pub direction: ObjectiveDirection,
+    pub evaluate: ObjectiveEvaluator,
+}
+
+impl Objective {
+    pub fn metric(metric: Metric, direction: ObjectiveDirection) -> Self {
+        return Self {
+            name: metric.key(),
+            direction,
+            evaluate: Arc::new(move |backtest_result| {
+                backtest_result.metrics().get(&metric).copied()
+            }),
+        };
+    }
+
+    pub fn trade_count(direction: ObjectiveDirection) -> Self {
+        return Self {
+            name: "TradeCount".into(),
+            direction,
+            evaluate: Arc::new(|backtest_result| Some(backtest_result.trades().len() as f32)),
+        };
+    }
+
+    /// Objective value turned into a maximization, missing values rank last.
+    pub fn oriented_value(&self, backtest_result: &BacktestResult) -> f32 {
+        match (self.evaluate)(backtest_result) {
+            Some(value) if !value.is_nan() => match self.direction {
+                ObjectiveDirection::Maximize => value,
+                ObjectiveDirection::Minimize => -value,
+            },
+            _ => f32::NEG_INFINITY,
+        }
+    }
+}
+
+/// `a` dominates `b` when it is no worse on every maximized objective and better on one.
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    let mut strictly_better = false;
+
+    for (a_value, b_value) in a.iter().zip(b.iter()) {
+        if a_value < b_value {
+            return false;
+        }
+
+        if a_value > b_value {
+            strictly_better = true;
+        }
+    }
+
+    strictly_better
+}
+
+/// Fast non-dominated sort of NSGA-II over maximized objective vectors, returns the
+/// indices of each front, the Pareto front first.
+pub fn non_dominated_sort(values: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let mut dominated_by: Vec<Vec<usize>> = vec![vec![]; values.len()];
+    let mut domination_counts: Vec<usize> = vec![0; values.len()];
+    let mut fronts: Vec<Vec<usize>> = vec![];
+    let mut current_front: Vec<usize> = vec![];
+
+    for i in 0..values.len() {
+        for j in (i + 1)..values.len() {
+            if dominates(&values[i], &values[j]) {
+                dominated_by[i].push(j);
+                domination_counts[j] += 1;
+            } else if dominates(&values[j], &values[i]) {
+                dominated_by[j].push(i);
+                domination_counts[i] += 1;
+            }
+        }
+    }
+
+    for i in 0..values.len() {
+        if domination_counts[i] == 0 {
+            current_front.push(i);
+        }
+    }
+
+    while !current_front.is_empty() {
+        let mut next_front: Vec<usize> = vec![];
+
+        for &i in &current_front {
+            for &j in &dominated_by[i] {
+                domination_counts[j] -= 1;
+
+                if domination_counts[j] == 0 {
+                    next_front.push(j);
+                }
+            }
+        }
+
+        fronts.push(current_front);
+        current_front = next_front;
+    }
+
+    fronts
+}
+
+/// Crowding distance of every member of `front`, boundary members get infinity so
+/// the extremes of the front are always kept.
+pub fn crowding_distances(values: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+    let mut distances: Vec<f32> = vec![0.0; front.len()];
+
+    if front.len() <= 2 {
+        return vec![f32::INFINITY; front.len()];
+    }
+
+    let objectives_count = values[front[0]].len();
+
+    for objective_index in 0..objectives_count {
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|&a, &b| {
+            values[front[a]][objective_index].total_cmp(&values[front[b]][objective_index])
+        });
+
+        let lowest = values[front[order[0]]][objective_index];
+        let highest = values[front[order[order.len() - 1]]][objective_index];
+
+        distances[order[0]] = f32::INFINITY;
+        distances[order[order.len() - 1]] = f32::INFINITY;
+
+        let span = highest - lowest;
+
+        if !span.is_finite() || span == 0.0 {
+            continue;
+        }
+
+        for window in 1..(order.len() - 1) {
+            let previous = values[front[order[window - 1]]][objective_index];
+            let next = values[front[order[window + 1]]][objective_index];
+
+            distances[order[window]] += (next - previous) / span;
+        }
+    }
+
+    distances
+}
+
+/// Evaluates the objectives on every result and marks it with its Pareto rank, 0 being
+/// the non-dominated front.
+pub fn assign_pareto_ranks(
+    optimized_backtest_results: &mut [OptimizedBacktestResult],
+    objectives: &[Objective],
+) {
+    let values: Vec<Vec<f32>> = optimized_backtest_results
+        .iter()
+        .map(|optimized_backtest_result| {
+            objectives
+                .iter()
+                .map(|objective| {
+                    objective.oriented_value(&optimized_backtest_result.backtest_result)
+                })
+                .collect()
+        })
+        .collect();
+
+    for (rank, front) in non_dominated_sort(&values).into_iter().enumerate() {
+        for index in front {
+            let optimized_backtest_result = &mut optimized_backtest_results[index];
+
+            optimized_backtest_result.objective_values = objectives
+                .iter()
+                .map(|objective| {
+                    (
+                        objective.name.clone(),
+                        (objective.evaluate)(&optimized_backtest_result.backtest_result),
+                    )
+                })
+                .collect();
+            optimized_backtest_result.pareto_rank = Some(rank);
+        }
+    }
+}
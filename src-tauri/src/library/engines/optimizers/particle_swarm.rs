@@ -0,0 +1,143 @@
+use crate::{
+    library::engines::optimizers::{
+        evaluate_unit_points, finalize_results, grid::OptimizedBacktestResult,
+        progress::OPTIMIZATION_MONITOR, space::OptimizationSpace, study::data_fingerprint,
+        Optimizer,
+    },
+    user::strategies::IStrategy,
+    utils::random::SeededRng,
+};
+use std::{collections::HashMap, io::Error};
+
+// Global-best particle swarm over the unit hypercube, every particle of an iteration is
+// backtested together
+pub struct ParticleSwarmOptimizer {
+    pub particles: usize,
+    pub iterations: usize,
+    pub inertia: f32,
+    // Pull towards the particle's own best position
+    pub cognitive_weight: f32,
+    // Pull towards the best position of the swarm
+    pub social_weight: f32,
+    // Largest move of a particle along one dimension in a single iteration
+    pub max_velocity: f32,
+    pub seed: u64,
+}
+
+impl ParticleSwarmOptimizer {
+    pub fn new(particles: usize, iterations: usize, seed: u64) -> Self {
+        return Self {
+            particles,
+            iterations,
+            inertia: 0.7,
+            cognitive_weight: 1.5,
+            social_weight: 1.5,
+            max_velocity: 0.2,
+            seed,
+        };
+    }
+}
+
+impl Optimizer for ParticleSwarmOptimizer {
+    fn optimize(
+        &self,
+        strategy: &Box<dyn IStrategy>,
+        space: &OptimizationSpace,
+    ) -> Result<Vec<OptimizedBacktestResult>, Error> {
+        space.validate()?;
+
+        let mut backtest_results: Vec<OptimizedBacktestResult> = vec![];
+
+        if space.is_empty() || self.particles == 0 {
+            return Ok(backtest_results);
+        }
+
+        let data_fingerprint = data_fingerprint(strategy);
+
+        let dimensions = space.parameters.len();
+        OPTIMIZATION_MONITOR.plan_evaluations(strategy, self.particles * (self.iterations + 1));
+
+        let mut rng = SeededRng::new(self.seed);
+        let mut scores: HashMap<Vec<usize>, f32> = HashMap::new();
+
+        let mut positions: Vec<Vec<f32>> = (0..self.particles)
+            .map(|_| (0..dimensions).map(|_| rng.next_f32()).collect())
+            .collect();
+        let mut velocities: Vec<Vec<f32>> = (0..self.particles)
+            .map(|_| {
+                (0..dimensions)
+                    .map(|_| rng.range_f32(-self.max_velocity, self.max_velocity))
+                    .collect()
+            })
+            .collect();
+
+        let mut personal_bests: Vec<(Vec<f32>, f32)> = vec![];
+        let mut global_best: (Vec<f32>, f32) = (positions[0].clone(), f32::NEG_INFINITY);
+
+        for iteration in 0..=self.iterations {
+            if OPTIMIZATION_MONITOR.is_cancelled(strategy) {
+                break;
+            }
+
+            if iteration > 0 {
+                for particle in 0..self.particles {
+                    for dimension in 0..dimensions {
+                        let position = positions[particle][dimension];
+                        let cognitive = self.cognitive_weight
+                            * rng.next_f32()
+                            * (personal_bests[particle].0[dimension] - position);
+                        let social = self.social_weight
+                            * rng.next_f32()
+                            * (global_best.0[dimension] - position);
+
+                        let velocity =
+                            (self.inertia * velocities[particle][dimension] + cognitive + social)
+                                .clamp(-self.max_velocity, self.max_velocity);
+
+                        // Particles bounce off the walls of the hypercube
+                        let (position, velocity) = match position + velocity {
+                            moved if moved < 0.0 => (-moved, -velocity),
+                            moved if moved > 1.0 => (2.0 - moved, -velocity),
+                            moved => (moved, velocity),
+                        };
+
+                        positions[particle][dimension] = position.clamp(0.0, 1.0);
+                        velocities[particle][dimension] = velocity;
+                    }
+                }
+            }
+
+            let iteration_scores = evaluate_unit_points(
+                strategy,
+                data_fingerprint,
+                space,
+                &positions,
+                iteration,
+                &mut scores,
+                &mut backtest_results,
+            );
+
+            for (particle, score) in iteration_scores.into_iter().enumerate() {
+                let fitness = if score.is_nan() {
+                    f32::NEG_INFINITY
+                } else {
+                    score
+                };
+
+                if particle >= personal_bests.len() {
+                    personal_bests.push((positions[particle].clone(), fitness));
+                } else if fitness > personal_bests[particle].1 {
+                    personal_bests[particle] = (positions[particle].clone(), fitness);
+                }
+
+                if fitness > global_best.1 {
+                    global_best = (positions[particle].clone(), fitness);
+                }
+            }
+        }
+
+        finalize_results(strategy, &mut backtest_results);
+
+        Ok(backtest_results)
+    }
+}
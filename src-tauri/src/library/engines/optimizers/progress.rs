@@ -0,0 +1,211 @@
+use crate::user::strategies::IStrategy;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, LazyLock, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+pub static OPTIMIZATION_MONITOR: LazyLock<OptimizationMonitor> =
+    LazyLock::new(OptimizationMonitor::new);
+
+// Evaluations can take microseconds, progress is reported at most this often
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OptimizationProgress {
+    pub completed: usize,
+    // Evaluations planned so far, iterative optimizers plan theirs up front while
+    // successive halving adds every rung as it starts
+    pub total: usize,
+    pub best_score: Option<f32>,
+    pub elapsed_seconds: f32,
+    pub eta_seconds: Option<f32>,
+    pub cancelled: bool,
+    pub finished: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        return self.cancelled.load(Ordering::SeqCst);
+    }
+}
+
+type ProgressCallback = Box<dyn Fn(OptimizationProgress) + Send + Sync>;
+
+struct OptimizationRun {
+    token: CancellationToken,
+    on_progress: Option<ProgressCallback>,
+    started_at: Instant,
+    state: Mutex<RunState>,
+}
+
+struct RunState {
+    completed: usize,
+    total: usize,
+    best_score: Option<f32>,
+    last_report: Option<Instant>,
+}
+
+impl OptimizationRun {
+    fn progress(&self, state: &RunState, finished: bool) -> OptimizationProgress {
+        let elapsed_seconds = self.started_at.elapsed().as_secs_f32();
+        let remaining = state.total.saturating_sub(state.completed);
+
+        OptimizationProgress {
+            completed: state.completed,
+            total: state.total,
+            best_score: state.best_score,
+            elapsed_seconds,
+            eta_seconds: if finished {
+                Some(0.0)
+            } else if state.completed > 0 {
+                Some(elapsed_seconds / state.completed as f32 * remaining as f32)
+            } else {
+                None
+            },
+            cancelled: self.token.is_cancelled(),
+            finished,
+        }
+    }
+
+    fn report(&self, progress: OptimizationProgress) {
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(progress);
+        }
+    }
+}
+
+// Runs are keyed by an id the strategy carries in its overrides, so the clones an
+// optimizer backtests (other data, another objective) report to the run the caller
+// started while two runs of the same strategy stay apart
+pub struct OptimizationMonitor {
+    runs: Mutex<HashMap<String, Arc<OptimizationRun>>>,
+}
+
+impl OptimizationMonitor {
+    pub fn new() -> Self {
+        return Self {
+            runs: Mutex::new(HashMap::new()),
+        };
+    }
+
+    /// Starts tracking the optimizations of strategies set to `run_id` until `finish`,
+    /// the returned token stops them. Evaluations outside a run are neither reported nor
+    /// cancellable.
+    pub fn start<F>(&self, run_id: &str, on_progress: Option<F>) -> CancellationToken
+    where
+        F: Fn(OptimizationProgress) + Send + Sync + 'static,
+    {
+        let token = CancellationToken::new();
+
+        self.runs.lock().unwrap().insert(
+            run_id.into(),
+            Arc::new(OptimizationRun {
+                token: token.clone(),
+                on_progress: on_progress.map(|f| Box::new(f) as ProgressCallback),
+                started_at: Instant::now(),
+                state: Mutex::new(RunState {
+                    completed: 0,
+                    total: 0,
+                    best_score: None,
+                    last_report: None,
+                }),
+            }),
+        );
+
+        token
+    }
+
+    /// Reports the final progress of the run and stops tracking it.
+    pub fn finish(&self, run_id: &str) -> Option<OptimizationProgress> {
+        let run = self.runs.lock().unwrap().remove(run_id)?;
+        let progress = run.progress(&run.state.lock().unwrap(), true);
+
+        run.report(progress.clone());
+
+        Some(progress)
+    }
+
+    /// Cancels the run, false when it isn't in progress.
+    pub fn cancel(&self, run_id: &str) -> bool {
+        match self.runs.lock().unwrap().get(run_id) {
+            Some(run) => {
+                run.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_cancelled(&self, strategy: &Box<dyn IStrategy>) -> bool {
+        self.run(strategy)
+            .map_or(false, |run| run.token.is_cancelled())
+    }
+
+    pub fn plan_evaluations(&self, strategy: &Box<dyn IStrategy>, count: usize) {
+        if let Some(run) = self.run(strategy) {
+            run.state.lock().unwrap().total += count;
+        }
+    }
+
+    /// Counts `count` evaluations as done, `score` is the one that was backtested if any.
+    /// Points an optimizer skips as already evaluated are completed without a score.
+    pub fn complete_evaluations(
+        &self,
+        strategy: &Box<dyn IStrategy>,
+        count: usize,
+        score: Option<f32>,
+    ) {
+        let run = match self.run(strategy) {
+            Some(run) => run,
+            None => return,
+        };
+
+        let progress = {
+            let mut state = run.state.lock().unwrap();
+
+            state.completed += count;
+            state.total = state.total.max(state.completed);
+
+            if let Some(score) = score.filter(|score| !score.is_nan()) {
+                state.best_score = Some(state.best_score.map_or(score, |best| best.max(score)));
+            }
+
+            let now = Instant::now();
+            if state
+                .last_report
+                .map_or(false, |last_report| now - last_report < REPORT_INTERVAL)
+            {
+                return;
+            }
+
+            state.last_report = Some(now);
+            run.progress(&state, false)
+        };
+
+        run.report(progress);
+    }
+
+    fn run(&self, strategy: &Box<dyn IStrategy>) -> Option<Arc<OptimizationRun>> {
+        let run_id = strategy.optimization_run()?;
+
+        self.runs.lock().unwrap().get(run_id).cloned()
+    }
+}
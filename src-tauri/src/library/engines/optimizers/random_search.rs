@@ -0,0 +1,61 @@
+use crate::{
+    library::engines::optimizers::{
+        evaluate_points, finalize_results, grid::OptimizedBacktestResult, space::OptimizationSpace,
+        study::data_fingerprint, Optimizer,
+    },
+    user::strategies::IStrategy,
+    utils::random::SeededRng,
+};
+use std::{collections::HashSet, io::Error};
+
+pub struct RandomSearchOptimizer {
+    pub budget: usize,
+    pub seed: u64,
+}
+
+impl RandomSearchOptimizer {
+    pub fn new(budget: usize, seed: u64) -> Self {
+        return Self { budget, seed };
+    }
+}
+
+impl Optimizer for RandomSearchOptimizer {
+    fn optimize(
+        &self,
+        strategy: &Box<dyn IStrategy>,
+        space: &OptimizationSpace,
+    ) -> Result<Vec<OptimizedBacktestResult>, Error> {
+        space.validate()?;
+
+        if space.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let data_fingerprint = data_fingerprint(strategy);
+
+        let budget = self.budget.min(space.total_combinations());
+
+        let mut rng = SeededRng::new(self.seed);
+        let mut seen: HashSet<Vec<usize>> = HashSet::new();
+        let mut points: Vec<Vec<usize>> = vec![];
+
+        // Log-uniform draws concentrate on few steps and constraints reject some draws,
+        // so both are retried a bounded number of times
+        let mut attempts = 0;
+        while points.len() < budget && attempts < budget.saturating_mul(20) {
+            attempts += 1;
+
+            let mut point = space.sample_point(&mut rng);
+
+            if space.resolve(&mut point).is_some() && seen.insert(point.clone()) {
+                points.push(point);
+            }
+        }
+
+        let mut backtest_results = evaluate_points(strategy, data_fingerprint, space, points);
+
+        finalize_results(strategy, &mut backtest_results);
+
+        Ok(backtest_results)
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{
+    library::engines::optimizers::{grid::OptimizedBacktestResult, space::numeric_value},
+    user::composer::CompositionDataType,
+    utils::classes::charting::{ChartingData, HeatmapChartingData},
+};
+use std::collections::HashMap;
+
+// Numeric parameters are placed on the grid of the distinct values the results took, so
+// sampled optimizers get neighbourhoods too; non numeric parameters must match exactly
+struct ParameterGrid {
+    numeric_values: HashMap<String, Vec<f32>>,
+}
+
+impl ParameterGrid {
+    fn new(optimized_backtest_results: &[OptimizedBacktestResult]) -> Self {
+        let mut numeric_values: HashMap<String, Vec<f32>> = HashMap::new();
+
+        for optimized_backtest_result in optimized_backtest_results {
+            for (name, value) in &optimized_backtest_result.optimized_parameters {
+                if let (Some(value), false) = (numeric_value(value), is_categorical(value)) {
+                    numeric_values.entry(name.clone()).or_default().push(value);
+                }
+            }
+        }
+
+        for values in numeric_values.values_mut() {
+            values.sort_by(|a, b| a.total_cmp(b));
+            values.dedup();
+        }
+
+        return Self { numeric_values };
+    }
+
+    fn position(&self, name: &str, value: &CompositionDataType) -> Option<usize> {
+        let value = numeric_value(value)?;
+
+        self.numeric_values
+            .get(name)?
+            .binary_search_by(|probe| probe.total_cmp(&value))
+            .ok()
+    }
+
+    fn are_neighbours(
+        &self,
+        a: &HashMap<String, CompositionDataType>,
+        b: &HashMap<String, CompositionDataType>,
+        radius: usize,
+    ) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+
+        for (name, a_value) in a {
+            let b_value = match b.get(name) {
+                Some(b_value) => b_value,
+                None => return false,
+            };
+
+            match (self.position(name, a_value), self.position(name, b_value)) {
+                (Some(a_position), Some(b_position)) => {
+                    if a_position.abs_diff(b_position) > radius {
+                        return false;
+                    }
+                }
+                _ => {
+                    if !same_value(a_value, b_value) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// Scores every result with the mean score of the results within `radius` grid steps
+/// on each numeric parameter, itself included, so isolated spikes rank below plateaus.
+pub fn attach_robustness_scores(
+    optimized_backtest_results: &mut [OptimizedBacktestResult],
+    radius: usize,
+) {
+    let parameter_grid = ParameterGrid::new(optimized_backtest_results);
+
+    let robustness_scores: Vec<Option<f32>> = optimized_backtest_results
+        .iter()
+        .map(|optimized_backtest_result| {
+            let neighbour_scores: Vec<f32> = optimized_backtest_results
+                .iter()
+                .filter(|neighbour| {
+                    parameter_grid.are_neighbours(
+                        &optimized_backtest_result.optimized_parameters,
+                        &neighbour.optimized_parameters,
+                        radius,
+                    )
+                })
+                .map(|neighbour| neighbour.score)
+                .filter(|score| !score.is_nan())
+                .collect();
+
+            if neighbour_scores.is_empty() {
+                return None;
+            }
+
+            Some(neighbour_scores.iter().sum::<f32>() / neighbour_scores.len() as f32)
+        })
+        .collect();
+
+    for (optimized_backtest_result, robustness_score) in
+        optimized_backtest_results.iter_mut().zip(robustness_scores)
+    {
+        optimized_backtest_result.robustness_score = robustness_score;
+    }
+}
+
+/// Indices of the results from the best optimization target down, NaN last.
+pub fn rank_by_score(optimized_backtest_results: &[OptimizedBacktestResult]) -> Vec<usize> {
+    rank_by(optimized_backtest_results, |optimized_backtest_result| {
+        Some(optimized_backtest_result.score)
+    })
+}
+
+/// Indices of the results from the best robustness score down, missing scores last.
+pub fn rank_by_robustness(optimized_backtest_results: &[OptimizedBacktestResult]) -> Vec<usize> {
+    rank_by(optimized_backtest_results, |optimized_backtest_result| {
+        optimized_backtest_result.robustness_score
+    })
+}
+
+/// Heatmap of the best score over every `(x, y)` pair of values, the other parameters
+/// being free. `None` when either parameter isn't numeric in the results.
+pub fn score_surface(
+    optimized_backtest_results: &[OptimizedBacktestResult],
+    x_parameter: &str,
+    y_parameter: &str,
+) -> Option<ChartingData> {
+    let parameter_grid = ParameterGrid::new(optimized_backtest_results);
+
+    let x_values = parameter_grid.numeric_values.get(x_parameter)?.clone();
+    let y_values = parameter_grid.numeric_values.get(y_parameter)?.clone();
+
+    let mut data: Vec<Vec<Option<f32>>> = vec![vec![None; x_values.len()]; y_values.len()];
+
+    for optimized_backtest_result in optimized_backtest_results {
+        if optimized_backtest_result.score.is_nan() {
+            continue;
+        }
+
+        let parameters = &optimized_backtest_result.optimized_parameters;
+
+        let x_position = parameters
+            .get(x_parameter)
+            .and_then(|value| parameter_grid.position(x_parameter, value));
+        let y_position = parameters
+            .get(y_parameter)
+            .and_then(|value| parameter_grid.position(y_parameter, value));
+
+        if let (Some(x_position), Some(y_position)) = (x_position, y_position) {
+            let cell = &mut data[y_position][x_position];
+
+            *cell = Some(match cell {
+                Some(best_score) => best_score.max(optimized_backtest_result.score),
+                None => optimized_backtest_result.score,
+            });
+        }
+    }
+
+    Some(ChartingData::HeatmapChartingData(HeatmapChartingData {
+        chart_type: "heatmap".into(),
+        height: None,
+        pane: None,
+        title: Some(format!("{} / {}", x_parameter, y_parameter)),
+        x_label: x_parameter.into(),
+        y_label: y_parameter.into(),
+        x_values,
+        y_values,
+        data,
+    }))
+}
+
+/// Score surfaces of every pair of numeric parameters.
+pub fn score_surfaces(optimized_backtest_results: &[OptimizedBacktestResult]) -> Vec<ChartingData> {
+    let parameter_grid = ParameterGrid::new(optimized_backtest_results);
+
+    let mut parameters: Vec<&String> = parameter_grid.numeric_values.keys().collect();
+    parameters.sort();
+
+    let mut charting_data: Vec<ChartingData> = vec![];
+
+    for (x_index, x_parameter) in parameters.iter().enumerate() {
+        for y_parameter in parameters.iter().skip(x_index + 1) {
+            if let Some(surface) =
+                score_surface(optimized_backtest_results, x_parameter, y_parameter)
+            {
+                charting_data.push(surface);
+            }
+        }
+    }
+
+    charting_data
+}
+
+fn rank_by(
+    optimized_backtest_results: &[OptimizedBacktestResult],
+    key: impl Fn(&OptimizedBacktestResult) -> Option<f32>,
+) -> Vec<usize> {
+    let keys: Vec<f32> = optimized_backtest_results
+        .iter()
+        .map(
+            |optimized_backtest_result| match key(optimized_backtest_result) {
+                Some(value) if !value.is_nan() => value,
+                _ => f32::NEG_INFINITY,
+            },
+        )
+        .collect();
+
+    let mut ranking: Vec<usize> = (0..optimized_backtest_results.len()).collect();
+    ranking.sort_by(|&a, &b| keys[b].total_cmp(&keys[a]));
+
+    ranking
+}
+
+pub fn is_categorical(value: &CompositionDataType) -> bool {
+    matches!(
+        value,
+        CompositionDataType::Bool(_) | CompositionDataType::String(_)
+    )
+}
+
+fn same_value(a: &CompositionDataType, b: &CompositionDataType) -> bool {
+    match (a, b) {
+        (CompositionDataType::String(a), CompositionDataType::String(b)) => a == b,
+        (CompositionDataType::Bool(a), CompositionDataType::Bool(b)) => a == b,
+        _ => match (numeric_value(a), numeric_value(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        },
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{
+    library::engines::optimizers::{
+        evaluate_unit_points, finalize_results, grid::OptimizedBacktestResult,
+        progress::OPTIMIZATION_MONITOR, space::OptimizationSpace, study::data_fingerprint,
+        Optimizer,
+    },
+    user::strategies::IStrategy,
+    utils::random::SeededRng,
+};
+use std::{collections::HashMap, io::Error};
+
+// Independent annealing chains over the unit hypercube, every chain proposes one move per
+// iteration and the proposals of all chains are backtested together
+pub struct SimulatedAnnealingOptimizer {
+    pub chains: usize,
+    pub iterations: usize,
+    // Geometric cooling factor applied to the temperature after every iteration
+    pub cooling_rate: f32,
+    // Standard deviation of a move in the unit hypercube at the initial temperature
+    pub step_size: f32,
+    pub seed: u64,
+}
+
+impl SimulatedAnnealingOptimizer {
+    pub fn new(chains: usize, iterations: usize, seed: u64) -> Self {
+        return Self {
+            chains,
+            iterations,
+            cooling_rate: 0.9,
+            step_size: 0.2,
+            seed,
+        };
+    }
+}
+
+impl Optimizer for SimulatedAnnealingOptimizer {
+    fn optimize(
+        &self,
+        strategy: &Box<dyn IStrategy>,
+        space: &OptimizationSpace,
+    ) -> Result<Vec<OptimizedBacktestResult>, Error> {
+        space.validate()?;
+
+        let mut backtest_results: Vec<OptimizedBacktestResult> = vec![];
+
+        if space.is_empty() || self.chains == 0 {
+            return Ok(backtest_results);
+        }
+
+        let data_fingerprint = data_fingerprint(strategy);
+
+        let dimensions = space.parameters.len();
+        OPTIMIZATION_MONITOR.plan_evaluations(strategy, self.chains * (self.iterations + 1));
+
+        let mut rng = SeededRng::new(self.seed);
+        let mut scores: HashMap<Vec<usize>, f32> = HashMap::new();
+
+        let mut positions: Vec<Vec<f32>> = (0..self.chains)
+            .map(|_| (0..dimensions).map(|_| rng.next_f32()).collect())
+            .collect();
+        let mut energies: Vec<f32> = evaluate_unit_points(
+            strategy,
+            data_fingerprint,
+            space,
+            &positions,
+            0,
+            &mut scores,
+            &mut backtest_results,
+        )
+        .into_iter()
+        .map(fitness)
+        .collect();
+
+        // Scores have no fixed scale, the starting temperature is their spread so early
+        // iterations accept most moves whatever the objective
+        let starting_temperature = initial_temperature(&energies);
+        let mut temperature = starting_temperature;
+
+        for iteration in 1..=self.iterations {
+            if OPTIMIZATION_MONITOR.is_cancelled(strategy) {
+                break;
+            }
+
+            // Moves shrink as the system cools, down to a tenth of the initial step
+            let step_size = self.step_size * (temperature / starting_temperature).max(0.1);
+
+            let proposals: Vec<Vec<f32>> = positions
+                .iter()
+                .map(|position| {
+                    position
+                        .iter()
+                        .map(|&unit| (unit + rng.normal() * step_size).clamp(0.0, 1.0))
+                        .collect()
+                })
+                .collect();
+
+            let proposal_energies = evaluate_unit_points(
+                strategy,
+                data_fingerprint,
+                space,
+                &proposals,
+                iteration,
+                &mut scores,
+                &mut backtest_results,
+            );
+
+            for (chain, proposal) in proposals.into_iter().enumerate() {
+                let proposal_energy = fitness(proposal_energies[chain]);
+                let delta = proposal_energy - energies[chain];
+
+                let accepted = if delta >= 0.0 {
+                    true
+                } else if delta.is_finite() && temperature > 0.0 {
+                    rng.chance((delta / temperature).exp())
+                } else {
+                    // Leaving an infeasible point is always accepted, entering one never is
+                    energies[chain] == f32::NEG_INFINITY
+                };
+
+                if accepted {
+                    positions[chain] = proposal;
+                    energies[chain] = proposal_energy;
+                }
+            }
+
+            temperature *= self.cooling_rate;
+        }
+
+        finalize_results(strategy, &mut backtest_results);
+
+        Ok(backtest_results)
+    }
+}
+
+fn fitness(score: f32) -> f32 {
+    if score.is_nan() {
+        f32::NEG_INFINITY
+    } else {
+        score
+    }
+}
+
+fn initial_temperature(energies: &[f32]) -> f32 {
+    let finite: Vec<f32> = energies.iter().copied().filter(|e| e.is_finite()).collect();
+
+    if finite.len() < 2 {
+        return 1.0;
+    }
+
+    let mean = finite.iter().sum::<f32>() / finite.len() as f32;
+    let deviation =
+        (finite.iter().map(|e| (e - mean).powi(2)).sum::<f32>() / (finite.len() - 1) as f32).sqrt();
+
+    if deviation > 0.0 {
+        deviation
+    } else {
+        1.0
+    }
+}
@@ -0,0 +1,261 @@
+use crate::{
+    library::engines::optimizers::grid::OptimizationParameter, user::composer::CompositionDataType,
+    utils::random::SeededRng,
+};
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+pub type CombinationPredicate =
+    Arc<dyn Fn(&HashMap<String, CompositionDataType>) -> bool + Send + Sync>;
+
+pub enum Constraint {
+    // `sum(coefficient * value) <= bound`, ignored while any of its parameters is inactive
+    LinearInequality {
+        terms: Vec<(String, f32)>,
+        bound: f32,
+    },
+    // Receives only the active parameters of the combination
+    Predicate(CombinationPredicate),
+}
+
+impl Constraint {
+    /// Strict `lesser < greater` ordering, e.g. a fast period below a slow period.
+    pub fn less_than(lesser: &str, greater: &str) -> Self {
+        let lesser = lesser.to_string();
+        let greater = greater.to_string();
+
+        return Constraint::Predicate(Arc::new(move |combination| {
+            match (
+                combination.get(&lesser).and_then(numeric_value),
+                combination.get(&greater).and_then(numeric_value),
+            ) {
+                (Some(lesser_value), Some(greater_value)) => lesser_value < greater_value,
+                _ => true,
+            }
+        }));
+    }
+
+    pub fn is_satisfied(&self, combination: &HashMap<String, CompositionDataType>) -> bool {
+        match self {
+            Constraint::LinearInequality { terms, bound } => {
+                let mut total = 0.0;
+
+                for (name, coefficient) in terms {
+                    match combination.get(name).and_then(numeric_value) {
+                        Some(value) => total += coefficient * value,
+                        None => return true,
+                    }
+                }
+
+                total <= *bound
+            }
+            Constraint::Predicate(predicate) => predicate(combination),
+        }
+    }
+}
+
+// `parameter` only exists while the categoric `parent` takes one of `values`
+pub struct ParameterCondition {
+    pub parameter: String,
+    pub parent: String,
+    pub values: Vec<String>,
+}
+
+pub struct OptimizationSpace {
+    pub parameters: Vec<OptimizationParameter>,
+    pub conditions: Vec<ParameterCondition>,
+    pub constraints: Vec<Constraint>,
+}
+
+impl OptimizationSpace {
+    pub fn new(parameters: Vec<OptimizationParameter>) -> Self {
+        return Self {
+            parameters,
+            conditions: vec![],
+            constraints: vec![],
+        };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+
+    /// Size of the unconstrained grid, an upper bound on the feasible combinations.
+    pub fn total_combinations(&self) -> usize {
+        self.parameters
+            .iter()
+            .map(|param| param.cardinality())
+            .fold(1usize, |total, cardinality| {
+                total.saturating_mul(cardinality)
+            })
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(empty_param) = self
+            .parameters
+            .iter()
+            .find(|param| param.cardinality() == 0)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Optimization parameter {} has no values",
+                    empty_param.name()
+                ),
+            ));
+        }
+
+        for condition in &self.conditions {
+            let parameter_position = self.position(&condition.parameter);
+            let parent_position = self.position(&condition.parent);
+
+            let (parameter_position, parent_position) = match (parameter_position, parent_position)
+            {
+                (Some(parameter_position), Some(parent_position)) => {
+                    (parameter_position, parent_position)
+                }
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "Condition on {} references an unknown parameter",
+                            condition.parameter
+                        ),
+                    ))
+                }
+            };
+
+            // Combinations are resolved in declaration order, so a parent must come first
+            if parent_position >= parameter_position {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Parent {} must be declared before {}",
+                        condition.parent, condition.parameter
+                    ),
+                ));
+            }
+
+            match &self.parameters[parent_position] {
+                OptimizationParameter::Categoric(parent) => {
+                    if let Some(value) = condition
+                        .values
+                        .iter()
+                        .find(|value| !parent.categories.contains(value))
+                    {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("{} is not a category of {}", value, condition.parent),
+                        ));
+                    }
+                }
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Parent {} must be categoric", condition.parent),
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the parameter at `param_index` exists given the already resolved parents.
+    pub fn is_active(
+        &self,
+        param_index: usize,
+        combination: &HashMap<String, CompositionDataType>,
+    ) -> bool {
+        let name = self.parameters[param_index].name();
+
+        self.conditions
+            .iter()
+            .filter(|condition| condition.parameter == name)
+            .all(|condition| match combination.get(&condition.parent) {
+                Some(CompositionDataType::String(value)) => condition.values.contains(value),
+                _ => false,
+            })
+    }
+
+    pub fn is_feasible(&self, combination: &HashMap<String, CompositionDataType>) -> bool {
+        self.constraints
+            .iter()
+            .all(|constraint| constraint.is_satisfied(combination))
+    }
+
+    /// Resets the indices of inactive parameters to zero, so points only differing in
+    /// parameters that do not exist collapse together, and returns the combination of
+    /// the active parameters when it satisfies every constraint.
+    pub fn resolve(&self, indices: &mut [usize]) -> Option<HashMap<String, CompositionDataType>> {
+        let mut combination: HashMap<String, CompositionDataType> = HashMap::new();
+
+        for (param_index, param) in self.parameters.iter().enumerate() {
+            if !self.is_active(param_index, &combination) {
+                indices[param_index] = 0;
+                continue;
+            }
+
+            combination.insert(
+                param.name().to_string(),
+                param.value_at(indices[param_index]),
+            );
+        }
+
+        if !self.is_feasible(&combination) {
+            return None;
+        }
+
+        Some(combination)
+    }
+
+    pub fn sample_point(&self, rng: &mut SeededRng) -> Vec<usize> {
+        self.parameters
+            .iter()
+            .map(|param| param.sample_index(rng.next_f32()))
+            .collect()
+    }
+
+    /// Rejection sampling of a resolved feasible point, `None` once `attempts` run out.
+    pub fn sample_feasible_point(
+        &self,
+        rng: &mut SeededRng,
+        attempts: usize,
+    ) -> Option<Vec<usize>> {
+        for _ in 0..attempts {
+            let mut point = self.sample_point(rng);
+
+            if self.resolve(&mut point).is_some() {
+                return Some(point);
+            }
+        }
+
+        None
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.parameters
+            .iter()
+            .position(|param| param.name() == name)
+    }
+}
+
+impl From<Vec<OptimizationParameter>> for OptimizationSpace {
+    fn from(parameters: Vec<OptimizationParameter>) -> Self {
+        Self::new(parameters)
+    }
+}
+
+pub fn numeric_value(value: &CompositionDataType) -> Option<f32> {
+    match value {
+        CompositionDataType::I64(value) => Some(*value as f32),
+        CompositionDataType::F32(value) => Some(*value),
+        CompositionDataType::OptionF32(value) => *value,
+        CompositionDataType::Bool(value) => Some(if *value { 1.0 } else { 0.0 }),
+        CompositionDataType::Usize(value) => Some(*value as f32),
+        CompositionDataType::String(_) => None,
+    }
+}
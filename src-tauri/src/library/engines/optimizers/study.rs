@@ -0,0 +1,281 @@
+use crate::{
+    library::engines::optimizers::objective_function::ObjectiveDefinition,
+    user::{
+        composer::CompositionDataType,
+        strategies::{metric_pairs, BacktestResult, IStrategy, Metric, Trade},
+    },
+    utils::paths::join_app_data_dir,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Error, Write},
+    path::PathBuf,
+    sync::{Arc, LazyLock, Mutex, RwLock},
+};
+
+// Every backtest run by an optimizer is appended to the study of its strategy and
+// composition, so an interrupted optimization resumes where it stopped and combinations
+// already tried are served from disk instead of being backtested again
+pub static STUDY_STORE: LazyLock<StudyStore> = LazyLock::new(StudyStore::new);
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StudyRecord {
+    pub parameter_hash: u64,
+    // Rows the backtest ran on, pruning optimizers and cross-validation backtest slices
+    // of the composition
+    pub data_fingerprint: u64,
+    pub parameters: Vec<(String, CompositionDataType)>,
+    pub score: Option<f32>,
+    // Expression the score was computed with, `None` for the strategy's own target
+    #[serde(default)]
+    pub objective: Option<ObjectiveDefinition>,
+    // The equity and benchmark series aren't kept, they would make every record as long
+    // as the composition
+    #[serde(with = "metric_pairs")]
+    pub metrics: HashMap<Metric, f32>,
+    pub trades: Vec<Trade>,
+}
+
+pub struct Study {
+    path: PathBuf,
+    records: RwLock<HashMap<(u64, u64), StudyRecord>>,
+    // Appends to the study file are serialized per study rather than by the store's lock
+    file: Mutex<()>,
+}
+
+impl Study {
+    /// Reads the study file, skipping lines that no longer parse.
+    pub fn load(path: PathBuf) -> Self {
+        let mut records: HashMap<(u64, u64), StudyRecord> = HashMap::new();
+
+        if let Ok(file) = File::open(&path) {
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                if let Ok(record) = serde_json::from_str::<StudyRecord>(&line) {
+                    records.insert((record.data_fingerprint, record.parameter_hash), record);
+                }
+            }
+        }
+
+        return Self {
+            path,
+            records: RwLock::new(records),
+            file: Mutex::new(()),
+        };
+    }
+
+    pub fn records(&self, data_fingerprint: u64) -> Vec<StudyRecord> {
+        return self
+            .records
+            .read()
+            .unwrap()
+            .values()
+            .filter(|record| record.data_fingerprint == data_fingerprint)
+            .cloned()
+            .collect();
+    }
+
+    fn get(&self, data_fingerprint: u64, parameter_hash: u64) -> Option<BacktestResult> {
+        self.records
+            .read()
+            .unwrap()
+            .get(&(data_fingerprint, parameter_hash))
+            .map(|record| BacktestResult::from_study(record.trades.clone(), record.metrics.clone()))
+    }
+
+    fn append(&self, record: StudyRecord) -> Result<(), Error> {
+        let line = serde_json::to_string(&record)?;
+
+        {
+            let _file_guard = self.file.lock().unwrap();
+
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+
+            writeln!(file, "{}", line)?;
+        }
+
+        self.records
+            .write()
+            .unwrap()
+            .insert((record.data_fingerprint, record.parameter_hash), record);
+
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), Error> {
+        let _file_guard = self.file.lock().unwrap();
+
+        self.records.write().unwrap().clear();
+
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct StudyStore {
+    studies: Mutex<HashMap<String, Arc<Study>>>,
+}
+
+impl StudyStore {
+    pub fn new() -> Self {
+        return Self {
+            studies: Mutex::new(HashMap::new()),
+        };
+    }
+
+    /// Backtest result saved for the combination, `None` when it was never evaluated
+    /// on those rows or when the app data dir isn't available. Results read back have
+    /// no equity or benchmark series.
+    pub fn lookup(
+        &self,
+        strategy: &Box<dyn IStrategy>,
+        data_fingerprint: u64,
+        combination: &HashMap<String, CompositionDataType>,
+    ) -> Option<BacktestResult> {
+        self.study(strategy)?
+            .get(data_fingerprint, parameter_hash(combination))
+    }
+
+    pub fn record(
+        &self,
+        strategy: &Box<dyn IStrategy>,
+        data_fingerprint: u64,
+        combination: &HashMap<String, CompositionDataType>,
+        backtest_result: &BacktestResult,
+    ) {
+        let Some(study) = self.study(strategy) else {
+            return;
+        };
+
+        let score = strategy.score(backtest_result);
+
+        let record = StudyRecord {
+            parameter_hash: parameter_hash(combination),
+            data_fingerprint,
+            parameters: sorted_parameters(combination),
+            score: if score.is_nan() { None } else { Some(score) },
+            objective: strategy
+                .objective_function()
+                .map(|objective_function| objective_function.definition.clone()),
+            metrics: backtest_result.metrics().clone(),
+            trades: backtest_result.trades().clone(),
+        };
+
+        if let Err(error) = study.append(record) {
+            eprintln!("Couldn't save study record: {}", error);
+        }
+    }
+
+    /// Records of the study on the strategy's whole composition.
+    pub fn records(&self, strategy: &Box<dyn IStrategy>) -> Vec<StudyRecord> {
+        match self.study(strategy) {
+            Some(study) => study.records(data_fingerprint(strategy)),
+            None => vec![],
+        }
+    }
+
+    pub fn clear(&self, strategy: &Box<dyn IStrategy>) -> Result<(), Error> {
+        match self.study(strategy) {
+            Some(study) => study.clear(),
+            None => Ok(()),
+        }
+    }
+
+    // Ids are generated on every launch, names are what stays stable between runs. The
+    // definition hash keeps the records of an edited rule or script apart
+    fn study_name(strategy: &Box<dyn IStrategy>) -> String {
+        let study_name = match strategy.definition_hash() {
+            Some(definition_hash) => format!(
+                "{}_{}_{:016x}",
+                strategy.name(),
+                strategy.composition().name(),
+                definition_hash
+            ),
+            None => format!("{}_{}", strategy.name(), strategy.composition().name()),
+        };
+
+        study_name
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+
+    // The store's lock is only held to find or load the study, reads and appends go
+    // through the study's own locks
+    fn study(&self, strategy: &Box<dyn IStrategy>) -> Option<Arc<Study>> {
+        let study_name = Self::study_name(strategy);
+        let mut studies = self.studies.lock().unwrap();
+
+        if !studies.contains_key(&study_name) {
+            let studies_dir = join_app_data_dir("studies").ok()?;
+            std::fs::create_dir_all(&studies_dir).ok()?;
+
+            let study = Study::load(studies_dir.join(format!("{}.jsonl", study_name)));
+            studies.insert(study_name.clone(), Arc::new(study));
+        }
+
+        studies.get(&study_name).cloned()
+    }
+}
+
+/// Combination sorted by parameter name, the order hashes and saves are made in.
+pub fn sorted_parameters(
+    combination: &HashMap<String, CompositionDataType>,
+) -> Vec<(String, CompositionDataType)> {
+    let mut parameters: Vec<(String, CompositionDataType)> = combination
+        .iter()
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+
+    parameters.sort_by(|a, b| a.0.cmp(&b.0));
+
+    parameters
+}
+
+/// FNV-1a of the sorted combination, stable across runs unlike the std hasher.
+pub fn parameter_hash(combination: &HashMap<String, CompositionDataType>) -> u64 {
+    let serialized = serde_json::to_string(&sorted_parameters(combination)).unwrap_or_default();
+
+    fnv1a(serialized.bytes())
+}
+
+/// FNV-1a of the timestamp of every row the strategy backtests on, slices of the same
+/// length but different rows get different fingerprints.
+pub fn data_fingerprint(strategy: &Box<dyn IStrategy>) -> u64 {
+    let timestamp_position = strategy
+        .composition()
+        .composition_fields()
+        .get("timestamp")
+        .copied()
+        .unwrap_or(0);
+
+    let composed_data = strategy.composed_data();
+
+    fnv1a(
+        composed_data
+            .iter()
+            .flat_map(|row| match row.get(timestamp_position) {
+                Some(CompositionDataType::I64(timestamp)) => timestamp.to_le_bytes(),
+                _ => (row.len() as i64).to_le_bytes(),
+            }),
+    )
+}
+
+/// FNV-1a hash, stable across runs and platforms.
+pub fn fnv1a(bytes: impl Iterator<Item = u8>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
@@ -30,7 +30,7 @@ impl IComposition for ETH_SMA_200_4H_4Y {
         return &self.description;
     }
 
-    fn composition_fields(&self) -> HashMap<&'static str, usize> {
+    fn composition_fields(&self) -> HashMap<&str, usize> {
         return self.composition_fields.clone();
     }
 
@@ -11,10 +11,11 @@ use crate::user::composer::{
     eth_standalone_4h_4y_composition::ETH_STANDALONE_4H_4Y,
     testing_composition::TESTING_COMPOSITION,
 };
+use crate::user::scripting::SCRIPT_REGISTRY;
 use crate::utils::classes::charting::ChartingData;
 use std::collections::HashMap;
 use std::error::Error;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum CompositionDataType {
@@ -71,7 +72,7 @@ pub trait IComposition: Send + Sync {
     fn id(&self) -> &str;
     fn name(&self) -> &str;
     fn description(&self) -> &str;
-    fn composition_fields(&self) -> HashMap<&'static str, usize>;
+    fn composition_fields(&self) -> HashMap<&str, usize>;
     fn get_composition_field_position(&self, field_name: &str) -> usize {
         return self
             .composition_fields()
@@ -79,6 +80,11 @@ pub trait IComposition: Send + Sync {
             .unwrap()
             .to_owned();
     }
+    // Hash of what a composition loaded at runtime (a script) is defined by, strategies
+    // fold it into theirs
+    fn definition_hash(&self) -> Option<u64> {
+        None
+    }
     fn compose(&self) -> Result<Vec<Vec<CompositionDataType>>, Box<dyn Error>>;
     fn safe_compose(&self) -> Result<Vec<Vec<CompositionDataType>>, Box<dyn Error>> {
         let composition_data = self.compose()?;
@@ -97,12 +103,20 @@ pub trait IComposition: Send + Sync {
     fn save(&self) -> Result<(), Box<dyn Error>>;
 }
 
-pub static COMPOSITIONS: LazyLock<Vec<Box<dyn IComposition>>> = LazyLock::new(|| {
+/// The built-in compositions followed by the ones scripts define.
+pub fn compositions() -> Vec<Arc<dyn IComposition>> {
+    let mut compositions: Vec<Arc<dyn IComposition>> = COMPOSITIONS.iter().cloned().collect();
+    compositions.extend(SCRIPT_REGISTRY.compositions());
+
+    compositions
+}
+
+pub static COMPOSITIONS: LazyLock<Vec<Arc<dyn IComposition>>> = LazyLock::new(|| {
     vec![
-        Box::new(ETH_SMA_200_4H_4Y::instance().clone()),
-        Box::new(BTC_ETH_STATARB_4H_4Y::instance().clone()),
-        Box::new(ETH_STANDALONE_4H_4Y::instance().clone()),
-        Box::new(ETH_HLC_STANDALONE_4H_4Y::instance().clone()),
-        Box::new(TESTING_COMPOSITION::instance().clone()),
+        Arc::new(ETH_SMA_200_4H_4Y::instance().clone()),
+        Arc::new(BTC_ETH_STATARB_4H_4Y::instance().clone()),
+        Arc::new(ETH_STANDALONE_4H_4Y::instance().clone()),
+        Arc::new(ETH_HLC_STANDALONE_4H_4Y::instance().clone()),
+        Arc::new(TESTING_COMPOSITION::instance().clone()),
     ]
 });
@@ -33,7 +33,7 @@ impl IComposition for TESTING_COMPOSITION {
         &self.description
     }
 
-    fn composition_fields(&self) -> HashMap<&'static str, usize> {
+    fn composition_fields(&self) -> HashMap<&str, usize> {
         self.composition_fields.clone()
     }
 
@@ -1,5 +1,11 @@
 use crate::{
-    user::library::{IInjectable},
+    user::{
+        library::{
+            metrics::{IMetric, MetricContext, MetricInput},
+            IInjectable,
+        },
+        strategies::Metric,
+    },
     utils::classes::charting::{ChartingData, LineChartingData, LineData},
 };
 use std::error::Error;
@@ -67,26 +73,24 @@ impl IInjectable<(f32, f32), f32> for Beta {
 
         let mut line_data: Vec<Option<LineData>> = vec![];
 
+        // Expanding window kept with running sums, use RollingBeta for a fixed window
+        let mut sum_asset = self.asset_returns[0];
+        let mut sum_market = self.market_returns[0];
+        let mut sum_market_sq = self.market_returns[0].powi(2);
+        let mut sum_asset_market = self.asset_returns[0] * self.market_returns[0];
+
         for i in 2..=self.asset_returns.len() {
-            let window_asset = &self.asset_returns[..i];
-            let window_market = &self.market_returns[..i];
-            
+            let asset_return = self.asset_returns[i - 1];
+            let market_return = self.market_returns[i - 1];
+
+            sum_asset += asset_return;
+            sum_market += market_return;
+            sum_market_sq += market_return * market_return;
+            sum_asset_market += asset_return * market_return;
+
             let n = i as f32;
-            let mean_asset = window_asset.iter().sum::<f32>() / n;
-            let mean_market = window_market.iter().sum::<f32>() / n;
-            
-            let covariance = window_asset
-                .iter()
-                .zip(window_market.iter())
-                .map(|(&a, &m)| (a - mean_asset) * (m - mean_market))
-                .sum::<f32>()
-                / (n - 1.0);
-            
-            let market_variance = window_market
-                .iter()
-                .map(|&m| (m - mean_market).powi(2))
-                .sum::<f32>()
-                / (n - 1.0);
+            let covariance = (sum_asset_market - sum_asset * sum_market / n) / (n - 1.0);
+            let market_variance = (sum_market_sq - sum_market * sum_market / n) / (n - 1.0);
 
             if market_variance > 0.0 {
                 let beta = covariance / market_variance;
@@ -111,6 +115,37 @@ impl IInjectable<(f32, f32), f32> for Beta {
     }
 }
 
+impl IMetric for Beta {
+    fn metrics(&self) -> Vec<Metric> {
+        vec![Metric::Beta]
+    }
+
+    fn inputs(&self) -> Vec<MetricInput> {
+        vec![MetricInput::EquitySeries, MetricInput::BenchmarkSeries]
+    }
+
+    fn compute(&self, context: &MetricContext) -> Vec<(Metric, f32)> {
+        let equity_returns = context.equity_returns();
+        let benchmark_returns = context.benchmark_returns();
+
+        // Both series are sampled once per bar, align them on their most recent points
+        let length = equity_returns.len().min(benchmark_returns.len());
+        let mut beta = Beta::new();
+
+        for (&asset_return, &market_return) in equity_returns[equity_returns.len() - length..]
+            .iter()
+            .zip(benchmark_returns[benchmark_returns.len() - length..].iter())
+        {
+            beta.allocate((asset_return, market_return));
+        }
+
+        match beta.get_data() {
+            Some(value) => vec![(Metric::Beta, value)],
+            None => vec![],
+        }
+    }
+}
+
 impl Beta {
     pub fn new() -> Self {
         Self {
@@ -7,4 +7,6 @@ pub use returns::*;
 pub mod trade_analysis;
 pub use trade_analysis::*;
 pub mod market_comparasion;
-pub use market_comparasion::*;
\ No newline at end of file
+pub use market_comparasion::*;
+pub mod rolling;
+pub use rolling::*;
\ No newline at end of file
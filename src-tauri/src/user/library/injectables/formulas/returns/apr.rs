@@ -1,4 +1,6 @@
+use crate::user::library::metrics::{IMetric, MetricContext, MetricInput};
 use crate::user::library::IInjectable;
+use crate::user::strategies::Metric;
 use crate::utils::classes::charting::ChartingData;
 use std::error::Error;
 
@@ -42,6 +44,26 @@ impl IInjectable<f32, f32> for APR {
     }
 }
 
+impl IMetric for APR {
+    fn metrics(&self) -> Vec<Metric> {
+        vec![Metric::APR]
+    }
+
+    fn inputs(&self) -> Vec<MetricInput> {
+        vec![MetricInput::TradeReturns]
+    }
+
+    fn compute(&self, context: &MetricContext) -> Vec<(Metric, f32)> {
+        let mut apr = APR::new();
+
+        for trade in context.trades {
+            apr.allocate(trade.pl_portfolio() / 100.0);
+        }
+
+        vec![(Metric::APR, apr.get_data().unwrap_or(0.0))]
+    }
+}
+
 impl APR {
     pub fn new() -> Self {
         return Self {
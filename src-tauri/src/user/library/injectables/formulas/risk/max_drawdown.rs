@@ -0,0 +1,89 @@
+use crate::{
+    user::{
+        library::{
+            metrics::{IMetric, MetricContext, MetricInput},
+            IInjectable,
+        },
+        strategies::Metric,
+    },
+    utils::classes::charting::ChartingData,
+};
+use std::error::Error;
+
+pub struct MaxDrawdown {
+    name: String,
+    description: String,
+    peak: Option<f32>,
+    max_drawdown: f32,
+}
+
+impl IInjectable<f32, f32> for MaxDrawdown {
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    fn allocate(&mut self, equity: f32) {
+        let peak = match self.peak {
+            Some(peak) if peak >= equity => peak,
+            _ => {
+                self.peak = Some(equity);
+                equity
+            }
+        };
+
+        if peak > 0.0 {
+            let drawdown = (peak - equity) / peak * 100.0;
+
+            if drawdown > self.max_drawdown {
+                self.max_drawdown = drawdown;
+            }
+        }
+    }
+
+    fn get_data(&mut self) -> Option<f32> {
+        self.peak?;
+
+        Some(self.max_drawdown)
+    }
+
+    fn render(&self, timestamps: Vec<i64>) -> Result<Vec<ChartingData>, Box<dyn Error>> {
+        let charting_data: Vec<ChartingData> = vec![];
+
+        Ok(charting_data)
+    }
+}
+
+impl IMetric for MaxDrawdown {
+    fn metrics(&self) -> Vec<Metric> {
+        vec![Metric::MaxDrawdown]
+    }
+
+    fn inputs(&self) -> Vec<MetricInput> {
+        vec![MetricInput::EquitySeries]
+    }
+
+    fn compute(&self, context: &MetricContext) -> Vec<(Metric, f32)> {
+        let mut max_drawdown = MaxDrawdown::new();
+
+        for equity_point in context.equity_series {
+            max_drawdown.allocate(equity_point.value);
+        }
+
+        vec![(Metric::MaxDrawdown, max_drawdown.get_data().unwrap_or(0.0))]
+    }
+}
+
+impl MaxDrawdown {
+    pub fn new() -> Self {
+        return Self {
+            name: "Max Drawdown".into(),
+            description: "Largest peak to trough decline of the equity, in percent".into(),
+            peak: None,
+            max_drawdown: 0.0,
+        };
+    }
+}
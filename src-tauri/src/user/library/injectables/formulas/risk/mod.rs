@@ -1 +1,2 @@
 pub mod standard_deviation;
+pub mod max_drawdown;
@@ -1,4 +1,13 @@
-use crate::{user::library::IInjectable, utils::classes::charting::ChartingData};
+use crate::{
+    user::{
+        library::{
+            metrics::{IMetric, MetricContext, MetricInput},
+            IInjectable,
+        },
+        strategies::Metric,
+    },
+    utils::classes::charting::ChartingData,
+};
 use std::error::Error;
 
 pub struct StandardDeviation {
@@ -58,6 +67,29 @@ impl IInjectable<f32, f32> for StandardDeviation {
     }
 }
 
+impl IMetric for StandardDeviation {
+    fn metrics(&self) -> Vec<Metric> {
+        vec![Metric::StandardDeviation]
+    }
+
+    fn inputs(&self) -> Vec<MetricInput> {
+        vec![MetricInput::TradeReturns]
+    }
+
+    fn compute(&self, context: &MetricContext) -> Vec<(Metric, f32)> {
+        let mut standard_deviation = StandardDeviation::new();
+
+        for trade in context.trades {
+            standard_deviation.allocate(trade.pl_portfolio());
+        }
+
+        vec![(
+            Metric::StandardDeviation,
+            standard_deviation.get_data().unwrap_or(0.0),
+        )]
+    }
+}
+
 impl StandardDeviation {
     pub fn new() -> Self {
         return Self {
@@ -0,0 +1,96 @@
+use crate::{
+    user::library::{formulas::probabilistic_sharpe_ratio::ProbabilisticSharpeRatio, IInjectable},
+    utils::{
+        classes::charting::ChartingData,
+        formulas::statistics::distribution::expected_maximum_standard_normal,
+    },
+};
+use std::error::Error;
+
+pub struct DeflatedSharpeRatio {
+    name: String,
+    description: String,
+    trials: usize,
+    trials_sharpe_variance: f32,
+    probabilistic_sharpe_ratio: ProbabilisticSharpeRatio,
+}
+
+impl IInjectable<f32, f32> for DeflatedSharpeRatio {
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    fn allocate(&mut self, return_value: f32) {
+        self.probabilistic_sharpe_ratio.allocate(return_value);
+    }
+
+    fn get_data(&mut self) -> Option<f32> {
+        let sharpe = self.probabilistic_sharpe_ratio.sharpe()?;
+        let skewness = self.probabilistic_sharpe_ratio.skewness()?;
+        let kurtosis = self.probabilistic_sharpe_ratio.kurtosis()?;
+
+        Self::compute(
+            sharpe,
+            self.probabilistic_sharpe_ratio.count(),
+            skewness,
+            kurtosis,
+            self.trials,
+            self.trials_sharpe_variance,
+        )
+    }
+
+    fn render(&self, timestamps: Vec<i64>) -> Result<Vec<ChartingData>, Box<dyn Error>> {
+        let charting_data: Vec<ChartingData> = vec![];
+
+        Ok(charting_data)
+    }
+}
+
+impl DeflatedSharpeRatio {
+    pub fn new(trials: usize, trials_sharpe_variance: f32) -> Self {
+        return Self {
+            name: "Deflated Sharpe Ratio".into(),
+            description: "Probabilistic Sharpe Ratio corrected for the number of trials ran".into(),
+            trials,
+            trials_sharpe_variance,
+            probabilistic_sharpe_ratio: ProbabilisticSharpeRatio::new(None),
+        };
+    }
+
+    /// Sharpe ratio expected from the best of `trials` unskilled configurations,
+    /// given the variance of the Sharpe ratios across those trials.
+    pub fn expected_maximum_sharpe(trials: usize, trials_sharpe_variance: f32) -> f32 {
+        trials_sharpe_variance.max(0.0).sqrt() * expected_maximum_standard_normal(trials)
+    }
+
+    pub fn compute(
+        sharpe: f32,
+        sample_length: usize,
+        skewness: f32,
+        kurtosis: f32,
+        trials: usize,
+        trials_sharpe_variance: f32,
+    ) -> Option<f32> {
+        let benchmark_sharpe = Self::expected_maximum_sharpe(trials, trials_sharpe_variance);
+
+        ProbabilisticSharpeRatio::compute(
+            sharpe,
+            benchmark_sharpe,
+            sample_length,
+            skewness,
+            kurtosis,
+        )
+    }
+
+    pub fn trials(&self) -> usize {
+        self.trials
+    }
+
+    pub fn trials_sharpe_variance(&self) -> f32 {
+        self.trials_sharpe_variance
+    }
+}
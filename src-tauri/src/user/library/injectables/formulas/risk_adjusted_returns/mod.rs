@@ -1 +1,3 @@
-pub mod sharpe_ratio;
\ No newline at end of file
+pub mod sharpe_ratio;
+pub mod probabilistic_sharpe_ratio;
+pub mod deflated_sharpe_ratio;
\ No newline at end of file
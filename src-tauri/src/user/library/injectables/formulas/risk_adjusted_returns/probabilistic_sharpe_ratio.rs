@@ -0,0 +1,164 @@
+use crate::{
+    user::library::IInjectable,
+    utils::{classes::charting::ChartingData, formulas::statistics::distribution::normal_cdf},
+};
+use std::error::Error;
+
+pub struct ProbabilisticSharpeRatio {
+    name: String,
+    description: String,
+    benchmark_sharpe: f32,
+    count: usize,
+    mean: f32,
+    m2: f32,
+    m3: f32,
+    m4: f32,
+}
+
+impl IInjectable<f32, f32> for ProbabilisticSharpeRatio {
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    // Online central moments update (Terriberry), O(1) per return
+    fn allocate(&mut self, return_value: f32) {
+        let previous_count = self.count as f32;
+        self.count += 1;
+        let n = self.count as f32;
+
+        let delta = return_value - self.mean;
+        let delta_n = delta / n;
+        let delta_n_squared = delta_n * delta_n;
+        let term = delta * delta_n * previous_count;
+
+        self.mean += delta_n;
+        self.m4 += term * delta_n_squared * (n * n - 3.0 * n + 3.0)
+            + 6.0 * delta_n_squared * self.m2
+            - 4.0 * delta_n * self.m3;
+        self.m3 += term * delta_n * (n - 2.0) - 3.0 * delta_n * self.m2;
+        self.m2 += term;
+    }
+
+    fn get_data(&mut self) -> Option<f32> {
+        let sharpe = self.sharpe()?;
+        let skewness = self.skewness()?;
+        let kurtosis = self.kurtosis()?;
+
+        Self::compute(
+            sharpe,
+            self.benchmark_sharpe,
+            self.count,
+            skewness,
+            kurtosis,
+        )
+    }
+
+    fn render(&self, timestamps: Vec<i64>) -> Result<Vec<ChartingData>, Box<dyn Error>> {
+        let charting_data: Vec<ChartingData> = vec![];
+
+        Ok(charting_data)
+    }
+}
+
+impl ProbabilisticSharpeRatio {
+    pub fn new(benchmark_sharpe: Option<f32>) -> Self {
+        return Self {
+            name: "Probabilistic Sharpe Ratio".into(),
+            description: "Probability that the true Sharpe ratio exceeds a benchmark Sharpe ratio"
+                .into(),
+            benchmark_sharpe: benchmark_sharpe.unwrap_or(0.0),
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            m3: 0.0,
+            m4: 0.0,
+        };
+    }
+
+    /// PSR of Bailey & López de Prado, `sharpe` is the non-annualized per-period
+    /// Sharpe ratio and `kurtosis` is the raw (non-excess) kurtosis of the returns.
+    pub fn compute(
+        sharpe: f32,
+        benchmark_sharpe: f32,
+        sample_length: usize,
+        skewness: f32,
+        kurtosis: f32,
+    ) -> Option<f32> {
+        if sample_length < 2 {
+            return None;
+        }
+
+        let sharpe_variance = 1.0 - skewness * sharpe + ((kurtosis - 1.0) / 4.0) * sharpe * sharpe;
+
+        if sharpe_variance <= 0.0 {
+            return None;
+        }
+
+        let z = (sharpe - benchmark_sharpe) * ((sample_length - 1) as f32).sqrt()
+            / sharpe_variance.sqrt();
+
+        Some(normal_cdf(z))
+    }
+
+    pub fn benchmark_sharpe(&self) -> f32 {
+        self.benchmark_sharpe
+    }
+
+    pub fn set_benchmark_sharpe(&mut self, benchmark_sharpe: f32) {
+        self.benchmark_sharpe = benchmark_sharpe;
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn mean(&self) -> Option<f32> {
+        if self.count == 0 {
+            return None;
+        }
+
+        Some(self.mean)
+    }
+
+    pub fn volatility(&self) -> Option<f32> {
+        if self.count < 2 {
+            return None;
+        }
+
+        Some((self.m2 / (self.count - 1) as f32).sqrt())
+    }
+
+    pub fn sharpe(&self) -> Option<f32> {
+        let volatility = self.volatility()?;
+
+        if volatility == 0.0 {
+            return None;
+        }
+
+        Some(self.mean / volatility)
+    }
+
+    pub fn skewness(&self) -> Option<f32> {
+        if self.count < 3 || self.m2 == 0.0 {
+            return None;
+        }
+
+        let n = self.count as f32;
+
+        Some(n.sqrt() * self.m3 / self.m2.powf(1.5))
+    }
+
+    pub fn kurtosis(&self) -> Option<f32> {
+        if self.count < 4 || self.m2 == 0.0 {
+            return None;
+        }
+
+        let n = self.count as f32;
+
+        Some(n * self.m4 / (self.m2 * self.m2))
+    }
+}
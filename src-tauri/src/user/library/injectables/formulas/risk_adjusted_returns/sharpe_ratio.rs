@@ -1,7 +1,11 @@
 use crate::{
-    user::library::{
-        formulas::standard_deviation::{self, StandardDeviation},
-        IInjectable,
+    user::{
+        library::{
+            formulas::standard_deviation::{self, StandardDeviation},
+            metrics::{IMetric, MetricContext, MetricInput},
+            IInjectable,
+        },
+        strategies::Metric,
     },
     utils::classes::charting::{ChartingData, LineChartingData, LineData},
 };
@@ -58,15 +62,17 @@ impl IInjectable<f32, f32> for SharpeRatio {
 
         let mut line_data: Vec<Option<LineData>> = vec![];
 
+        // Expanding window kept with running sums, use RollingSharpeRatio for a fixed window
+        let mut sum = self.returns[0] + self.returns[1];
+        let mut sum_squared = self.returns[0].powi(2) + self.returns[1].powi(2);
+
         for i in 2..self.returns.len() {
-            let window_returns = &self.returns[..=i];
-            let mean_return = window_returns.iter().sum::<f32>() / window_returns.len() as f32;
+            sum += self.returns[i];
+            sum_squared += self.returns[i].powi(2);
 
-            let variance = window_returns
-                .iter()
-                .map(|&r| (r - mean_return).powi(2))
-                .sum::<f32>()
-                / (window_returns.len() - 1) as f32;
+            let n = (i + 1) as f32;
+            let mean_return = sum / n;
+            let variance = ((sum_squared - sum * sum / n) / (n - 1.0)).max(0.0);
 
             let std_dev = variance.sqrt();
 
@@ -98,6 +104,29 @@ impl IInjectable<f32, f32> for SharpeRatio {
     }
 }
 
+impl IMetric for SharpeRatio {
+    fn metrics(&self) -> Vec<Metric> {
+        vec![Metric::SharpeRatio]
+    }
+
+    fn inputs(&self) -> Vec<MetricInput> {
+        vec![MetricInput::TradeReturns]
+    }
+
+    fn compute(&self, context: &MetricContext) -> Vec<(Metric, f32)> {
+        let mut sharpe = SharpeRatio::new_with_annualization(
+            Some(self.risk_free_rate),
+            self.annualization_factor,
+        );
+
+        for trade in context.trades {
+            sharpe.allocate(trade.pl_portfolio());
+        }
+
+        vec![(Metric::SharpeRatio, sharpe.get_data().unwrap_or(0.0))]
+    }
+}
+
 impl SharpeRatio {
     pub fn new(risk_free_rate: Option<f32>) -> Self {
         return Self {
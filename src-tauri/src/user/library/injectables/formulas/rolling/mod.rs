@@ -0,0 +1,6 @@
+pub mod rolling_sharpe_ratio;
+pub mod rolling_volatility;
+pub mod rolling_beta;
+pub mod rolling_correlation;
+pub mod rolling_drawdown;
+pub mod rolling_win_rate;
\ No newline at end of file
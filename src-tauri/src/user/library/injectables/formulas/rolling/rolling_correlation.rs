@@ -0,0 +1,122 @@
+use crate::{
+    user::library::IInjectable,
+    utils::classes::charting::{ChartingData, LineChartingData, LineData},
+};
+use std::collections::VecDeque;
+use std::error::Error;
+
+pub struct RollingCorrelation {
+    name: String,
+    description: String,
+    window: usize,
+    returns: VecDeque<(f32, f32)>,
+    // Accumulated in f64, the sums are added to and subtracted from on every bar
+    sum_asset: f64,
+    sum_market: f64,
+    sum_asset_sq: f64,
+    sum_market_sq: f64,
+    sum_asset_market: f64,
+    data_values: Vec<Option<f32>>,
+}
+
+impl IInjectable<(f32, f32), f32> for RollingCorrelation {
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    fn allocate(&mut self, (asset_return, market_return): (f32, f32)) {
+        if self.returns.len() == self.window {
+            if let Some((oldest_asset, oldest_market)) = self.returns.pop_front() {
+                let (oldest_asset, oldest_market) = (oldest_asset as f64, oldest_market as f64);
+                self.sum_asset -= oldest_asset;
+                self.sum_market -= oldest_market;
+                self.sum_asset_sq -= oldest_asset * oldest_asset;
+                self.sum_market_sq -= oldest_market * oldest_market;
+                self.sum_asset_market -= oldest_asset * oldest_market;
+            }
+        }
+
+        self.returns.push_back((asset_return, market_return));
+
+        let (asset_return, market_return) = (asset_return as f64, market_return as f64);
+        self.sum_asset += asset_return;
+        self.sum_market += market_return;
+        self.sum_asset_sq += asset_return * asset_return;
+        self.sum_market_sq += market_return * market_return;
+        self.sum_asset_market += asset_return * market_return;
+
+        let correlation = self.get_data();
+        self.data_values.push(correlation);
+    }
+
+    fn get_data(&mut self) -> Option<f32> {
+        if self.returns.len() < self.window || self.window < 2 {
+            return None;
+        }
+
+        let n = self.window as f64;
+        let covariance = self.sum_asset_market - self.sum_asset * self.sum_market / n;
+        let asset_variance = self.sum_asset_sq - self.sum_asset * self.sum_asset / n;
+        let market_variance = self.sum_market_sq - self.sum_market * self.sum_market / n;
+
+        let denominator = (asset_variance * market_variance).sqrt();
+        if denominator <= 0.0 || denominator.is_nan() {
+            return None;
+        }
+
+        Some((covariance / denominator).clamp(-1.0, 1.0) as f32)
+    }
+
+    fn render(&self, timestamps: Vec<i64>) -> Result<Vec<ChartingData>, Box<dyn Error>> {
+        let mut line_data: Vec<Option<LineData>> = vec![];
+
+        for (timestamp, value) in timestamps.iter().zip(self.data_values.iter()) {
+            if let Some(value) = value {
+                line_data.push(Some(LineData {
+                    time: timestamp.clone(),
+                    value: value.to_owned(),
+                    color: Some("purple".into()),
+                }));
+            }
+        }
+
+        let charting_data: Vec<ChartingData> =
+            vec![ChartingData::LineChartingData(LineChartingData {
+                chart_type: "line".into(),
+                height: None,
+                data: line_data,
+                pane: Some(0),
+                title: Some(format!("Rolling Correlation {}", self.window)),
+            })];
+
+        Ok(charting_data)
+    }
+}
+
+impl RollingCorrelation {
+    pub fn new(window: usize) -> Self {
+        return Self {
+            name: "Rolling Correlation".into(),
+            description: format!(
+                "Pearson correlation over the last {} returns",
+                window.max(1)
+            ),
+            window: window.max(1),
+            returns: VecDeque::with_capacity(window.max(1)),
+            sum_asset: 0.0,
+            sum_market: 0.0,
+            sum_asset_sq: 0.0,
+            sum_market_sq: 0.0,
+            sum_asset_market: 0.0,
+            data_values: Vec::new(),
+        };
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+}
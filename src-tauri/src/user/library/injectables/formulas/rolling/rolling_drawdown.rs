@@ -0,0 +1,110 @@
+use crate::{
+    user::library::IInjectable,
+    utils::classes::charting::{ChartingData, LineChartingData, LineData},
+};
+use std::collections::VecDeque;
+use std::error::Error;
+
+pub struct RollingDrawdown {
+    name: String,
+    description: String,
+    window: usize,
+    index: usize,
+    // Monotonic deque of (index, equity) with decreasing equity, front is the window peak
+    peaks: VecDeque<(usize, f32)>,
+    current_equity: Option<f32>,
+    data_values: Vec<Option<f32>>,
+}
+
+impl IInjectable<f32, f32> for RollingDrawdown {
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    fn allocate(&mut self, equity: f32) {
+        while let Some(&(_, back_equity)) = self.peaks.back() {
+            if back_equity <= equity {
+                self.peaks.pop_back();
+            } else {
+                break;
+            }
+        }
+
+        self.peaks.push_back((self.index, equity));
+
+        while let Some(&(front_index, _)) = self.peaks.front() {
+            if front_index + self.window <= self.index {
+                self.peaks.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        self.index += 1;
+        self.current_equity = Some(equity);
+
+        let drawdown = self.get_data();
+        self.data_values.push(drawdown);
+    }
+
+    fn get_data(&mut self) -> Option<f32> {
+        let current_equity = self.current_equity?;
+        let &(_, peak) = self.peaks.front()?;
+
+        if peak <= 0.0 {
+            return None;
+        }
+
+        Some((peak - current_equity) / peak * 100.0)
+    }
+
+    fn render(&self, timestamps: Vec<i64>) -> Result<Vec<ChartingData>, Box<dyn Error>> {
+        let mut line_data: Vec<Option<LineData>> = vec![];
+
+        for (timestamp, value) in timestamps.iter().zip(self.data_values.iter()) {
+            if let Some(value) = value {
+                line_data.push(Some(LineData {
+                    time: timestamp.clone(),
+                    value: -value.to_owned(),
+                    color: Some("red".into()),
+                }));
+            }
+        }
+
+        let charting_data: Vec<ChartingData> =
+            vec![ChartingData::LineChartingData(LineChartingData {
+                chart_type: "line".into(),
+                height: None,
+                data: line_data,
+                pane: Some(0),
+                title: Some(format!("Rolling Drawdown {} (%)", self.window)),
+            })];
+
+        Ok(charting_data)
+    }
+}
+
+impl RollingDrawdown {
+    pub fn new(window: usize) -> Self {
+        return Self {
+            name: "Rolling Drawdown".into(),
+            description: format!(
+                "Decline of the equity from its peak over the last {} points",
+                window.max(1)
+            ),
+            window: window.max(1),
+            index: 0,
+            peaks: VecDeque::new(),
+            current_equity: None,
+            data_values: Vec::new(),
+        };
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    user::library::IInjectable,
+    utils::classes::charting::{ChartingData, LineChartingData, LineData},
+};
+use std::collections::VecDeque;
+use std::error::Error;
+
+pub struct RollingSharpeRatio {
+    name: String,
+    description: String,
+    window: usize,
+    risk_free_rate: f32,
+    annualization_factor: f32,
+    excess_returns: VecDeque<f32>,
+    // Accumulated in f64, the sums are added to and subtracted from on every bar
+    sum: f64,
+    sum_squared: f64,
+    data_values: Vec<Option<f32>>,
+}
+
+impl IInjectable<f32, f32> for RollingSharpeRatio {
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    fn allocate(&mut self, return_value: f32) {
+        if self.excess_returns.len() == self.window {
+            if let Some(oldest_return) = self.excess_returns.pop_front() {
+                self.sum -= oldest_return as f64;
+                self.sum_squared -= (oldest_return as f64).powi(2);
+            }
+        }
+
+        let excess_return = return_value - self.risk_free_rate;
+        self.excess_returns.push_back(excess_return);
+        self.sum += excess_return as f64;
+        self.sum_squared += (excess_return as f64).powi(2);
+
+        let sharpe = self.get_data();
+        self.data_values.push(sharpe);
+    }
+
+    fn get_data(&mut self) -> Option<f32> {
+        if self.excess_returns.len() < self.window || self.window < 2 {
+            return None;
+        }
+
+        let n = self.window as f64;
+        let mean = self.sum / n;
+        let variance = ((self.sum_squared - (self.sum * self.sum) / n) / (n - 1.0)).max(0.0);
+        let volatility = variance.sqrt();
+
+        if volatility == 0.0 {
+            return None;
+        }
+
+        Some((mean / volatility) as f32 * self.annualization_factor.sqrt())
+    }
+
+    fn render(&self, timestamps: Vec<i64>) -> Result<Vec<ChartingData>, Box<dyn Error>> {
+        let mut line_data: Vec<Option<LineData>> = vec![];
+
+        for (timestamp, value) in timestamps.iter().zip(self.data_values.iter()) {
+            if let Some(value) = value {
+                line_data.push(Some(LineData {
+                    time: timestamp.clone(),
+                    value: value.to_owned(),
+                    color: Some(if *value >= 0.0 { "green" } else { "red" }.into()),
+                }));
+            }
+        }
+
+        let charting_data: Vec<ChartingData> =
+            vec![ChartingData::LineChartingData(LineChartingData {
+                chart_type: "line".into(),
+                height: None,
+                data: line_data,
+                pane: Some(0),
+                title: Some(format!("Rolling Sharpe Ratio {}", self.window)),
+            })];
+
+        Ok(charting_data)
+    }
+}
+
+impl RollingSharpeRatio {
+    pub fn new(
+        window: usize,
+        risk_free_rate: Option<f32>,
+        annualization_factor: Option<f32>,
+    ) -> Self {
+        return Self {
+            name: "Rolling Sharpe Ratio".into(),
+            description: format!("Sharpe ratio of the last {} returns", window.max(1)),
+            window: window.max(1),
+            risk_free_rate: risk_free_rate.unwrap_or(0.0),
+            annualization_factor: annualization_factor.unwrap_or(1.0),
+            excess_returns: VecDeque::with_capacity(window.max(1)),
+            sum: 0.0,
+            sum_squared: 0.0,
+            data_values: Vec::new(),
+        };
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{
+    user::library::IInjectable,
+    utils::classes::charting::{ChartingData, LineChartingData, LineData},
+};
+use std::collections::VecDeque;
+use std::error::Error;
+
+pub struct RollingWinRate {
+    name: String,
+    description: String,
+    window: usize,
+    outcomes: VecDeque<bool>,
+    wins: usize,
+    data_values: Vec<Option<f32>>,
+}
+
+impl IInjectable<f32, f32> for RollingWinRate {
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    fn allocate(&mut self, pl_ratio: f32) {
+        if self.outcomes.len() == self.window {
+            if let Some(true) = self.outcomes.pop_front() {
+                self.wins -= 1;
+            }
+        }
+
+        let is_win = pl_ratio > 0.0;
+        self.outcomes.push_back(is_win);
+
+        if is_win {
+            self.wins += 1;
+        }
+
+        let win_rate = self.get_data();
+        self.data_values.push(win_rate);
+    }
+
+    fn get_data(&mut self) -> Option<f32> {
+        if self.outcomes.len() < self.window {
+            return None;
+        }
+
+        Some(self.wins as f32 / self.window as f32 * 100.0)
+    }
+
+    fn render(&self, timestamps: Vec<i64>) -> Result<Vec<ChartingData>, Box<dyn Error>> {
+        let mut line_data: Vec<Option<LineData>> = vec![];
+
+        for (timestamp, value) in timestamps.iter().zip(self.data_values.iter()) {
+            if let Some(value) = value {
+                line_data.push(Some(LineData {
+                    time: timestamp.clone(),
+                    value: value.to_owned(),
+                    color: Some(if *value >= 50.0 { "green" } else { "red" }.into()),
+                }));
+            }
+        }
+
+        let charting_data: Vec<ChartingData> =
+            vec![ChartingData::LineChartingData(LineChartingData {
+                chart_type: "line".into(),
+                height: None,
+                data: line_data,
+                pane: Some(0),
+                title: Some(format!("Rolling Win Rate {} trades (%)", self.window)),
+            })];
+
+        Ok(charting_data)
+    }
+}
+
+impl RollingWinRate {
+    pub fn new(window: usize) -> Self {
+        return Self {
+            name: "Rolling Win Rate".into(),
+            description: format!(
+                "Share of winning trades over the last {} trades",
+                window.max(1)
+            ),
+            window: window.max(1),
+            outcomes: VecDeque::with_capacity(window),
+            wins: 0,
+            data_values: Vec::new(),
+        };
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+}
@@ -1,6 +1,15 @@
 use std::error::Error;
 
-use crate::{user::library::IInjectable, utils::classes::charting::ChartingData};
+use crate::{
+    user::{
+        library::{
+            metrics::{IMetric, MetricContext, MetricInput},
+            IInjectable,
+        },
+        strategies::Metric,
+    },
+    utils::classes::charting::ChartingData,
+};
 
 pub struct ConsecutiveWinsLosses {
     name: String,
@@ -48,6 +57,35 @@ impl IInjectable<f32, (i32, i32)> for ConsecutiveWinsLosses {
     }
 }
 
+impl IMetric for ConsecutiveWinsLosses {
+    fn metrics(&self) -> Vec<Metric> {
+        vec![Metric::MostConsecutiveWins, Metric::MostConsecutiveLosses]
+    }
+
+    fn inputs(&self) -> Vec<MetricInput> {
+        vec![MetricInput::TradeReturns]
+    }
+
+    fn compute(&self, context: &MetricContext) -> Vec<(Metric, f32)> {
+        let mut consecutive_wins_losses = ConsecutiveWinsLosses::new();
+
+        for trade in context.trades {
+            consecutive_wins_losses.allocate(trade.pl_ratio());
+        }
+
+        let (most_consecutive_wins, most_consecutive_losses) =
+            consecutive_wins_losses.get_data().unwrap_or((0, 0));
+
+        vec![
+            (Metric::MostConsecutiveWins, most_consecutive_wins as f32),
+            (
+                Metric::MostConsecutiveLosses,
+                most_consecutive_losses as f32,
+            ),
+        ]
+    }
+}
+
 impl ConsecutiveWinsLosses {
     pub fn new() -> Self {
         return Self {
@@ -0,0 +1,146 @@
+use crate::user::{
+    library::{
+        apr::APR, consecutive_wins_losses::ConsecutiveWinsLosses, max_drawdown::MaxDrawdown,
+        sharpe_ratio::SharpeRatio, standard_deviation::StandardDeviation,
+    },
+    strategies::{EquityPoint, Metric, Trade},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MetricInput {
+    // Closed trades of the backtest, in closing order
+    TradeReturns,
+    // Portfolio value sampled once per bar
+    EquitySeries,
+    // Benchmark prices pushed by the strategy through `BacktestManager::update_benchmark`
+    BenchmarkSeries,
+}
+
+pub struct MetricContext<'a> {
+    pub initial_capital: f32,
+    pub final_capital: f32,
+    pub trades: &'a [Trade],
+    pub equity_series: &'a [EquityPoint],
+    pub benchmark_series: &'a [f32],
+}
+
+impl<'a> MetricContext<'a> {
+    pub fn has_input(&self, input: MetricInput) -> bool {
+        match input {
+            MetricInput::TradeReturns => true,
+            MetricInput::EquitySeries => !self.equity_series.is_empty(),
+            MetricInput::BenchmarkSeries => !self.benchmark_series.is_empty(),
+        }
+    }
+
+    pub fn equity_returns(&self) -> Vec<f32> {
+        return self
+            .equity_series
+            .windows(2)
+            .map(|pair| {
+                if pair[0].value == 0.0 {
+                    0.0
+                } else {
+                    (pair[1].value - pair[0].value) / pair[0].value
+                }
+            })
+            .collect();
+    }
+
+    pub fn benchmark_returns(&self) -> Vec<f32> {
+        return self
+            .benchmark_series
+            .windows(2)
+            .map(|pair| {
+                if pair[0] == 0.0 {
+                    0.0
+                } else {
+                    (pair[1] - pair[0]) / pair[0]
+                }
+            })
+            .collect();
+    }
+}
+
+pub trait IMetric: Send + Sync {
+    fn metrics(&self) -> Vec<Metric>;
+    fn inputs(&self) -> Vec<MetricInput>;
+    fn compute(&self, context: &MetricContext) -> Vec<(Metric, f32)>;
+}
+
+#[derive(Clone)]
+pub struct MetricRegistry {
+    metrics: Vec<Arc<dyn IMetric>>,
+}
+
+impl MetricRegistry {
+    pub fn new() -> Self {
+        return Self {
+            metrics: Vec::new(),
+        };
+    }
+
+    pub fn register(&mut self, metric: Arc<dyn IMetric>) {
+        self.metrics.push(metric);
+    }
+
+    /// Every metric the registered metrics can compute.
+    pub fn reported_metrics(&self) -> Vec<Metric> {
+        return self
+            .metrics
+            .iter()
+            .flat_map(|metric| metric.metrics())
+            .collect();
+    }
+
+    pub fn requires(&self, input: MetricInput) -> bool {
+        return self
+            .metrics
+            .iter()
+            .any(|metric| metric.inputs().contains(&input));
+    }
+
+    pub fn compute(&self, context: &MetricContext) -> HashMap<Metric, f32> {
+        let mut computed_metrics: HashMap<Metric, f32> = HashMap::new();
+
+        for metric in &self.metrics {
+            if !metric
+                .inputs()
+                .iter()
+                .all(|&input| context.has_input(input))
+            {
+                continue;
+            }
+
+            for (key, value) in metric.compute(context) {
+                computed_metrics.insert(key, value);
+            }
+        }
+
+        computed_metrics
+    }
+}
+
+impl Default for MetricRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+
+        registry.register(Arc::new(SharpeRatio::new(Some(0.0))));
+        registry.register(Arc::new(StandardDeviation::new()));
+        registry.register(Arc::new(APR::new()));
+        registry.register(Arc::new(ConsecutiveWinsLosses::new()));
+        registry.register(Arc::new(MaxDrawdown::new()));
+
+        registry
+    }
+}
+
+impl fmt::Debug for MetricRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.metrics.iter().flat_map(|metric| metric.metrics()))
+            .finish()
+    }
+}
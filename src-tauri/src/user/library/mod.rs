@@ -13,4 +13,5 @@ pub trait IInjectable<Input: for<'de> Deserialize<'de>, Output: for<'de> Deseria
 }
 
 pub mod injectables;
+pub mod metrics;
 pub use injectables::*;
@@ -1,4 +1,5 @@
 pub mod static_resources;
 pub mod composer;
 pub mod strategies;
-pub mod library;
\ No newline at end of file
+pub mod library;
+pub mod scripting;
\ No newline at end of file
@@ -0,0 +1,326 @@
+use crate::user::{
+    composer::CompositionDataType,
+    library::{
+        atr::ATR, bollinger_bands::BollingerBands, kalman_filter::KalmanFilter, renko::Renko,
+        sma::SMA, theilsen::TheilSen, tr::TR, IInjectable,
+    },
+    static_resources::STATIC_RESOURCES,
+    strategies::{BacktestManager, BacktestResult, Trade, TradeOptions, TradeSide},
+};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, FLOAT, INT};
+use std::sync::{Arc, Mutex};
+
+pub type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+// The backtest a script strategy trades in, shared with the script as `backtest`
+#[derive(Clone)]
+pub struct ScriptBacktest {
+    manager: Arc<Mutex<BacktestManager>>,
+    asset_name: String,
+}
+
+impl ScriptBacktest {
+    pub fn new(manager: BacktestManager, asset_name: &str) -> Self {
+        return Self {
+            manager: Arc::new(Mutex::new(manager)),
+            asset_name: asset_name.into(),
+        };
+    }
+
+    pub fn end(&self) -> BacktestResult {
+        return self.manager.lock().unwrap().backtest_end();
+    }
+}
+
+// Trades stay shared with the script so closing one updates the script's handle
+#[derive(Clone)]
+pub struct ScriptTrade(Arc<Mutex<Trade>>);
+
+enum InjectableKind {
+    Price(Box<dyn IInjectable<f32, f32>>),
+    Range(Box<dyn IInjectable<(f32, f32, f32), f32>>),
+    Bands(BollingerBands),
+}
+
+#[derive(Clone)]
+pub struct ScriptInjectable(Arc<Mutex<InjectableKind>>);
+
+impl ScriptInjectable {
+    fn price(injectable: impl IInjectable<f32, f32> + 'static) -> Self {
+        return Self(Arc::new(Mutex::new(InjectableKind::Price(Box::new(
+            injectable,
+        )))));
+    }
+
+    fn range(injectable: impl IInjectable<(f32, f32, f32), f32> + 'static) -> Self {
+        return Self(Arc::new(Mutex::new(InjectableKind::Range(Box::new(
+            injectable,
+        )))));
+    }
+}
+
+/// Integers and floats both pass where the bindings expect a number.
+pub fn number(value: &Dynamic) -> ScriptResult<f32> {
+    if let Ok(float) = value.as_float() {
+        return Ok(float as f32);
+    }
+
+    if let Ok(int) = value.as_int() {
+        return Ok(int as f32);
+    }
+
+    Err(format!("Expected a number, got {}", value.type_name()).into())
+}
+
+pub fn to_dynamic(value: &CompositionDataType) -> Dynamic {
+    match value {
+        CompositionDataType::I64(value) => Dynamic::from_int(*value as INT),
+        CompositionDataType::F32(value) => Dynamic::from_float(*value as FLOAT),
+        CompositionDataType::OptionF32(value) => match value {
+            Some(value) => Dynamic::from_float(*value as FLOAT),
+            None => Dynamic::UNIT,
+        },
+        CompositionDataType::Bool(value) => Dynamic::from_bool(*value),
+        CompositionDataType::Usize(value) => Dynamic::from_int(*value as INT),
+        CompositionDataType::String(value) => Dynamic::from(value.clone()),
+    }
+}
+
+// Integers become timestamps (`I64`) and floats prices (`F32`), `()` a missing value
+pub fn from_dynamic(value: Dynamic) -> ScriptResult<CompositionDataType> {
+    if value.is_unit() {
+        return Ok(CompositionDataType::OptionF32(None));
+    }
+
+    if let Ok(int) = value.as_int() {
+        return Ok(CompositionDataType::I64(int as i64));
+    }
+
+    if let Ok(float) = value.as_float() {
+        return Ok(CompositionDataType::F32(float as f32));
+    }
+
+    if let Ok(boolean) = value.as_bool() {
+        return Ok(CompositionDataType::Bool(boolean));
+    }
+
+    if value.is_string() {
+        return Ok(CompositionDataType::String(value.into_string()?));
+    }
+
+    Err(format!("Unsupported composition value {}", value.type_name()).into())
+}
+
+fn period(value: INT) -> usize {
+    return value.max(1) as usize;
+}
+
+fn ohlcv(name: &str) -> ScriptResult<Array> {
+    let static_resource = (&*STATIC_RESOURCES)
+        .into_iter()
+        .find(|static_resource| static_resource.name() == name)
+        .ok_or_else(|| format!("Unknown static resource {}", name))?;
+
+    let candles = static_resource
+        .load_ohlcv_mmap()
+        .map_err(|error| error.to_string())?;
+
+    Ok(candles
+        .iter()
+        .map(|candle| {
+            let mut row = Map::new();
+            row.insert("timestamp".into(), Dynamic::from_int(candle.timestamp));
+            row.insert("open".into(), Dynamic::from_float(candle.open as FLOAT));
+            row.insert("high".into(), Dynamic::from_float(candle.high as FLOAT));
+            row.insert("low".into(), Dynamic::from_float(candle.low as FLOAT));
+            row.insert("close".into(), Dynamic::from_float(candle.close as FLOAT));
+            row.insert("volume".into(), Dynamic::from_float(candle.volume as FLOAT));
+
+            Dynamic::from_map(row)
+        })
+        .collect())
+}
+
+// Limits of a single script call, so a runaway loop or recursion fails the call instead
+// of hanging the backtest. A backtest over years of 4h bars stays well below them
+const MAX_OPERATIONS: u64 = 500_000_000;
+const MAX_CALL_LEVELS: usize = 64;
+
+/// Engine every script runs in: `Backtest`, `Trade` and `Injectable` types, injectable
+/// constructors named after the library (`sma(period)`, `atr(period)`, ...) and
+/// `ohlcv(static_resource_name)` for compositions.
+pub fn create_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+
+    engine
+        .register_type_with_name::<ScriptBacktest>("Backtest")
+        .register_fn(
+            "update_price",
+            |backtest: &mut ScriptBacktest, timestamp: INT, price: Dynamic| -> ScriptResult<()> {
+                let price = number(&price)?;
+                backtest.manager.lock().unwrap().update_price(
+                    &backtest.asset_name,
+                    timestamp,
+                    price,
+                );
+
+                Ok(())
+            },
+        )
+        .register_fn(
+            "open_trade",
+            |backtest: &mut ScriptBacktest,
+             side: &str,
+             capital_allocation: Dynamic,
+             leverage: Dynamic|
+             -> ScriptResult<ScriptTrade> {
+                let side = match side {
+                    "LONG" | "long" => TradeSide::LONG,
+                    "SHORT" | "short" => TradeSide::SHORT,
+                    _ => return Err(format!("Unknown trade side {}", side).into()),
+                };
+
+                let mut trade = Trade::new(TradeOptions {
+                    asset_name: &backtest.asset_name,
+                    side,
+                    capital_allocation: Some(number(&capital_allocation)?),
+                    leverage: Some(number(&leverage)?),
+                });
+                backtest.manager.lock().unwrap().open_trade(&mut trade);
+
+                Ok(ScriptTrade(Arc::new(Mutex::new(trade))))
+            },
+        )
+        .register_fn(
+            "close_trade",
+            |backtest: &mut ScriptBacktest, trade: ScriptTrade| {
+                let mut trade = trade.0.lock().unwrap();
+                backtest.manager.lock().unwrap().close_trade(&mut trade);
+            },
+        )
+        .register_get("available_capital", |backtest: &mut ScriptBacktest| {
+            backtest.manager.lock().unwrap().available_capital() as FLOAT
+        })
+        .register_get("portfolio_value", |backtest: &mut ScriptBacktest| {
+            backtest.manager.lock().unwrap().current_portfolio_value() as FLOAT
+        })
+        .register_get("equity", |backtest: &mut ScriptBacktest| {
+            backtest.manager.lock().unwrap().equity() as FLOAT
+        })
+        .register_get("ended", |backtest: &mut ScriptBacktest| {
+            backtest.manager.lock().unwrap().backtest_ended()
+        });
+
+    engine
+        .register_type_with_name::<ScriptTrade>("Trade")
+        .register_get("side", |trade: &mut ScriptTrade| {
+            format!("{:?}", trade.0.lock().unwrap().side())
+        })
+        .register_get("is_closed", |trade: &mut ScriptTrade| {
+            trade.0.lock().unwrap().is_closed()
+        })
+        .register_get("open_price", |trade: &mut ScriptTrade| {
+            match trade.0.lock().unwrap().open_price() {
+                Some(open_price) => Dynamic::from_float(open_price as FLOAT),
+                None => Dynamic::UNIT,
+            }
+        })
+        .register_get("pl_ratio", |trade: &mut ScriptTrade| {
+            trade.0.lock().unwrap().pl_ratio() as FLOAT
+        })
+        .register_get("pl_fixed", |trade: &mut ScriptTrade| {
+            trade.0.lock().unwrap().pl_fixed() as FLOAT
+        });
+
+    engine
+        .register_type_with_name::<ScriptInjectable>("Injectable")
+        .register_fn("sma", |period_length: INT| {
+            ScriptInjectable::price(SMA::new(period(period_length)))
+        })
+        .register_fn(
+            "kalman_filter",
+            |q_noise: Dynamic, r_noise: Dynamic| -> ScriptResult<ScriptInjectable> {
+                Ok(ScriptInjectable::price(KalmanFilter::new(
+                    number(&q_noise)?,
+                    number(&r_noise)?,
+                )))
+            },
+        )
+        .register_fn(
+            "renko",
+            |change: Dynamic| -> ScriptResult<ScriptInjectable> {
+                Ok(ScriptInjectable::price(Renko::new(number(&change)?)))
+            },
+        )
+        .register_fn("bollinger_bands", |period_length: INT| {
+            ScriptInjectable(Arc::new(Mutex::new(InjectableKind::Bands(
+                BollingerBands::new(period(period_length)),
+            ))))
+        })
+        .register_fn("tr", || ScriptInjectable::range(TR::new()))
+        .register_fn("atr", |period_length: INT| {
+            ScriptInjectable::range(ATR::new(period(period_length)))
+        })
+        .register_fn("theilsen", |window_length: INT| {
+            ScriptInjectable::range(TheilSen::new(Some(period(window_length)), None, None))
+        })
+        .register_fn(
+            "allocate",
+            |injectable: &mut ScriptInjectable, value: Dynamic| -> ScriptResult<()> {
+                let value = number(&value)?;
+
+                match &mut *injectable.0.lock().unwrap() {
+                    InjectableKind::Price(injectable) => injectable.allocate(value),
+                    InjectableKind::Bands(injectable) => injectable.allocate(value),
+                    InjectableKind::Range(_) => {
+                        return Err("This injectable allocates (high, low, close)".into())
+                    }
+                }
+
+                Ok(())
+            },
+        )
+        .register_fn(
+            "allocate",
+            |injectable: &mut ScriptInjectable,
+             high: Dynamic,
+             low: Dynamic,
+             close: Dynamic|
+             -> ScriptResult<()> {
+                let bar = (number(&high)?, number(&low)?, number(&close)?);
+
+                match &mut *injectable.0.lock().unwrap() {
+                    InjectableKind::Range(injectable) => injectable.allocate(bar),
+                    _ => return Err("This injectable allocates a single value".into()),
+                }
+
+                Ok(())
+            },
+        )
+        // `()` while the injectable warms up, `[upper, lower]` for bollinger bands
+        .register_fn("get_data", |injectable: &mut ScriptInjectable| {
+            let data = match &mut *injectable.0.lock().unwrap() {
+                InjectableKind::Price(injectable) => injectable
+                    .get_data()
+                    .map(|value| Dynamic::from_float(value as FLOAT)),
+                InjectableKind::Range(injectable) => injectable
+                    .get_data()
+                    .map(|value| Dynamic::from_float(value as FLOAT)),
+                InjectableKind::Bands(injectable) => injectable.get_data().map(|(upper, lower)| {
+                    Dynamic::from_array(vec![
+                        Dynamic::from_float(upper as FLOAT),
+                        Dynamic::from_float(lower as FLOAT),
+                    ])
+                }),
+            };
+
+            data.unwrap_or(Dynamic::UNIT)
+        });
+
+    engine.register_fn("ohlcv", ohlcv);
+
+    engine
+}
@@ -0,0 +1,359 @@
+pub mod bindings;
+pub mod script_composition;
+pub mod script_strategy;
+
+use crate::{
+    library::engines::optimizers::study::fnv1a,
+    user::{
+        composer::IComposition,
+        scripting::{
+            bindings::create_engine, script_composition::ScriptComposition,
+            script_strategy::ScriptStrategy,
+        },
+        strategies::IStrategy,
+    },
+    utils::paths::join_app_data_dir,
+    APP_HANDLE,
+};
+use rhai::{Engine, EvalAltResult, FuncArgs, ImmutableString, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Mutex, RwLock},
+    thread,
+    time::{Duration, SystemTime},
+};
+use tauri::Emitter;
+use uuid::Uuid;
+
+pub static SCRIPT_ENGINE: LazyLock<Engine> = LazyLock::new(create_engine);
+pub static SCRIPT_REGISTRY: LazyLock<ScriptRegistry> = LazyLock::new(ScriptRegistry::new);
+
+// Directories of the app data dir `.rhai` scripts are loaded from
+pub const STRATEGY_SCRIPTS_DIRECTORY: &str = "scripts/strategies";
+pub const COMPOSITION_SCRIPTS_DIRECTORY: &str = "scripts/compositions";
+
+// How often the script directories are checked for changes
+const SCRIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// A compiled script file, shared by what it defines and the clones optimizers make of
+// it. A change compiles a new one, running backtests finish on the version they started
+pub struct Script {
+    ast: AST,
+    // Part of the study key of what the script defines, edits start a new study
+    source_hash: u64,
+}
+
+impl Script {
+    fn compile(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let source = fs::read_to_string(path)?;
+
+        return Ok(Self {
+            ast: SCRIPT_ENGINE.compile(&source)?,
+            source_hash: fnv1a(source.bytes()),
+        });
+    }
+
+    pub fn source_hash(&self) -> u64 {
+        return self.source_hash;
+    }
+
+    pub fn has_fn(&self, name: &str) -> bool {
+        return self
+            .ast
+            .iter_functions()
+            .any(|function| function.name == name);
+    }
+
+    pub fn call<T: Clone + Send + Sync + 'static>(
+        &self,
+        name: &str,
+        args: impl FuncArgs,
+    ) -> Result<T, Box<EvalAltResult>> {
+        SCRIPT_ENGINE.call_fn::<T>(&mut Scope::new(), &self.ast, name, args)
+    }
+
+    pub fn call_string(&self, name: &str) -> Result<String, Box<EvalAltResult>> {
+        Ok(self.call::<ImmutableString>(name, ())?.to_string())
+    }
+}
+
+// What a script is registered under, a reload changing it gives the script a new id
+#[derive(Clone, PartialEq)]
+pub struct ScriptMetadata {
+    pub name: String,
+    pub description: String,
+    // Composition fields, empty for strategies
+    pub fields: Vec<String>,
+}
+
+impl ScriptMetadata {
+    fn read(script: &Script, with_fields: bool) -> Result<Self, Box<dyn Error>> {
+        let description = if script.has_fn("description") {
+            script.call_string("description")?
+        } else {
+            String::new()
+        };
+
+        let fields = if with_fields {
+            script
+                .call::<rhai::Array>("fields", ())?
+                .into_iter()
+                .map(|field| field.into_string().map_err(|error| error.into()))
+                .collect::<Result<Vec<String>, Box<dyn Error>>>()?
+        } else {
+            vec![]
+        };
+
+        return Ok(Self {
+            name: script.call_string("name")?,
+            description,
+            fields,
+        });
+    }
+}
+
+struct ScriptEntry<T: ?Sized> {
+    modified: SystemTime,
+    id: String,
+    metadata: ScriptMetadata,
+    loaded: Arc<T>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScriptError {
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ScriptReloadReport {
+    // Scripts compiled, recompiled or removed by the reload
+    pub reloaded: Vec<String>,
+    pub errors: Vec<ScriptError>,
+}
+
+// Strategies and compositions defined by the scripts of the app data dir. What a script
+// defines is dropped once it is replaced or removed and nothing running uses it
+pub struct ScriptRegistry {
+    strategies: RwLock<BTreeMap<PathBuf, ScriptEntry<Box<dyn IStrategy>>>>,
+    compositions: RwLock<BTreeMap<PathBuf, ScriptEntry<dyn IComposition>>>,
+    // Modification time of the scripts that failed to load, so they are only retried
+    // once they change
+    failed: Mutex<HashMap<PathBuf, SystemTime>>,
+}
+
+impl ScriptRegistry {
+    pub fn new() -> Self {
+        return Self {
+            strategies: RwLock::new(BTreeMap::new()),
+            compositions: RwLock::new(BTreeMap::new()),
+            failed: Mutex::new(HashMap::new()),
+        };
+    }
+
+    pub fn strategies(&self) -> Vec<Arc<Box<dyn IStrategy>>> {
+        self.strategies
+            .read()
+            .unwrap()
+            .values()
+            .map(|entry| entry.loaded.clone())
+            .collect()
+    }
+
+    pub fn compositions(&self) -> Vec<Arc<dyn IComposition>> {
+        self.compositions
+            .read()
+            .unwrap()
+            .values()
+            .map(|entry| entry.loaded.clone())
+            .collect()
+    }
+
+    /// Loads new and modified scripts and drops deleted ones. A script that fails to
+    /// compile or load keeps its previous version.
+    pub fn reload(&self) -> ScriptReloadReport {
+        let mut report = ScriptReloadReport::default();
+
+        // Compositions first, strategies look theirs up by name
+        reload_directory(
+            &self.compositions,
+            COMPOSITION_SCRIPTS_DIRECTORY,
+            true,
+            false,
+            &self.failed,
+            |script, metadata, id| {
+                Ok(Arc::new(ScriptComposition::new(script, metadata, id)) as Arc<dyn IComposition>)
+            },
+            &mut report,
+        );
+
+        // Strategies compose their data when they are loaded, all of them are loaded
+        // again once a composition changed
+        let compositions_changed = !report.reloaded.is_empty();
+
+        reload_directory(
+            &self.strategies,
+            STRATEGY_SCRIPTS_DIRECTORY,
+            false,
+            compositions_changed,
+            &self.failed,
+            |script, metadata, id| {
+                let strategy: Box<dyn IStrategy> =
+                    Box::new(ScriptStrategy::new(script, metadata, id)?);
+                Ok(Arc::new(strategy))
+            },
+            &mut report,
+        );
+
+        report
+    }
+}
+
+fn reload_directory<T: ?Sized>(
+    entries: &RwLock<BTreeMap<PathBuf, ScriptEntry<T>>>,
+    directory: &str,
+    with_fields: bool,
+    // Loads every script, changed or not
+    force: bool,
+    failed: &Mutex<HashMap<PathBuf, SystemTime>>,
+    load: impl Fn(Arc<Script>, &ScriptMetadata, String) -> Result<Arc<T>, Box<dyn Error>>,
+    report: &mut ScriptReloadReport,
+) {
+    let paths: Vec<(PathBuf, SystemTime)> = match join_app_data_dir(directory)
+        .ok()
+        .and_then(|directory| fs::read_dir(directory).ok())
+    {
+        Some(read_dir) => read_dir
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension().and_then(|extension| extension.to_str()) == Some("rhai")
+            })
+            .filter_map(|path| {
+                let modified = fs::metadata(&path).and_then(|metadata| metadata.modified());
+                modified.ok().map(|modified| (path, modified))
+            })
+            .collect(),
+        None => vec![],
+    };
+
+    // Scripts are compiled and loaded, strategies composing their data, without holding
+    // the entries lock so readers and running lookups aren't blocked meanwhile. What the
+    // load needs of the current entries is copied out first
+    let current_entries = entries.read().unwrap();
+
+    let removed: Vec<PathBuf> = current_entries
+        .keys()
+        .filter(|path| !paths.iter().any(|(existing, _)| existing == *path))
+        .cloned()
+        .collect();
+
+    let current: HashMap<PathBuf, (SystemTime, String, ScriptMetadata)> = current_entries
+        .iter()
+        .map(|(path, entry)| {
+            (
+                path.clone(),
+                (entry.modified, entry.id.clone(), entry.metadata.clone()),
+            )
+        })
+        .collect();
+
+    drop(current_entries);
+
+    let mut loaded_entries: Vec<(PathBuf, ScriptEntry<T>)> = vec![];
+
+    for (path, modified) in paths {
+        if !force
+            && current
+                .get(&path)
+                .map_or(false, |(current_modified, _, _)| {
+                    *current_modified == modified
+                })
+        {
+            continue;
+        }
+
+        if !force && failed.lock().unwrap().get(&path) == Some(&modified) {
+            continue;
+        }
+
+        let loaded = Script::compile(&path).and_then(|script| {
+            let metadata = ScriptMetadata::read(&script, with_fields)?;
+
+            // What the script defines keeps its id while its name and fields don't change
+            let id = current
+                .get(&path)
+                .filter(|(_, _, current_metadata)| *current_metadata == metadata)
+                .map_or_else(|| Uuid::new_v4().into(), |(_, id, _)| id.clone());
+
+            let loaded = load(Arc::new(script), &metadata, id.clone())?;
+            Ok((id, metadata, loaded))
+        });
+
+        let (id, metadata, loaded) = match loaded {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                // Retried on the next change of the file
+                failed.lock().unwrap().insert(path.clone(), modified);
+                report.errors.push(ScriptError {
+                    path: path.display().to_string(),
+                    message: error.to_string(),
+                });
+                continue;
+            }
+        };
+
+        loaded_entries.push((
+            path,
+            ScriptEntry {
+                modified,
+                id,
+                metadata,
+                loaded,
+            },
+        ));
+    }
+
+    let mut entries = entries.write().unwrap();
+
+    for path in removed {
+        entries.remove(&path);
+        report.reloaded.push(path.display().to_string());
+    }
+
+    for (path, entry) in loaded_entries {
+        failed.lock().unwrap().remove(&path);
+        report.reloaded.push(path.display().to_string());
+
+        entries.insert(path, entry);
+    }
+}
+
+/// Loads the scripts once, then reloads them on change in the background and emits
+/// `scripts_reloaded` so lists of strategies and compositions can be refreshed.
+pub fn watch_scripts() {
+    for error in SCRIPT_REGISTRY.reload().errors {
+        eprintln!("Couldn't load script {}: {}", error.path, error.message);
+    }
+
+    thread::spawn(|| loop {
+        thread::sleep(SCRIPT_POLL_INTERVAL);
+
+        let report = SCRIPT_REGISTRY.reload();
+
+        if report.reloaded.is_empty() && report.errors.is_empty() {
+            continue;
+        }
+
+        for error in &report.errors {
+            eprintln!("Couldn't load script {}: {}", error.path, error.message);
+        }
+
+        if let Some(app_handle) = APP_HANDLE.get() {
+            app_handle.emit("scripts_reloaded", report).unwrap();
+        }
+    });
+}
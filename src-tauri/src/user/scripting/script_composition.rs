@@ -0,0 +1,150 @@
+use crate::{
+    library::engines::optimizers::space::numeric_value,
+    user::{
+        composer::{CompositionDataType, IComposition},
+        scripting::{
+            bindings::{from_dynamic, ScriptResult},
+            Script, ScriptMetadata,
+        },
+    },
+    utils::classes::charting::{ChartingData, LineChartingData, LineData},
+};
+use rhai::{Array, Dynamic};
+use std::{collections::HashMap, error::Error, sync::Arc};
+
+// A composition defined by a script of `scripts/compositions`:
+//
+// fn name() { "ETH close and SMA" }
+// fn fields() { ["timestamp", "close", "sma"] }
+// fn compose() {
+//     let sma = sma(50);
+//     ohlcv("ETHUSDT 4h").map(|candle| {
+//         sma.allocate(candle.close);
+//         [candle.timestamp, candle.close, sma.get_data()]
+//     })
+// }
+//
+// Rows are arrays in field order or maps keyed by field
+#[derive(Clone)]
+pub struct ScriptComposition {
+    id: String,
+    name: String,
+    description: String,
+    fields: Vec<String>,
+    script: Arc<Script>,
+}
+
+impl IComposition for ScriptComposition {
+    fn id(&self) -> &str {
+        return &self.id;
+    }
+
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    fn composition_fields(&self) -> HashMap<&str, usize> {
+        return self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(position, field)| (field.as_str(), position))
+            .collect();
+    }
+
+    fn definition_hash(&self) -> Option<u64> {
+        return Some(self.script.source_hash());
+    }
+
+    fn compose(&self) -> Result<Vec<Vec<CompositionDataType>>, Box<dyn Error>> {
+        let rows = self.script.call::<Array>("compose", ())?;
+
+        let composed_data = rows
+            .into_iter()
+            .map(|row| self.composition_row(row))
+            .collect::<ScriptResult<Vec<Vec<CompositionDataType>>>>()?;
+
+        Ok(composed_data)
+    }
+
+    // One line per numeric field, each in its own pane
+    fn render(&self) -> Result<Vec<ChartingData>, Box<dyn Error>> {
+        let composed_data = self.compose()?;
+        let timestamp_position = self.get_composition_field_position("timestamp");
+
+        let charting_data = self
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| *field != "timestamp")
+            .map(|(position, field)| {
+                let data: Vec<Option<LineData>> = composed_data
+                    .iter()
+                    .map(|data_point| {
+                        numeric_value(&data_point[position]).map(|value| LineData {
+                            time: CompositionDataType::extract_i64(&data_point[timestamp_position]),
+                            value,
+                            color: None,
+                        })
+                    })
+                    .collect();
+
+                ChartingData::LineChartingData(LineChartingData {
+                    chart_type: "line".into(),
+                    height: None,
+                    data,
+                    pane: Some(position as i8),
+                    title: Some(field.clone()),
+                })
+            })
+            .collect();
+
+        Ok(charting_data)
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl ScriptComposition {
+    pub fn new(script: Arc<Script>, metadata: &ScriptMetadata, id: String) -> Self {
+        return Self {
+            id,
+            name: metadata.name.clone(),
+            description: metadata.description.clone(),
+            fields: metadata.fields.clone(),
+            script,
+        };
+    }
+
+    fn composition_row(&self, row: Dynamic) -> ScriptResult<Vec<CompositionDataType>> {
+        let values: Vec<Dynamic> = if row.is_array() {
+            row.into_array()?
+        } else if row.is_map() {
+            let mut row = row.cast::<rhai::Map>();
+
+            self.fields
+                .iter()
+                .map(|field| row.remove(field.as_str()).unwrap_or(Dynamic::UNIT))
+                .collect()
+        } else {
+            return Err(format!("A row is an array or a map, got {}", row.type_name()).into());
+        };
+
+        if values.len() != self.fields.len() {
+            return Err(format!(
+                "Row has {} values for {} fields",
+                values.len(),
+                self.fields.len()
+            )
+            .into());
+        }
+
+        values.into_iter().map(from_dynamic).collect()
+    }
+}
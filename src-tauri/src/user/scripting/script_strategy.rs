@@ -0,0 +1,278 @@
+use crate::{
+    library::engines::optimizers::{
+        grid::{
+            BooleanOptimizationParameter, CategoricOptimizationParameter, NumericKind,
+            NumericOptimizationParameter, OptimizationParameter, OptimizedBacktestResult,
+        },
+        random_search::RandomSearchOptimizer,
+        space::OptimizationSpace,
+        study::fnv1a,
+        Optimizer,
+    },
+    user::{
+        composer::{compositions, CompositionDataType, IComposition},
+        scripting::{
+            bindings::{number, to_dynamic, ScriptBacktest},
+            Script, ScriptMetadata,
+        },
+        strategies::{
+            BacktestManager, BacktestOptions, BacktestResult, IStrategy, ParameterSchema,
+            StrategyOverrides,
+        },
+    },
+};
+use rhai::{Array, Dynamic, Map};
+use std::{collections::HashMap, error::Error, sync::Arc};
+
+// A strategy defined by a script of `scripts/strategies`:
+//
+// fn name() { "Scripted SMA crossover" }
+// fn composition() { "ETH Standalone composition" }
+// fn parameters() { [#{ name: "period", value: 50, start: 10, end: 200, step: 1 }] }
+// fn backtest(backtest, rows, params) {
+//     let sma = sma(params.period);
+//     let trade = ();
+//     for row in rows {
+//         backtest.update_price(row.timestamp, row.close);
+//         sma.allocate(row.close);
+//         ...
+//         trade = backtest.open_trade("LONG", backtest.available_capital * 0.1, 1.0);
+//     }
+// }
+//
+// `parameters` is optional, `value` is the default (`default` is reserved in Rhai).
+// Integer values are optimized as integers and float ones as floats, either sampled in
+// log space with `log: true`, booleans and `categories` of strings as such.
+// The composition and parameters are read once when the script is loaded, a script
+// whose composition doesn't compose or whose parameters are invalid isn't loaded
+#[derive(Clone)]
+pub struct ScriptStrategy {
+    id: String,
+    name: String,
+    description: String,
+    script: Arc<Script>,
+    composition: Arc<dyn IComposition>,
+    parameter_schema: Vec<ParameterSchema>,
+    // The script's source and its composition's, if scripted
+    definition_hash: u64,
+    overrides: StrategyOverrides,
+}
+
+impl IStrategy for ScriptStrategy {
+    fn id(&self) -> &str {
+        return &self.id;
+    }
+
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    fn definition_hash(&self) -> Option<u64> {
+        return Some(self.definition_hash);
+    }
+
+    fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
+        let optimization_ranges = self.optimization_ranges();
+
+        if optimization_ranges.is_empty() {
+            return None;
+        }
+
+        let optimization_space = OptimizationSpace::new(optimization_ranges);
+
+        let strategy: Box<dyn IStrategy> = Box::new(self.clone());
+
+        let optimization_results = self
+            .optimizer(Box::new(RandomSearchOptimizer::new(48, 42)))
+            .optimize(&strategy, &optimization_space)
+            .unwrap_or(Vec::new());
+
+        Some(optimization_results)
+    }
+
+    fn backtest(
+        &self,
+        optimization_map: Option<&HashMap<String, CompositionDataType>>,
+    ) -> Result<BacktestResult, Box<dyn Error>> {
+        let backtest_manager = BacktestManager::new(BacktestOptions {
+            initial_capital: 1_000.0,
+            fees: 0.001,
+        });
+
+        let composition = self.composition();
+        let composition_data = self.composed_data();
+        let composition_fields = composition.composition_fields();
+
+        let parameters: Map = self
+            .parameters_with_defaults(optimization_map)
+            .iter()
+            .map(|(name, value)| (name.into(), to_dynamic(value)))
+            .collect();
+
+        let rows: Array = composition_data
+            .iter()
+            .map(|composition_point| {
+                let row: Map = composition_fields
+                    .iter()
+                    .map(|(field, &position)| {
+                        ((*field).into(), to_dynamic(&composition_point[position]))
+                    })
+                    .collect();
+
+                Dynamic::from_map(row)
+            })
+            .collect();
+
+        let backtest = ScriptBacktest::new(backtest_manager, composition.name());
+
+        // Whatever the script returns is ignored, it trades through `backtest`
+        let _ = self
+            .script
+            .call::<Dynamic>("backtest", (backtest.clone(), rows, parameters))?;
+
+        let backtest_result = backtest.end();
+        Ok(backtest_result)
+    }
+
+    fn composition(&self) -> &dyn IComposition {
+        return self.composition.as_ref();
+    }
+
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        return self.parameter_schema.clone();
+    }
+
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        return Some(&self.overrides);
+    }
+
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        let mut strategy = self.clone();
+        strategy.overrides = overrides;
+
+        return Some(Box::new(strategy));
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl ScriptStrategy {
+    pub fn new(
+        script: Arc<Script>,
+        metadata: &ScriptMetadata,
+        id: String,
+    ) -> Result<Self, Box<dyn Error>> {
+        let composition_name = script.call_string("composition")?;
+
+        let composition: Arc<dyn IComposition> = compositions()
+            .into_iter()
+            .find(|composition| composition.name() == composition_name)
+            .ok_or_else(|| format!("Unknown composition {}", composition_name))?;
+
+        let parameter_schema = if script.has_fn("parameters") {
+            script
+                .call::<Array>("parameters", ())?
+                .into_iter()
+                .map(parameter_schema)
+                .collect::<Result<Vec<ParameterSchema>, Box<dyn Error>>>()?
+        } else {
+            vec![]
+        };
+
+        let definition_hash = fnv1a(
+            script
+                .source_hash()
+                .to_le_bytes()
+                .into_iter()
+                .chain(composition.definition_hash().unwrap_or(0).to_le_bytes()),
+        );
+
+        let mut strategy = Self {
+            id,
+            name: metadata.name.clone(),
+            description: metadata.description.clone(),
+            script,
+            composition,
+            parameter_schema,
+            definition_hash,
+            overrides: StrategyOverrides::default(),
+        };
+
+        strategy.overrides.composition_data = Some(strategy.composition().compose()?);
+
+        return Ok(strategy);
+    }
+}
+
+fn parameter_schema(parameter: Dynamic) -> Result<ParameterSchema, Box<dyn Error>> {
+    let parameter = parameter
+        .try_cast::<Map>()
+        .ok_or("A parameter is a map like #{ name: \"period\", value: 50, ... }")?;
+
+    let field = |key: &str| -> Result<Dynamic, Box<dyn Error>> {
+        parameter
+            .get(key)
+            .cloned()
+            .ok_or_else(|| format!("Parameter is missing `{}`", key).into())
+    };
+
+    let name = field("name")?.into_string()?;
+    let default = field("value")?;
+
+    let numeric_range = |kind: NumericKind| -> Result<OptimizationParameter, Box<dyn Error>> {
+        Ok(OptimizationParameter::Numeric(
+            NumericOptimizationParameter {
+                name: name.clone(),
+                start: number(&field("start")?)?,
+                end: number(&field("end")?)?,
+                step: number(&field("step")?)?,
+                kind,
+                log_scale: parameter
+                    .get("log")
+                    .and_then(|log| log.as_bool().ok())
+                    .unwrap_or(false),
+            },
+        ))
+    };
+
+    let parameter_schema = if let Ok(int) = default.as_int() {
+        ParameterSchema {
+            default: CompositionDataType::Usize(int.max(0) as usize),
+            range: numeric_range(NumericKind::Integer)?,
+        }
+    } else if let Ok(float) = default.as_float() {
+        ParameterSchema {
+            default: CompositionDataType::F32(float as f32),
+            range: numeric_range(NumericKind::Float)?,
+        }
+    } else if let Ok(boolean) = default.as_bool() {
+        ParameterSchema {
+            default: CompositionDataType::Bool(boolean),
+            range: OptimizationParameter::Boolean(BooleanOptimizationParameter { name }),
+        }
+    } else if default.is_string() {
+        let categories = field("categories")?
+            .into_array()?
+            .into_iter()
+            .map(|category| category.into_string())
+            .collect::<Result<Vec<String>, &str>>()?;
+
+        ParameterSchema {
+            default: CompositionDataType::String(default.into_string()?),
+            range: OptimizationParameter::Categoric(CategoricOptimizationParameter {
+                name,
+                categories,
+            }),
+        }
+    } else {
+        return Err(format!("Unsupported default for parameter {}", name).into());
+    };
+
+    Ok(parameter_schema)
+}
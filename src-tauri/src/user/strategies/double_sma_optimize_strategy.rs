@@ -1,8 +1,15 @@
-use super::{BacktestManager, BacktestResult, IStrategy, Trade, TradeOptions, TradeSide};
+use super::{
+    BacktestManager, BacktestResult, IStrategy, ParameterSchema, StrategyOverrides, Trade,
+    TradeOptions, TradeSide,
+};
 use crate::library::engines::optimizers::Optimizer;
 use crate::{
-    library::engines::optimizers::grid::{
-        GridOptimizer, NumericOptimizationParameter, OptimizationParameter, OptimizedBacktestResult,
+    library::engines::optimizers::{
+        grid::{
+            GridOptimizer, NumericKind, NumericOptimizationParameter, OptimizationParameter,
+            OptimizedBacktestResult,
+        },
+        space::{Constraint, OptimizationSpace},
     },
     user::{
         composer::{
@@ -22,7 +29,7 @@ pub struct DoubleSmaOptimizablePeriodStrategy {
     id: String,
     name: String,
     description: String,
-    composition_data: Option<Vec<Vec<CompositionDataType>>>,
+    overrides: StrategyOverrides,
 }
 
 impl IStrategy for DoubleSmaOptimizablePeriodStrategy {
@@ -39,25 +46,18 @@ impl IStrategy for DoubleSmaOptimizablePeriodStrategy {
     }
 
     fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
-        let optimization_parameters = [
-            OptimizationParameter::Numeric(NumericOptimizationParameter {
-                name: "sma_short_period".into(),
-                start: 10.0,
-                end: 100.0,
-                step: 15.0,
-            }),
-            OptimizationParameter::Numeric(NumericOptimizationParameter {
-                name: "sma_long_period".into(),
-                start: 100.0,
-                end: 200.0,
-                step: 15.0,
-            }),
-        ];
+        let optimization_space = OptimizationSpace {
+            parameters: self.optimization_ranges(),
+            conditions: vec![],
+            constraints: vec![Constraint::less_than("sma_short_period", "sma_long_period")],
+        };
 
         let strategy: Box<dyn IStrategy> = Box::new(self.clone());
 
-        let optimization_results =
-            GridOptimizer::optimize(&strategy, &optimization_parameters).unwrap_or(Vec::new());
+        let optimization_results = self
+            .optimizer(Box::new(GridOptimizer::new()))
+            .optimize(&strategy, &optimization_space)
+            .unwrap_or(Vec::new());
 
         Some(optimization_results)
     }
@@ -71,13 +71,11 @@ impl IStrategy for DoubleSmaOptimizablePeriodStrategy {
             fees: 0.001,
         });
 
-        let composition: &'static dyn IComposition = self.composition();
+        let composition: &dyn IComposition = self.composition();
         let composition_data = self.composed_data();
 
-        if optimization_map.is_none() {
-            let backtest_result = backtest_manager.backtest_end();
-            return Ok(backtest_result);
-        }
+        let parameters = self.parameters_with_defaults(optimization_map);
+        let optimization_map = Some(&parameters);
 
         let sma_short_comp = optimization_map
             .unwrap()
@@ -172,12 +170,42 @@ impl IStrategy for DoubleSmaOptimizablePeriodStrategy {
         }
     }
 
-    fn composed_data(&self) -> Vec<Vec<CompositionDataType>> {
-        if self.composition_data.is_some() {
-            return self.composition_data.as_ref().unwrap().to_vec();
-        }
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        vec![
+            ParameterSchema {
+                default: CompositionDataType::Usize(20),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "sma_short_period".into(),
+                    start: 10.0,
+                    end: 100.0,
+                    step: 15.0,
+                    kind: NumericKind::Integer,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::Usize(100),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "sma_long_period".into(),
+                    start: 100.0,
+                    end: 200.0,
+                    step: 15.0,
+                    kind: NumericKind::Integer,
+                    log_scale: false,
+                }),
+            },
+        ]
+    }
+
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        return Some(&self.overrides);
+    }
+
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        let mut strategy = self.clone();
+        strategy.overrides = overrides;
 
-        return self.composition().compose().unwrap();
+        return Some(Box::new(strategy));
     }
 
     fn save(&self) -> Result<(), Box<dyn Error>> {
@@ -191,10 +219,10 @@ impl DoubleSmaOptimizablePeriodStrategy {
             id: Uuid::new_v4().into(),
             name: "Double SMA optimizable period price crossover".into(),
             description: "Long when sma(short_period) > sma(long_period) and vice-versa where short_period and long_period are optimizable".into(),
-            composition_data: None,
+            overrides: StrategyOverrides::default(),
         };
 
-        strategy.composition_data = Some(strategy.composition().compose().unwrap());
+        strategy.overrides.composition_data = Some(strategy.composition().compose().unwrap());
 
         return strategy;
     }
@@ -0,0 +1,496 @@
+use super::target_position::{backtest_target_positions, TargetPosition, TargetPositionOptions};
+use super::{
+    double_sma_optimize_strategy::DoubleSmaOptimizablePeriodStrategy,
+    kalman_optimize_strategy::KalmanOptimizeableStrategy,
+    renko_sma_optimize::SmaRenkoOptimizablePeriodStrategy, sma_200_strategy::Sma200Strategy,
+    sma_optimizable_period_strategy::SmaOptimizablePeriodStrategy,
+    theilsen_optimize_strategy::TheilSenOptimizeableStrategy, BacktestManager, BacktestOptions,
+    BacktestResult, IStrategy, Metric, ParameterSchema, StrategyOverrides, TradeSide,
+};
+use crate::{
+    library::engines::optimizers::{
+        grid::{
+            CategoricOptimizationParameter, NumericKind, NumericOptimizationParameter,
+            OptimizationParameter, OptimizedBacktestResult,
+        },
+        random_search::RandomSearchOptimizer,
+        space::{OptimizationSpace, ParameterCondition},
+        Optimizer,
+    },
+    user::composer::{
+        eth_standalone_4h_4y_composition::ETH_STANDALONE_4H_4Y, CompositionDataType, IComposition,
+    },
+};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    error::Error,
+    sync::{Arc, OnceLock},
+};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CombinationMethod {
+    // Side most of the weight agrees on, flat on a tie
+    MajorityVote,
+    // Weighted mean of the signals, so the position shrinks as children disagree
+    WeightedAverage,
+    // Weighted mean with every child weighted by the return its signal made over the
+    // last bars, children losing money over the window are left out
+    RollingPerformance,
+}
+
+impl CombinationMethod {
+    const CATEGORIES: [&'static str; 3] =
+        ["majority_vote", "weighted_average", "rolling_performance"];
+
+    fn from_category(category: &str) -> Result<Self, Box<dyn Error>> {
+        match category {
+            "majority_vote" => Ok(Self::MajorityVote),
+            "weighted_average" => Ok(Self::WeightedAverage),
+            "rolling_performance" => Ok(Self::RollingPerformance),
+            _ => Err(format!("Unknown combination method {}", category).into()),
+        }
+    }
+}
+
+// Builds a child strategy, children are only built when the ensemble first backtests
+pub type EnsembleChildBuilder = Box<dyn Fn() -> Box<dyn IStrategy> + Send + Sync>;
+
+struct EnsembleChild {
+    build: EnsembleChildBuilder,
+    weight: f32,
+    // Name and signal of the child's default backtest keyed by timestamp, computed once
+    // and shared by the clones optimizers make. The child and its composed data are
+    // dropped once the signal is read
+    signals: OnceLock<Result<ChildSignals, String>>,
+}
+
+struct ChildSignals {
+    name: String,
+    signals: BTreeMap<i64, f32>,
+}
+
+impl EnsembleChild {
+    // The position the child holds after every bar, rebuilt from its trades: 1.0 long,
+    // -1.0 short and 0.0 flat. Children only need to backtest, whatever they trade
+    fn signals(&self) -> Result<&ChildSignals, Box<dyn Error>> {
+        let signals = self.signals.get_or_init(|| {
+            let strategy = (self.build)();
+
+            let backtest_result = strategy.backtest(None).map_err(|error| error.to_string())?;
+
+            let timestamp_position = strategy
+                .composition()
+                .get_composition_field_position("timestamp");
+
+            // Position changes at the bars trades open and close on, trades still open
+            // when the backtest ended are held until the last bar
+            let mut changes: BTreeMap<i64, f32> = BTreeMap::new();
+
+            for trade in backtest_result.trades() {
+                let open_timestamp = match trade.open_timestamp() {
+                    Some(open_timestamp) => open_timestamp,
+                    None => continue,
+                };
+
+                let side = match trade.side() {
+                    TradeSide::LONG => 1.0,
+                    TradeSide::SHORT => -1.0,
+                };
+
+                *changes.entry(open_timestamp).or_insert(0.0) += side;
+
+                if let Some(close_timestamp) = trade.close_timestamp() {
+                    *changes.entry(close_timestamp).or_insert(0.0) -= side;
+                }
+            }
+
+            let mut position = 0.0;
+            let signals = strategy
+                .composed_data()
+                .iter()
+                .map(|composition_point| {
+                    let timestamp =
+                        CompositionDataType::extract_i64(&composition_point[timestamp_position]);
+                    position += changes.get(&timestamp).copied().unwrap_or(0.0);
+
+                    (timestamp, f32::clamp(position, -1.0, 1.0))
+                })
+                .collect();
+
+            Ok(ChildSignals {
+                name: strategy.name().into(),
+                signals,
+            })
+        });
+
+        signals.as_ref().map_err(|error| error.clone().into())
+    }
+}
+
+// Combines the signals of strategies trading the same asset into one position, backtested
+// with a single manager. Children backtest with their default parameters and are matched
+// on timestamps, so they can read different compositions of the asset
+#[derive(Clone)]
+pub struct EnsembleStrategy {
+    id: String,
+    name: String,
+    description: String,
+    children: Arc<Vec<EnsembleChild>>,
+    overrides: StrategyOverrides,
+}
+
+impl IStrategy for EnsembleStrategy {
+    fn id(&self) -> &str {
+        return &self.id;
+    }
+
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
+        let optimization_ranges = self.optimization_ranges();
+
+        let mut optimization_space = OptimizationSpace::new(optimization_ranges);
+
+        // The window is only read by the rolling performance combination
+        let names: Vec<&str> = optimization_space
+            .parameters
+            .iter()
+            .map(|parameter| parameter.name())
+            .collect();
+
+        if names.contains(&"combination") && names.contains(&"performance_window") {
+            optimization_space.conditions.push(ParameterCondition {
+                parameter: "performance_window".into(),
+                parent: "combination".into(),
+                values: vec!["rolling_performance".into()],
+            });
+        }
+
+        let strategy: Box<dyn IStrategy> = Box::new(self.clone());
+
+        let optimization_results = self
+            .optimizer(Box::new(RandomSearchOptimizer::new(48, 42)))
+            .optimize(&strategy, &optimization_space)
+            .unwrap_or(Vec::new());
+
+        Some(optimization_results)
+    }
+
+    fn backtest(
+        &self,
+        optimization_map: Option<&HashMap<String, CompositionDataType>>,
+    ) -> Result<BacktestResult, Box<dyn Error>> {
+        let backtest_manager = BacktestManager::new(BacktestOptions {
+            initial_capital: 1_000.0,
+            fees: 0.001,
+        });
+
+        let composition = self.composition();
+        let composition_data = self.composed_data();
+
+        let parameters = self.parameters_with_defaults(optimization_map);
+
+        let combination_method = CombinationMethod::from_category(
+            &CompositionDataType::extract_string(&parameters["combination"]),
+        )?;
+        let capital_ratio = CompositionDataType::extract_f32(&parameters["capital_ratio"]);
+        let rebalance_threshold =
+            CompositionDataType::extract_f32(&parameters["rebalance_threshold"]);
+        let performance_window = parameters
+            .get("performance_window")
+            .map(CompositionDataType::extract_usize)
+            .unwrap_or(60)
+            .max(1);
+
+        let timestamp_position = composition.get_composition_field_position("timestamp");
+        let close_position = composition.get_composition_field_position("close");
+
+        let children_signals = self
+            .children
+            .iter()
+            .map(|child| child.signals())
+            .collect::<Result<Vec<&ChildSignals>, Box<dyn Error>>>()?;
+
+        let mut rolling_performance =
+            RollingPerformance::new(self.children.len(), performance_window);
+        // Share of the target exposure each child was responsible for on the previous bar
+        let mut previous_shares: Vec<f32> = vec![0.0; self.children.len()];
+        let mut previous_close: Option<f32> = None;
+        let mut contributions: Vec<f32> = vec![0.0; self.children.len()];
+
+        let mut backtest_result = backtest_target_positions(
+            backtest_manager,
+            composition,
+            &composition_data,
+            "close",
+            TargetPositionOptions {
+                leverage: 1.0,
+                rebalance_threshold: Some(rebalance_threshold),
+            },
+            |composition_point| {
+                let timestamp =
+                    CompositionDataType::extract_i64(&composition_point[timestamp_position]);
+                let close = CompositionDataType::extract_f32(&composition_point[close_position]);
+
+                // Attributed before fees and rebalancing, the return of the asset over the
+                // bar times the exposure each child asked for on the bar before
+                if let Some(previous_close) = previous_close.replace(close) {
+                    let asset_return = (close - previous_close) / previous_close;
+
+                    for (contribution, share) in contributions.iter_mut().zip(&previous_shares) {
+                        *contribution += share * asset_return;
+                    }
+
+                    rolling_performance.allocate(asset_return);
+                }
+
+                let signals: Vec<f32> = children_signals
+                    .iter()
+                    .map(|child_signals| {
+                        // A child reading another composition may have no bar at this
+                        // timestamp, it holds the position of its last bar before it
+                        child_signals
+                            .signals
+                            .range(..=timestamp)
+                            .next_back()
+                            .map_or(0.0, |(_, signal)| *signal)
+                    })
+                    .collect();
+
+                let weights: Vec<f32> = match combination_method {
+                    CombinationMethod::RollingPerformance => rolling_performance.weights(),
+                    _ => self.children.iter().map(|child| child.weight).collect(),
+                };
+
+                let shares = combine(combination_method, &signals, &weights);
+                rolling_performance.hold(&signals);
+
+                previous_shares = shares.iter().map(|share| share * capital_ratio).collect();
+
+                let exposure: f32 = previous_shares.iter().sum();
+                Some(TargetPosition::Exposure(exposure))
+            },
+        );
+
+        for (child_signals, contribution) in children_signals.iter().zip(contributions) {
+            backtest_result.metrics.insert(
+                Metric::Custom(format!("Contribution {}", child_signals.name)),
+                contribution * 100.0,
+            );
+        }
+
+        Ok(backtest_result)
+    }
+
+    fn composition(&self) -> &'static dyn IComposition {
+        return ETH_STANDALONE_4H_4Y::instance();
+    }
+
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        vec![
+            ParameterSchema {
+                default: CompositionDataType::String("majority_vote".into()),
+                range: OptimizationParameter::Categoric(CategoricOptimizationParameter {
+                    name: "combination".into(),
+                    categories: CombinationMethod::CATEGORIES
+                        .iter()
+                        .map(|category| category.to_string())
+                        .collect(),
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::Usize(60),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "performance_window".into(),
+                    start: 10.0,
+                    end: 360.0,
+                    step: 10.0,
+                    kind: NumericKind::Integer,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::F32(0.30),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "capital_ratio".into(),
+                    start: 0.05,
+                    end: 0.9,
+                    step: 0.05,
+                    kind: NumericKind::Float,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::F32(0.05),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "rebalance_threshold".into(),
+                    start: 0.01,
+                    end: 0.3,
+                    step: 0.01,
+                    kind: NumericKind::Float,
+                    log_scale: false,
+                }),
+            },
+        ]
+    }
+
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        return Some(&self.overrides);
+    }
+
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        let mut strategy = self.clone();
+        strategy.overrides = overrides;
+
+        return Some(Box::new(strategy));
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl EnsembleStrategy {
+    pub fn new(name: &str, description: &str, children: Vec<(EnsembleChildBuilder, f32)>) -> Self {
+        return Self {
+            id: Uuid::new_v4().into(),
+            name: name.into(),
+            description: description.into(),
+            children: Arc::new(
+                children
+                    .into_iter()
+                    .map(|(build, weight)| EnsembleChild {
+                        build,
+                        weight,
+                        signals: OnceLock::new(),
+                    })
+                    .collect(),
+            ),
+            overrides: StrategyOverrides::default(),
+        };
+    }
+
+    /// The built-in ETH 4h strategies, equally weighted. They compose their data when the
+    /// ensemble first backtests rather than when the strategy list is built.
+    pub fn eth_4h() -> Self {
+        return Self::new(
+            "ETH 4h ensemble",
+            "Majority vote, weighted average or rolling performance weighting of the built-in ETH 4h strategies",
+            vec![
+                (Box::new(|| Box::new(Sma200Strategy::new())), 1.0),
+                (Box::new(|| Box::new(SmaOptimizablePeriodStrategy::new())), 1.0),
+                (Box::new(|| Box::new(DoubleSmaOptimizablePeriodStrategy::new())), 1.0),
+                (Box::new(|| Box::new(TheilSenOptimizeableStrategy::new())), 1.0),
+                (Box::new(|| Box::new(KalmanOptimizeableStrategy::new())), 1.0),
+                (Box::new(|| Box::new(SmaRenkoOptimizablePeriodStrategy::new())), 1.0),
+            ],
+        );
+    }
+}
+
+// Each child's share of the combined exposure, the shares sum to the exposure in -1..1
+fn combine(combination_method: CombinationMethod, signals: &[f32], weights: &[f32]) -> Vec<f32> {
+    let total_weight: f32 = weights.iter().map(|weight| weight.abs()).sum();
+
+    if total_weight <= 0.0 {
+        return vec![0.0; signals.len()];
+    }
+
+    match combination_method {
+        CombinationMethod::MajorityVote => {
+            let vote: f32 = signals
+                .iter()
+                .zip(weights)
+                .map(|(signal, weight)| signal_side(*signal) * weight)
+                .sum();
+            let side = signal_side(vote);
+
+            if side == 0.0 {
+                return vec![0.0; signals.len()];
+            }
+
+            // The position is split between the children that voted for its side
+            let agreeing_weight: f32 = signals
+                .iter()
+                .zip(weights)
+                .filter(|(signal, _)| signal_side(**signal) == side)
+                .map(|(_, weight)| weight)
+                .sum();
+
+            signals
+                .iter()
+                .zip(weights)
+                .map(|(signal, weight)| {
+                    if signal_side(*signal) == side {
+                        side * weight / agreeing_weight
+                    } else {
+                        0.0
+                    }
+                })
+                .collect()
+        }
+        CombinationMethod::WeightedAverage | CombinationMethod::RollingPerformance => signals
+            .iter()
+            .zip(weights)
+            .map(|(signal, weight)| signal * weight / total_weight)
+            .collect(),
+    }
+}
+
+fn signal_side(signal: f32) -> f32 {
+    if signal > 0.0 {
+        1.0
+    } else if signal < 0.0 {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+// Return each child's signal made over the last `window` bars, from the signal it held
+// going into every bar
+struct RollingPerformance {
+    window: usize,
+    held: Vec<f32>,
+    returns: Vec<VecDeque<f32>>,
+    totals: Vec<f32>,
+}
+
+impl RollingPerformance {
+    fn new(children: usize, window: usize) -> Self {
+        return Self {
+            window,
+            held: vec![0.0; children],
+            returns: vec![VecDeque::with_capacity(window); children],
+            totals: vec![0.0; children],
+        };
+    }
+
+    fn allocate(&mut self, asset_return: f32) {
+        for child in 0..self.held.len() {
+            let child_return = self.held[child] * asset_return;
+
+            self.returns[child].push_back(child_return);
+            self.totals[child] += child_return;
+
+            if self.returns[child].len() > self.window {
+                self.totals[child] -= self.returns[child].pop_front().unwrap();
+            }
+        }
+    }
+
+    fn hold(&mut self, signals: &[f32]) {
+        self.held.copy_from_slice(signals);
+    }
+
+    fn weights(&self) -> Vec<f32> {
+        self.totals.iter().map(|total| total.max(0.0)).collect()
+    }
+}
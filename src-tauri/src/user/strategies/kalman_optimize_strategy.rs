@@ -1,10 +1,15 @@
-use super::{BacktestManager, BacktestResult, IStrategy, Trade, TradeOptions, TradeSide};
+use super::{
+    BacktestManager, BacktestResult, IStrategy, ParameterSchema, StrategyOverrides, Trade,
+    TradeOptions, TradeSide,
+};
+use crate::library::engines::optimizers::space::OptimizationSpace;
 use crate::library::engines::optimizers::Optimizer;
 use crate::user::composer::eth_standalone_4h_4y_composition::ETH_STANDALONE_4H_4Y;
 use crate::user::library::kalman_filter::KalmanFilter;
 use crate::{
     library::engines::optimizers::grid::{
-        GridOptimizer, NumericOptimizationParameter, OptimizationParameter, OptimizedBacktestResult,
+        GridOptimizer, NumericKind, NumericOptimizationParameter, OptimizationParameter,
+        OptimizedBacktestResult,
     },
     user::{
         composer::{CompositionDataType, IComposition},
@@ -21,7 +26,7 @@ pub struct KalmanOptimizeableStrategy {
     id: String,
     name: String,
     description: String,
-    composition_data: Option<Vec<Vec<CompositionDataType>>>,
+    overrides: StrategyOverrides,
 }
 
 impl IStrategy for KalmanOptimizeableStrategy {
@@ -38,31 +43,14 @@ impl IStrategy for KalmanOptimizeableStrategy {
     }
 
     fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
-        let optimization_parameters = [
-            OptimizationParameter::Numeric(NumericOptimizationParameter {
-                name: "q_noise".into(),
-                start: 0.1,
-                end: 2.0,
-                step: 0.5,
-            }),
-            OptimizationParameter::Numeric(NumericOptimizationParameter {
-                name: "r_noise".into(),
-                start: 1.0,
-                end: 50.0,
-                step: 15.0,
-            }),
-            OptimizationParameter::Numeric(NumericOptimizationParameter {
-                name: "capital_ratio".into(),
-                start: 0.1,
-                end: 0.9,
-                step: 0.4,
-            }),
-        ];
+        let optimization_space = OptimizationSpace::new(self.optimization_ranges());
 
         let strategy: Box<dyn IStrategy> = Box::new(self.clone());
 
-        let optimization_results =
-            GridOptimizer::optimize(&strategy, &optimization_parameters).unwrap_or(Vec::new());
+        let optimization_results = self
+            .optimizer(Box::new(GridOptimizer::new()))
+            .optimize(&strategy, &optimization_space)
+            .unwrap_or(Vec::new());
 
         Some(optimization_results)
     }
@@ -76,13 +64,11 @@ impl IStrategy for KalmanOptimizeableStrategy {
             fees: 0.001,
         });
 
-        let composition: &'static dyn IComposition = self.composition();
+        let composition: &dyn IComposition = self.composition();
         let composition_data = self.composed_data();
 
-        if optimization_map.is_none() {
-            let backtest_result = backtest_manager.backtest_end();
-            return Ok(backtest_result);
-        }
+        let parameters = self.parameters_with_defaults(optimization_map);
+        let optimization_map = Some(&parameters);
 
         let q_noise_comp = optimization_map
             .unwrap()
@@ -101,7 +87,7 @@ impl IStrategy for KalmanOptimizeableStrategy {
             .to_owned();
 
         let q_noise = CompositionDataType::extract_f32(&q_noise_comp);
-        let r_noise = CompositionDataType::extract_usize(&r_noise_comp) as f32;
+        let r_noise = CompositionDataType::extract_f32(&r_noise_comp);
         let capital_ratio = CompositionDataType::extract_f32(&capital_ratio_comp);
         let timestamp_position = composition.get_composition_field_position("timestamp");
         let close_position = composition.get_composition_field_position("close");
@@ -181,12 +167,53 @@ impl IStrategy for KalmanOptimizeableStrategy {
         }
     }
 
-    fn composed_data(&self) -> Vec<Vec<CompositionDataType>> {
-        if self.composition_data.is_some() {
-            return self.composition_data.as_ref().unwrap().to_vec();
-        }
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        vec![
+            ParameterSchema {
+                default: CompositionDataType::F32(0.1),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "q_noise".into(),
+                    start: 0.1,
+                    end: 2.0,
+                    step: 0.5,
+                    kind: NumericKind::Float,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::F32(1.0),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "r_noise".into(),
+                    start: 1.0,
+                    end: 50.0,
+                    step: 15.0,
+                    kind: NumericKind::Float,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::F32(0.30),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "capital_ratio".into(),
+                    start: 0.1,
+                    end: 0.9,
+                    step: 0.4,
+                    kind: NumericKind::Float,
+                    log_scale: false,
+                }),
+            },
+        ]
+    }
+
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        return Some(&self.overrides);
+    }
+
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        let mut strategy = self.clone();
+        strategy.overrides = overrides;
 
-        return self.composition().compose().unwrap();
+        return Some(Box::new(strategy));
     }
 
     fn save(&self) -> Result<(), Box<dyn Error>> {
@@ -200,10 +227,10 @@ impl KalmanOptimizeableStrategy {
             id: Uuid::new_v4().into(),
             name: "Kalman".into(),
             description: "Kalman".into(),
-            composition_data: None,
+            overrides: StrategyOverrides::default(),
         };
 
-        strategy.composition_data = Some(strategy.composition().compose().unwrap());
+        strategy.overrides.composition_data = Some(strategy.composition().compose().unwrap());
 
         return strategy;
     }
@@ -0,0 +1,280 @@
+use super::target_position::{backtest_target_positions, TargetPosition, TargetPositionOptions};
+use super::{
+    BacktestManager, BacktestOptions, BacktestResult, IStrategy, ParameterSchema, StrategyOverrides,
+};
+use crate::{
+    library::engines::{
+        ml::{
+            features::{Feature, FeatureSet},
+            labels::Labeling,
+            pipeline::MlPipeline,
+            walk_forward::WalkForward,
+            ModelKind,
+        },
+        optimizers::{
+            grid::{
+                CategoricOptimizationParameter, NumericKind, NumericOptimizationParameter,
+                OptimizationParameter, OptimizedBacktestResult,
+            },
+            random_search::RandomSearchOptimizer,
+            space::{OptimizationSpace, ParameterCondition},
+            Optimizer,
+        },
+    },
+    user::composer::{
+        eth_hlc_standalone_4h_4y::ETH_HLC_STANDALONE_4H_4Y, CompositionDataType, IComposition,
+    },
+};
+use std::{collections::HashMap, error::Error};
+use uuid::Uuid;
+
+// Trades the walk-forward predictions of a model trained on returns, volatility and the
+// bar's range: long when the model leans up by more than `threshold`, short when it
+// leans down and flat in between
+#[derive(Clone)]
+pub struct MlStrategy {
+    id: String,
+    name: String,
+    description: String,
+    overrides: StrategyOverrides,
+}
+
+impl IStrategy for MlStrategy {
+    fn id(&self) -> &str {
+        return &self.id;
+    }
+
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
+        let mut optimization_space = OptimizationSpace::new(self.optimization_ranges());
+
+        // The barrier is only read by triple barrier labels
+        let names: Vec<&str> = optimization_space
+            .parameters
+            .iter()
+            .map(|parameter| parameter.name())
+            .collect();
+
+        if names.contains(&"labeling") && names.contains(&"barrier") {
+            optimization_space.conditions.push(ParameterCondition {
+                parameter: "barrier".into(),
+                parent: "labeling".into(),
+                values: vec!["triple_barrier".into()],
+            });
+        }
+
+        let strategy: Box<dyn IStrategy> = Box::new(self.clone());
+
+        // Every backtest retrains the model along the whole composition
+        let optimization_results = self
+            .optimizer(Box::new(RandomSearchOptimizer::new(24, 42)))
+            .optimize(&strategy, &optimization_space)
+            .unwrap_or(Vec::new());
+
+        Some(optimization_results)
+    }
+
+    fn backtest(
+        &self,
+        optimization_map: Option<&HashMap<String, CompositionDataType>>,
+    ) -> Result<BacktestResult, Box<dyn Error>> {
+        let backtest_manager = BacktestManager::new(BacktestOptions {
+            initial_capital: 1_000.0,
+            fees: 0.001,
+        });
+
+        let composition = self.composition();
+        let composition_data = self.composed_data();
+
+        let parameters = self.parameters_with_defaults(optimization_map);
+
+        let model =
+            ModelKind::from_category(&CompositionDataType::extract_string(&parameters["model"]))?;
+        let labeling = Labeling::from_category(
+            &CompositionDataType::extract_string(&parameters["labeling"]),
+            CompositionDataType::extract_usize(&parameters["horizon"]),
+            CompositionDataType::extract_f32(&parameters["barrier"]),
+        )?;
+        let train_window = CompositionDataType::extract_usize(&parameters["train_window"]);
+        let retrain_every = CompositionDataType::extract_usize(&parameters["retrain_every"]);
+        let threshold = CompositionDataType::extract_f32(&parameters["threshold"]);
+        let capital_ratio = CompositionDataType::extract_f32(&parameters["capital_ratio"]);
+
+        let pipeline = MlPipeline {
+            feature_set: FeatureSet::new(
+                "close",
+                vec![
+                    Feature::Return(1),
+                    Feature::Return(3),
+                    Feature::Return(6),
+                    Feature::Return(12),
+                    Feature::Return(24),
+                    Feature::Return(48),
+                    Feature::Volatility(24),
+                    Feature::Volatility(96),
+                    Feature::RelativeField("high".into()),
+                    Feature::RelativeField("low".into()),
+                ],
+            ),
+            labeling,
+            model,
+            walk_forward: WalkForward::new(train_window, retrain_every, train_window / 4),
+            seed: 42,
+        };
+
+        let mut signals = pipeline
+            .signals(composition, &composition_data)?
+            .into_iter();
+
+        let backtest_result = backtest_target_positions(
+            backtest_manager,
+            composition,
+            &composition_data,
+            "close",
+            TargetPositionOptions::default(),
+            |_| {
+                let signal = signals.next().flatten()?;
+
+                let exposure = if signal > threshold {
+                    capital_ratio
+                } else if signal < -threshold {
+                    -capital_ratio
+                } else {
+                    0.0
+                };
+
+                Some(TargetPosition::Exposure(exposure))
+            },
+        );
+
+        Ok(backtest_result)
+    }
+
+    fn composition(&self) -> &'static dyn IComposition {
+        return ETH_HLC_STANDALONE_4H_4Y::instance();
+    }
+
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        vec![
+            ParameterSchema {
+                default: CompositionDataType::String("logistic_regression".into()),
+                range: OptimizationParameter::Categoric(CategoricOptimizationParameter {
+                    name: "model".into(),
+                    categories: ModelKind::CATEGORIES
+                        .iter()
+                        .map(|category| category.to_string())
+                        .collect(),
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::String("forward_return".into()),
+                range: OptimizationParameter::Categoric(CategoricOptimizationParameter {
+                    name: "labeling".into(),
+                    categories: Labeling::CATEGORIES
+                        .iter()
+                        .map(|category| category.to_string())
+                        .collect(),
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::Usize(6),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "horizon".into(),
+                    start: 1.0,
+                    end: 48.0,
+                    step: 1.0,
+                    kind: NumericKind::Integer,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::F32(0.03),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "barrier".into(),
+                    start: 0.01,
+                    end: 0.1,
+                    step: 0.005,
+                    kind: NumericKind::Float,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::Usize(1500),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "train_window".into(),
+                    start: 500.0,
+                    end: 4000.0,
+                    step: 250.0,
+                    kind: NumericKind::Integer,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::Usize(180),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "retrain_every".into(),
+                    start: 60.0,
+                    end: 720.0,
+                    step: 60.0,
+                    kind: NumericKind::Integer,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::F32(0.05),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "threshold".into(),
+                    start: 0.0,
+                    end: 0.3,
+                    step: 0.01,
+                    kind: NumericKind::Float,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::F32(0.30),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "capital_ratio".into(),
+                    start: 0.05,
+                    end: 0.9,
+                    step: 0.05,
+                    kind: NumericKind::Float,
+                    log_scale: false,
+                }),
+            },
+        ]
+    }
+
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        return Some(&self.overrides);
+    }
+
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        let mut strategy = self.clone();
+        strategy.overrides = overrides;
+
+        return Some(Box::new(strategy));
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl MlStrategy {
+    pub fn new() -> Self {
+        return Self {
+            id: Uuid::new_v4().into(),
+            name: "Machine learning walk-forward".into(),
+            description: "Logistic regression, gradient boosted trees or a random forest retrained walk-forward on ETH 4h returns and volatility".into(),
+            overrides: StrategyOverrides::default(),
+        };
+    }
+}
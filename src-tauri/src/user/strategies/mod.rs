@@ -1,28 +1,44 @@
 use crate::{
-    library::engines::optimizers::grid::{OptimizationParameter, OptimizedBacktestResult},
+    library::engines::optimizers::{
+        grid::{OptimizationParameter, OptimizedBacktestResult},
+        objective_function::ObjectiveFunction,
+        pareto::Objective,
+        Optimizer, OptimizerKind,
+    },
     user::{
         composer::{CompositionDataType, IComposition},
         library::{
-            beta::Beta,
-            injectables::formulas::{
-                apr::APR,
-                consecutive_wins_losses::{self, ConsecutiveWinsLosses},
-                sharpe_ratio::SharpeRatio,
-                standard_deviation::StandardDeviation,
-            },
-            kalman_filter, IInjectable,
+            kalman_filter,
+            metrics::{IMetric, MetricContext, MetricInput, MetricRegistry},
+            rolling_beta::RollingBeta,
+            rolling_correlation::RollingCorrelation,
+            rolling_drawdown::RollingDrawdown,
+            rolling_sharpe_ratio::RollingSharpeRatio,
+            rolling_volatility::RollingVolatility,
+            rolling_win_rate::RollingWinRate,
+            IInjectable,
         },
+        scripting::SCRIPT_REGISTRY,
     },
     utils::classes::charting::{ChartingData, LineChartingData, LineData},
 };
 use std::{collections::HashMap, error::Error, hash::Hash};
-use std::{sync::LazyLock, time::Instant};
+use std::{
+    sync::{Arc, LazyLock},
+    time::Instant,
+};
 pub mod double_sma_optimize_strategy;
+pub mod ensemble_strategy;
 pub mod kalman_optimize_strategy;
+pub mod ml_strategy;
 pub mod renko_sma_optimize;
+pub mod rule_strategy;
 pub mod sma_200_strategy;
 pub mod sma_optimizable_period_strategy;
+pub mod sma_target_exposure_strategy;
+pub mod target_position;
 pub mod theilsen_optimize_strategy;
+pub mod wasm_strategy;
 use rayon::option;
 use serde::{Deserialize, Serialize};
 use std::marker::Copy;
@@ -34,10 +50,10 @@ pub enum TradeSide {
     SHORT,
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Trade {
     id: Uuid,
-    asset_name: &'static str,
+    asset_name: String,
     open_timestamp: Option<i64>,
     close_timestamp: Option<i64>,
     capital_allocation: Option<f32>,
@@ -224,7 +240,7 @@ impl Trade {
     pub fn new(trade_options: TradeOptions) -> Self {
         return Self {
             id: Uuid::new_v4(),
-            asset_name: trade_options.asset_name,
+            asset_name: trade_options.asset_name.into(),
             open_timestamp: None,
             close_timestamp: None,
             open_price: None,
@@ -245,10 +261,41 @@ impl Trade {
     }
 }
 
+// Metrics are saved as a list of pairs since `Metric::Custom` can't be a JSON key,
+// NaN values go through as null
+pub mod metric_pairs {
+    use super::Metric;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(
+        metrics: &HashMap<Metric, f32>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let pairs: Vec<(&Metric, Option<f32>)> = metrics
+            .iter()
+            .map(|(metric, value)| (metric, if value.is_nan() { None } else { Some(*value) }))
+            .collect();
+
+        pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<Metric, f32>, D::Error> {
+        let pairs = Vec::<(Metric, Option<f32>)>::deserialize(deserializer)?;
+
+        Ok(pairs
+            .into_iter()
+            .map(|(metric, value)| (metric, value.unwrap_or(f32::NAN)))
+            .collect())
+    }
+}
+
 // Probably make builder pattern
 #[derive(Debug)]
-pub struct TradeOptions {
-    pub asset_name: &'static str,
+pub struct TradeOptions<'a> {
+    pub asset_name: &'a str,
     pub side: TradeSide,
     pub capital_allocation: Option<f32>,
     pub leverage: Option<f32>,
@@ -300,6 +347,24 @@ pub enum Metric {
     RecoveryFactor,
     RiskRewardRatio,
     WinRate,
+
+    // User defined metrics registered through `MetricRegistry`
+    Custom(String),
+}
+
+impl Metric {
+    pub fn key(&self) -> String {
+        match self {
+            Metric::Custom(name) => name.clone(),
+            metric => format!("{:?}", metric),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp: i64,
+    pub value: f32,
 }
 
 #[derive(Clone, Debug)]
@@ -312,7 +377,12 @@ pub struct BacktestManager {
     fees: f32,
     available_capital: f32,
     trades: Vec<Trade>,
+    open_trade_indices: Vec<usize>,
     computational_metrics: HashMap<Metric, f32>,
+    metric_registry: MetricRegistry,
+    samples_equity: bool,
+    equity_series: Vec<EquityPoint>,
+    benchmark_series: Vec<f32>,
     instant: Instant,
     backtest_ended: bool,
     backtest_result: Option<BacktestResult>,
@@ -327,11 +397,22 @@ impl BacktestManager {
         return self.available_capital;
     }
 
+    pub fn fees(&self) -> f32 {
+        return self.fees;
+    }
+
+    /// Latest price given to `update_price` for the asset.
+    pub fn asset_price(&self, asset_name: &str) -> Option<f32> {
+        self.asset_prices
+            .get(asset_name)
+            .map(|timestamp_price| timestamp_price.1)
+    }
+
     pub fn current_portfolio_value(&self) -> f32 {
         let mut total_value = self.available_capital;
 
         for trade in &self.trades {
-            if let Some(timestamp_price) = self.asset_prices.get(trade.asset_name) {
+            if let Some(timestamp_price) = self.asset_prices.get(&trade.asset_name) {
                 if !trade.is_closed() {
                     total_value += trade.pl_unrealized_fixed(Some(timestamp_price.1));
                 }
@@ -341,6 +422,24 @@ impl BacktestManager {
         total_value
     }
 
+    /// Cash plus the marked-to-market value of every open trade.
+    pub fn equity(&self) -> f32 {
+        let mut equity = self.available_capital;
+
+        for &trade_index in &self.open_trade_indices {
+            let trade = &self.trades[trade_index];
+            let current_price = self
+                .asset_prices
+                .get(&trade.asset_name)
+                .map(|timestamp_price| timestamp_price.1);
+
+            equity += trade.capital_allocation().unwrap_or(0.0)
+                + trade.pl_unrealized_fixed(current_price);
+        }
+
+        equity
+    }
+
     pub fn update_price(&mut self, asset_name: &str, timestamp: i64, price: f32) {
         if self.backtest_ended {
             return;
@@ -348,6 +447,32 @@ impl BacktestManager {
 
         self.asset_prices
             .insert(asset_name.into(), TimestampPrice(timestamp, price));
+
+        if self.samples_equity
+            && self
+                .equity_series
+                .last()
+                .map_or(true, |equity_point| equity_point.timestamp < timestamp)
+        {
+            let equity = self.equity();
+            self.equity_series.push(EquityPoint {
+                timestamp,
+                value: equity,
+            });
+        }
+    }
+
+    pub fn update_benchmark(&mut self, price: f32) {
+        if self.backtest_ended {
+            return;
+        }
+
+        self.benchmark_series.push(price);
+    }
+
+    pub fn register_metric(&mut self, metric: Arc<dyn IMetric>) {
+        self.metric_registry.register(metric);
+        self.samples_equity = self.metric_registry.requires(MetricInput::EquitySeries);
     }
 
     pub fn open_trade(&mut self, trade: &mut Trade) {
@@ -359,7 +484,7 @@ impl BacktestManager {
 
         let needed = trade.required_cash_to_open(self.fees);
         if self.available_capital() >= needed {
-            if let Some(timestamp_price) = self.asset_prices.get(trade.asset_name) {
+            if let Some(timestamp_price) = self.asset_prices.get(&trade.asset_name) {
                 let cash_delta = trade.apply_open(
                     timestamp_price.0,
                     timestamp_price.1,
@@ -367,7 +492,8 @@ impl BacktestManager {
                     self.fees,
                 );
                 self.adjust_available_capital(cash_delta);
-                self.trades.push(*trade);
+                self.open_trade_indices.push(self.trades.len());
+                self.trades.push(trade.clone());
             }
         }
     }
@@ -379,11 +505,14 @@ impl BacktestManager {
 
         self.check_capital();
 
-        if let Some(existing_trade) = self.trades.iter_mut().find(|t| t.id() == trade.id()) {
-            if let Some(timestamp_price) = self.asset_prices.get(existing_trade.asset_name) {
+        if let Some(trade_index) = self.trades.iter().position(|t| t.id() == trade.id()) {
+            let existing_trade = &mut self.trades[trade_index];
+            if let Some(timestamp_price) = self.asset_prices.get(&existing_trade.asset_name) {
                 let cash_delta =
                     existing_trade.apply_close(timestamp_price.0, timestamp_price.1, self.fees);
                 self.adjust_available_capital(cash_delta);
+                self.open_trade_indices
+                    .retain(|&open_trade_index| open_trade_index != trade_index);
             }
         }
     }
@@ -448,6 +577,8 @@ impl BacktestManager {
 
     pub fn new(options: BacktestOptions) -> Self {
         let computational_metrics: HashMap<Metric, f32> = HashMap::new();
+        let metric_registry = MetricRegistry::default();
+        let samples_equity = metric_registry.requires(MetricInput::EquitySeries);
 
         return Self {
             initial_capital: options.initial_capital,
@@ -455,7 +586,12 @@ impl BacktestManager {
             available_capital: options.initial_capital,
             asset_prices: HashMap::new(),
             trades: Vec::new(),
+            open_trade_indices: Vec::new(),
             computational_metrics,
+            metric_registry,
+            samples_equity,
+            equity_series: Vec::new(),
+            benchmark_series: Vec::new(),
             instant: Instant::now(),
             backtest_ended: false,
             backtest_result: None,
@@ -473,6 +609,10 @@ pub struct BacktestResult {
     growth_capital: f32,
     trades: Vec<Trade>,
     metrics: HashMap<Metric, f32>,
+    equity_series: Vec<EquityPoint>,
+    benchmark_series: Vec<f32>,
+    // Read back from a study, the series are empty whatever the strategy sampled
+    from_study: bool,
 }
 
 impl BacktestResult {
@@ -491,40 +631,79 @@ impl BacktestResult {
     pub fn metrics(&self) -> &HashMap<Metric, f32> {
         return &self.metrics;
     }
-}
 
-impl BacktestResult {
-    pub fn from(backtest_manager: BacktestManager) -> Self {
-        let mut metrics: HashMap<Metric, f32> = HashMap::new();
+    pub fn equity_series(&self) -> &Vec<EquityPoint> {
+        return &self.equity_series;
+    }
 
-        // Maybe compute these on backtest_manager side as to have an O(1) metrics compute solution
-        let mut sharpe = SharpeRatio::new(Some(0.0));
-        let mut standard_deviation = StandardDeviation::new();
-        let mut apr = APR::new();
-        let mut consecutive_wins_losses = ConsecutiveWinsLosses::new();
+    pub fn benchmark_series(&self) -> &Vec<f32> {
+        return &self.benchmark_series;
+    }
 
-        let mut valid_trades: Vec<Trade> = vec![];
+    /// Whether the result was read back from an optimization study, without its equity
+    /// and benchmark series.
+    pub fn is_from_study(&self) -> bool {
+        return self.from_study;
+    }
 
-        for trade in backtest_manager.trades() {
-            if !trade.is_closed() {
-                continue;
-            }
+    pub fn equity_returns(&self) -> Vec<f32> {
+        return self
+            .equity_series
+            .windows(2)
+            .map(|pair| {
+                if pair[0].value == 0.0 {
+                    0.0
+                } else {
+                    (pair[1].value - pair[0].value) / pair[0].value
+                }
+            })
+            .collect();
+    }
+}
 
-            valid_trades.push(trade.to_owned());
+impl BacktestResult {
+    /// Result read back from an optimization study, which keeps the trades and metrics
+    /// but not the equity and benchmark series.
+    pub fn from_study(trades: Vec<Trade>, metrics: HashMap<Metric, f32>) -> Self {
+        let growth_capital = metrics
+            .get(&Metric::TotalDollarReturn)
+            .copied()
+            .unwrap_or(0.0);
+        let initial_capital = match metrics.get(&Metric::TotalRatioReturn) {
+            Some(&total_ratio_return) if total_ratio_return != 0.0 => {
+                growth_capital / total_ratio_return * 100.0
+            }
+            _ => 0.0,
+        };
 
-            let pl_portfolio = trade.pl_portfolio();
-            let pl_ratio = trade.pl_ratio();
+        return Self {
+            initial_capital,
+            growth_capital,
+            trades,
+            metrics,
+            equity_series: vec![],
+            benchmark_series: vec![],
+            from_study: true,
+        };
+    }
 
-            apr.allocate(pl_portfolio / 100.0);
-            sharpe.allocate(pl_portfolio);
-            standard_deviation.allocate(pl_portfolio);
-            consecutive_wins_losses.allocate(pl_ratio);
-        }
+    pub fn from(backtest_manager: BacktestManager) -> Self {
+        let valid_trades: Vec<Trade> = backtest_manager
+            .trades()
+            .iter()
+            .filter(|trade| trade.is_closed())
+            .map(|trade| trade.to_owned())
+            .collect();
+
+        let metric_context = MetricContext {
+            initial_capital: backtest_manager.initial_capital(),
+            final_capital: backtest_manager.available_capital(),
+            trades: &valid_trades,
+            equity_series: &backtest_manager.equity_series,
+            benchmark_series: &backtest_manager.benchmark_series,
+        };
 
-        let sharpe = sharpe.get_data().unwrap_or(0.0);
-        let standard_deviation = standard_deviation.get_data().unwrap_or(0.0);
-        let apr = apr.get_data().unwrap_or(0.0);
-        let consecutive_wins_losses = consecutive_wins_losses.get_data().unwrap_or((0, 0));
+        let mut metrics = backtest_manager.metric_registry.compute(&metric_context);
 
         let performance_time = backtest_manager
             .computational_metrics
@@ -535,36 +714,54 @@ impl BacktestResult {
         let total_ratio_returns =
             (total_dollar_returns / backtest_manager.initial_capital()) * 100.0;
 
-        // Maybe expand upon metrics with MetricType
-        metrics.insert(Metric::StandardDeviation, standard_deviation);
-        metrics.insert(Metric::SharpeRatio, sharpe);
         metrics.insert(Metric::PerformanceTime, performance_time);
         metrics.insert(Metric::TotalDollarReturn, total_dollar_returns);
         metrics.insert(Metric::TotalRatioReturn, total_ratio_returns);
-        metrics.insert(Metric::APR, apr);
-        metrics.insert(
-            Metric::MostConsecutiveWins,
-            consecutive_wins_losses.0 as f32,
-        );
-        metrics.insert(
-            Metric::MostConsecutiveLosses,
-            consecutive_wins_losses.1 as f32,
-        );
 
         return Self {
             initial_capital: backtest_manager.initial_capital(),
             growth_capital: backtest_manager.available_capital(),
             trades: valid_trades,
             metrics,
+            equity_series: backtest_manager.equity_series,
+            benchmark_series: backtest_manager.benchmark_series,
+            from_study: false,
         };
     }
 }
 
+// A parameter `backtest` reads: optimizations search `range` and a backtest without
+// parameters runs with `default`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ParameterSchema {
+    pub default: CompositionDataType,
+    pub range: OptimizationParameter,
+}
+
+// What a copy of a strategy runs with instead of its own composition, target and schema
+// ranges. Strategies keeping one get the clone-and-set methods of `IStrategy` through
+// `overrides` and `with_overrides`
+#[derive(Clone, Default)]
+pub struct StrategyOverrides {
+    pub composition_data: Option<Vec<Vec<CompositionDataType>>>,
+    pub objective_function: Option<ObjectiveFunction>,
+    pub optimization_ranges: Option<Vec<OptimizationParameter>>,
+    // Id of the `OPTIMIZATION_MONITOR` run the copy's evaluations report to
+    pub optimization_run: Option<String>,
+    pub optimizer: Option<OptimizerKind>,
+    pub optimization_objectives: Option<Vec<Objective>>,
+}
+
 pub trait IStrategy: Send + Sync {
     fn id(&self) -> &str;
     fn name(&self) -> &str;
     fn description(&self) -> &str;
-    fn composition(&self) -> &'static dyn IComposition;
+    // Hash of what a strategy loaded at runtime (a rule file, a script) is defined by,
+    // editing the definition then starts a new optimization study
+    fn definition_hash(&self) -> Option<u64> {
+        None
+    }
+    fn composition(&self) -> &dyn IComposition;
     // fn wfo(&self, optimizer: OptimizationStrategy) {}
     fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
         None
@@ -578,11 +775,147 @@ pub trait IStrategy: Send + Sync {
 
         return sharpe;
     }
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        None
+    }
+    // Copy of the strategy with the given overrides, `None` when it keeps none
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        None
+    }
+    // Objective expression set from the frontend, it replaces `optimization_target`
+    fn objective_function(&self) -> Option<&ObjectiveFunction> {
+        self.overrides()?.objective_function.as_ref()
+    }
+    fn with_objective_function(
+        &self,
+        objective_function: ObjectiveFunction,
+    ) -> Option<Box<dyn IStrategy>> {
+        self.with_overrides(StrategyOverrides {
+            objective_function: Some(objective_function),
+            ..self.overrides()?.clone()
+        })
+    }
+    // Optimizer picked from the frontend, `optimize` runs it instead of its own
+    fn optimizer_kind(&self) -> Option<OptimizerKind> {
+        self.overrides()?.optimizer
+    }
+    fn with_optimizer_kind(&self, optimizer_kind: OptimizerKind) -> Option<Box<dyn IStrategy>> {
+        self.with_overrides(StrategyOverrides {
+            optimizer: Some(optimizer_kind),
+            ..self.overrides()?.clone()
+        })
+    }
+    // What `optimize` runs, `default` unless an optimizer was picked
+    fn optimizer(&self, default: Box<dyn Optimizer>) -> Box<dyn Optimizer> {
+        match self.optimizer_kind() {
+            Some(optimizer_kind) => optimizer_kind.optimizer(),
+            None => default,
+        }
+    }
+    fn optimization_run(&self) -> Option<&str> {
+        self.overrides()?.optimization_run.as_deref()
+    }
+    fn with_optimization_run(&self, run_id: String) -> Option<Box<dyn IStrategy>> {
+        self.with_overrides(StrategyOverrides {
+            optimization_run: Some(run_id),
+            ..self.overrides()?.clone()
+        })
+    }
+    // What optimizers maximize
+    fn score(&self, backtest_result: &BacktestResult) -> f32 {
+        match self.objective_function() {
+            Some(objective_function) => objective_function.evaluate(backtest_result),
+            None => self.optimization_target(backtest_result),
+        }
+    }
+    // More than one objective turns optimization multi-objective, results are then
+    // ranked by Pareto dominance on top of `optimization_target`. Opted into per
+    // request, a strategy optimizes its single target otherwise
+    fn optimization_objectives(&self) -> Vec<Objective> {
+        self.overrides()
+            .and_then(|overrides| overrides.optimization_objectives.clone())
+            .unwrap_or_default()
+    }
+    fn with_optimization_objectives(
+        &self,
+        objectives: Vec<Objective>,
+    ) -> Option<Box<dyn IStrategy>> {
+        self.with_overrides(StrategyOverrides {
+            optimization_objectives: Some(objectives),
+            ..self.overrides()?.clone()
+        })
+    }
     fn backtest(
         &self,
         optimization_map: Option<&HashMap<String, CompositionDataType>>,
     ) -> Result<BacktestResult, Box<dyn Error>>;
-    fn composed_data(&self) -> Vec<Vec<CompositionDataType>>;
+    // Parameters `backtest` reads, empty when it reads none
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        vec![]
+    }
+    // The schema's defaults overridden by the given parameters, what `backtest` runs with
+    fn parameters_with_defaults(
+        &self,
+        parameters: Option<&HashMap<String, CompositionDataType>>,
+    ) -> HashMap<String, CompositionDataType> {
+        let mut parameters_with_defaults: HashMap<String, CompositionDataType> = self
+            .parameter_schema()
+            .into_iter()
+            .map(|parameter| (parameter.range.name().to_string(), parameter.default))
+            .collect();
+
+        if let Some(parameters) = parameters {
+            for (name, value) in parameters {
+                parameters_with_defaults.insert(name.clone(), value.clone());
+            }
+        }
+
+        return parameters_with_defaults;
+    }
+    // What `optimize` searches, the schema's ranges unless the user edited them
+    fn optimization_ranges(&self) -> Vec<OptimizationParameter> {
+        if let Some(optimization_ranges) = self
+            .overrides()
+            .and_then(|overrides| overrides.optimization_ranges.as_ref())
+        {
+            return optimization_ranges.clone();
+        }
+
+        self.parameter_schema()
+            .into_iter()
+            .map(|parameter| parameter.range)
+            .collect()
+    }
+    fn with_optimization_ranges(
+        &self,
+        optimization_ranges: Vec<OptimizationParameter>,
+    ) -> Option<Box<dyn IStrategy>> {
+        self.with_overrides(StrategyOverrides {
+            optimization_ranges: Some(optimization_ranges),
+            ..self.overrides()?.clone()
+        })
+    }
+    fn composed_data(&self) -> Vec<Vec<CompositionDataType>> {
+        if let Some(composition_data) = self
+            .overrides()
+            .and_then(|overrides| overrides.composition_data.as_ref())
+        {
+            return composition_data.to_vec();
+        }
+
+        return self.composition().compose().unwrap();
+    }
+    // Copy of the strategy backtesting over the given rows instead of its composition,
+    // pruning optimizers use it to evaluate candidates on prefixes of the data
+    fn with_composed_data(
+        &self,
+        composed_data: Vec<Vec<CompositionDataType>>,
+    ) -> Option<Box<dyn IStrategy>> {
+        self.with_overrides(StrategyOverrides {
+            composition_data: Some(composed_data),
+            ..self.overrides()?.clone()
+        })
+    }
     fn render_equity_growth(&self, backtest_result: &BacktestResult) -> Vec<ChartingData> {
         let mut charting_data: Vec<ChartingData> = Vec::new();
 
@@ -668,16 +1001,147 @@ pub trait IStrategy: Send + Sync {
 
         return charting_data;
     }
+    fn rolling_window(&self) -> usize {
+        180
+    }
+    fn rolling_trades_window(&self) -> usize {
+        20
+    }
+    fn render_rolling_metrics(&self, backtest_result: &BacktestResult) -> Vec<ChartingData> {
+        let mut charting_data: Vec<ChartingData> = Vec::new();
+
+        let window = self.rolling_window();
+        let equity_series = backtest_result.equity_series();
+        let equity_returns = backtest_result.equity_returns();
+        let equity_timestamps: Vec<i64> = equity_series
+            .iter()
+            .map(|equity_point| equity_point.timestamp)
+            .collect();
+        let returns_timestamps: Vec<i64> = equity_timestamps.iter().skip(1).copied().collect();
+
+        let mut rolling_sharpe = RollingSharpeRatio::new(window, None, None);
+        let mut rolling_volatility = RollingVolatility::new(window, None);
+        let mut rolling_drawdown = RollingDrawdown::new(window);
+
+        for &equity_return in &equity_returns {
+            rolling_sharpe.allocate(equity_return);
+            rolling_volatility.allocate(equity_return);
+        }
+
+        for equity_point in equity_series {
+            rolling_drawdown.allocate(equity_point.value);
+        }
+
+        charting_data.extend(
+            rolling_sharpe
+                .render(returns_timestamps.clone())
+                .unwrap_or_default(),
+        );
+        charting_data.extend(
+            rolling_volatility
+                .render(returns_timestamps.clone())
+                .unwrap_or_default(),
+        );
+        charting_data.extend(
+            rolling_drawdown
+                .render(equity_timestamps)
+                .unwrap_or_default(),
+        );
+
+        let benchmark_series = backtest_result.benchmark_series();
+        if benchmark_series.len() > 1 {
+            let benchmark_returns: Vec<f32> = benchmark_series
+                .windows(2)
+                .map(|pair| {
+                    if pair[0] == 0.0 {
+                        0.0
+                    } else {
+                        (pair[1] - pair[0]) / pair[0]
+                    }
+                })
+                .collect();
+
+            // Both series are sampled once per bar, align them on their most recent points
+            let length = equity_returns.len().min(benchmark_returns.len());
+            let mut rolling_beta = RollingBeta::new(window);
+            let mut rolling_correlation = RollingCorrelation::new(window);
+
+            for (&equity_return, &benchmark_return) in equity_returns
+                [equity_returns.len() - length..]
+                .iter()
+                .zip(benchmark_returns[benchmark_returns.len() - length..].iter())
+            {
+                rolling_beta.allocate((equity_return, benchmark_return));
+                rolling_correlation.allocate((equity_return, benchmark_return));
+            }
+
+            let aligned_timestamps: Vec<i64> =
+                returns_timestamps[returns_timestamps.len() - length..].to_vec();
+
+            charting_data.extend(
+                rolling_beta
+                    .render(aligned_timestamps.clone())
+                    .unwrap_or_default(),
+            );
+            charting_data.extend(
+                rolling_correlation
+                    .render(aligned_timestamps)
+                    .unwrap_or_default(),
+            );
+        }
+
+        let mut rolling_win_rate = RollingWinRate::new(self.rolling_trades_window());
+        let mut trades_timestamps: Vec<i64> = Vec::new();
+
+        for trade in backtest_result.trades() {
+            if let Some(close_timestamp) = trade.close_timestamp() {
+                rolling_win_rate.allocate(trade.pl_ratio());
+                trades_timestamps.push(close_timestamp);
+            }
+        }
+
+        charting_data.extend(
+            rolling_win_rate
+                .render(trades_timestamps)
+                .unwrap_or_default(),
+        );
+
+        for (pane, chart) in charting_data.iter_mut().enumerate() {
+            if let ChartingData::LineChartingData(line_charting_data) = chart {
+                line_charting_data.pane = Some(pane as i8);
+            }
+        }
+
+        return charting_data;
+    }
     fn save(&self) -> Result<(), Box<dyn Error>>;
 }
 
-pub static STRATEGIES: LazyLock<Vec<Box<dyn IStrategy>>> = LazyLock::new(|| {
-    vec![
+/// The built-in and rule strategies followed by the ones scripts define.
+pub fn strategies() -> Vec<Arc<Box<dyn IStrategy>>> {
+    let mut strategies: Vec<Arc<Box<dyn IStrategy>>> = STRATEGIES.iter().cloned().collect();
+    strategies.extend(SCRIPT_REGISTRY.strategies());
+
+    strategies
+}
+
+// Rule strategies and plugins are read from the app data dir when the list is first
+// used, after the app handle is set
+pub static STRATEGIES: LazyLock<Vec<Arc<Box<dyn IStrategy>>>> = LazyLock::new(|| {
+    let mut strategies: Vec<Box<dyn IStrategy>> = vec![
         Box::new(sma_200_strategy::Sma200Strategy::new()),
         Box::new(sma_optimizable_period_strategy::SmaOptimizablePeriodStrategy::new()),
         Box::new(double_sma_optimize_strategy::DoubleSmaOptimizablePeriodStrategy::new()),
         Box::new(theilsen_optimize_strategy::TheilSenOptimizeableStrategy::new()),
         Box::new(kalman_optimize_strategy::KalmanOptimizeableStrategy::new()),
         Box::new(renko_sma_optimize::SmaRenkoOptimizablePeriodStrategy::new()),
-    ]
+        Box::new(sma_target_exposure_strategy::SmaTargetExposureStrategy::new()),
+        Box::new(ensemble_strategy::EnsembleStrategy::eth_4h()),
+        Box::new(ml_strategy::MlStrategy::new()),
+    ];
+
+    strategies.extend(rule_strategy::load_rule_strategies());
+    strategies.extend(wasm_strategy::load_wasm_strategies());
+
+    strategies.into_iter().map(Arc::new).collect()
 });
@@ -1,10 +1,14 @@
-use super::{BacktestManager, BacktestResult, IStrategy, Trade, TradeOptions, TradeSide};
+use super::{
+    BacktestManager, BacktestResult, IStrategy, ParameterSchema, StrategyOverrides, Trade,
+    TradeOptions, TradeSide,
+};
 use crate::{
     library::engines::optimizers::{
         grid::{
-            GridOptimizer, NumericOptimizationParameter, OptimizationParameter,
+            GridOptimizer, NumericKind, NumericOptimizationParameter, OptimizationParameter,
             OptimizedBacktestResult,
         },
+        space::OptimizationSpace,
         Optimizer,
     },
     user::{
@@ -25,7 +29,7 @@ pub struct SmaRenkoOptimizablePeriodStrategy {
     id: String,
     name: String,
     description: String,
-    composition_data: Option<Vec<Vec<CompositionDataType>>>,
+    overrides: StrategyOverrides,
 }
 
 impl IStrategy for SmaRenkoOptimizablePeriodStrategy {
@@ -42,25 +46,14 @@ impl IStrategy for SmaRenkoOptimizablePeriodStrategy {
     }
 
     fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
-        let optimization_parameters = [
-            OptimizationParameter::Numeric(NumericOptimizationParameter {
-                name: "sma_period".into(),
-                start: 10.0,
-                end: 200.0,
-                step: 30.0,
-            }),
-            OptimizationParameter::Numeric(NumericOptimizationParameter {
-                name: "renko_change".into(),
-                start: 10.0,
-                end: 400.0,
-                step: 30.0,
-            }),
-        ];
+        let optimization_space = OptimizationSpace::new(self.optimization_ranges());
 
         let strategy: Box<dyn IStrategy> = Box::new(self.clone());
 
-        let optimization_results =
-            GridOptimizer::optimize(&strategy, &optimization_parameters).unwrap_or(Vec::new());
+        let optimization_results = self
+            .optimizer(Box::new(GridOptimizer::new()))
+            .optimize(&strategy, &optimization_space)
+            .unwrap_or(Vec::new());
 
         Some(optimization_results)
     }
@@ -74,13 +67,11 @@ impl IStrategy for SmaRenkoOptimizablePeriodStrategy {
             fees: 0.001,
         });
 
-        let composition: &'static dyn IComposition = self.composition();
+        let composition: &dyn IComposition = self.composition();
         let composition_data = self.composed_data();
 
-        if optimization_map.is_none() {
-            let backtest_result = backtest_manager.backtest_end();
-            return Ok(backtest_result);
-        }
+        let parameters = self.parameters_with_defaults(optimization_map);
+        let optimization_map = Some(&parameters);
 
         let sma_comp = optimization_map
             .unwrap()
@@ -165,12 +156,42 @@ impl IStrategy for SmaRenkoOptimizablePeriodStrategy {
         return ETH_STANDALONE_4H_4Y::instance();
     }
 
-    fn composed_data(&self) -> Vec<Vec<CompositionDataType>> {
-        if self.composition_data.is_some() {
-            return self.composition_data.as_ref().unwrap().to_vec();
-        }
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        vec![
+            ParameterSchema {
+                default: CompositionDataType::Usize(50),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "sma_period".into(),
+                    start: 10.0,
+                    end: 200.0,
+                    step: 30.0,
+                    kind: NumericKind::Integer,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::Usize(100),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "renko_change".into(),
+                    start: 10.0,
+                    end: 400.0,
+                    step: 30.0,
+                    kind: NumericKind::Integer,
+                    log_scale: false,
+                }),
+            },
+        ]
+    }
+
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        return Some(&self.overrides);
+    }
+
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        let mut strategy = self.clone();
+        strategy.overrides = overrides;
 
-        return self.composition().compose().unwrap();
+        return Some(Box::new(strategy));
     }
 
     fn save(&self) -> Result<(), Box<dyn Error>> {
@@ -184,10 +205,10 @@ impl SmaRenkoOptimizablePeriodStrategy {
             id: Uuid::new_v4().into(),
             name: "SMA Renko optimizable period price crossover".into(),
             description: "Long when renko(price) > sma(period) and short when renko(price) < sma(period) where period is optimizable".into(),
-            composition_data: None,
+            overrides: StrategyOverrides::default(),
         };
 
-        strategy.composition_data = Some(strategy.composition().compose().unwrap());
+        strategy.overrides.composition_data = Some(strategy.composition().compose().unwrap());
 
         return strategy;
     }
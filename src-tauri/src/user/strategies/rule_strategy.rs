@@ -0,0 +1,673 @@
+use super::{
+    BacktestManager, BacktestResult, IStrategy, ParameterSchema, StrategyOverrides, Trade,
+    TradeOptions, TradeSide,
+};
+use crate::{
+    library::engines::optimizers::{
+        grid::OptimizedBacktestResult,
+        random_search::RandomSearchOptimizer,
+        space::{numeric_value, OptimizationSpace},
+        study::fnv1a,
+        Optimizer,
+    },
+    user::{
+        composer::{compositions, CompositionDataType, IComposition},
+        library::{
+            atr::ATR, bollinger_bands::BollingerBands, kalman_filter::KalmanFilter, renko::Renko,
+            sma::SMA, theilsen::TheilSen, tr::TR, IInjectable,
+        },
+    },
+    utils::paths::join_app_data_dir,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, error::Error, fs, path::Path, sync::Arc};
+use uuid::Uuid;
+
+// Directory of the app data dir rule strategies are loaded from
+pub const RULE_STRATEGIES_DIRECTORY: &str = "strategies";
+
+// A strategy written as data instead of code, e.g. in TOML:
+//
+// name = "SMA crossover"
+// composition = "ETH standalone 4h 4y"
+//
+// [[parameters]]
+// default = { Usize = 20 }
+// range.Numeric = { name = "fast", start = 5.0, end = 50.0, step = 1.0, kind = "Integer" }
+//
+// [[injectables]]
+// kind = "sma"
+// name = "fast_sma"
+// period = { parameter = "fast" }
+//
+// [[rules.long_entry]]
+// left = "close"
+// operator = "crosses_above"
+// right = "fast_sma"
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RuleStrategyDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    // Name of one of the registered compositions
+    pub composition: String,
+    // Composition field trades are opened and closed at
+    #[serde(default = "default_price_field")]
+    pub price_field: String,
+    #[serde(default)]
+    pub parameters: Vec<ParameterSchema>,
+    #[serde(default)]
+    pub injectables: Vec<InjectableDefinition>,
+    pub rules: RuleSet,
+    #[serde(default)]
+    pub sizing: Sizing,
+    // Fractions of the entry price, checked against the price field every bar
+    pub stop_loss: Option<RuleValue>,
+    pub take_profit: Option<RuleValue>,
+}
+
+fn default_price_field() -> String {
+    "close".into()
+}
+
+// A constant or the value of one of the definition's parameters
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum RuleValue {
+    Number(f32),
+    Parameter { parameter: String },
+}
+
+// Injectables read `source` (a composition field or another injectable) or, for the
+// range based ones, the high, low and close fields. Bollinger bands are read as
+// `<name>.upper` and `<name>.lower`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InjectableDefinition {
+    Sma {
+        name: String,
+        period: RuleValue,
+        #[serde(default = "default_price_field")]
+        source: String,
+    },
+    KalmanFilter {
+        name: String,
+        q_noise: RuleValue,
+        r_noise: RuleValue,
+        #[serde(default = "default_price_field")]
+        source: String,
+    },
+    Renko {
+        name: String,
+        change: RuleValue,
+        #[serde(default = "default_price_field")]
+        source: String,
+    },
+    BollingerBands {
+        name: String,
+        period: RuleValue,
+        #[serde(default = "default_price_field")]
+        source: String,
+    },
+    Tr {
+        name: String,
+    },
+    Atr {
+        name: String,
+        period: RuleValue,
+    },
+    TheilSen {
+        name: String,
+        window_length: RuleValue,
+        atr_length: Option<RuleValue>,
+        atr_multiplier: Option<RuleValue>,
+    },
+}
+
+// A value or the name of a composition field or injectable output
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum Operand {
+    Value(RuleValue),
+    Series(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    #[serde(alias = ">")]
+    GreaterThan,
+    #[serde(alias = "<")]
+    LessThan,
+    CrossesAbove,
+    CrossesBelow,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Condition {
+    pub left: Operand,
+    pub operator: Comparison,
+    pub right: Operand,
+}
+
+// Every condition of a rule has to hold, an empty rule never triggers. An entry in the
+// other direction closes the open trade before reversing it
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RuleSet {
+    #[serde(default)]
+    pub long_entry: Vec<Condition>,
+    #[serde(default)]
+    pub long_exit: Vec<Condition>,
+    #[serde(default)]
+    pub short_entry: Vec<Condition>,
+    #[serde(default)]
+    pub short_exit: Vec<Condition>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Sizing {
+    // Share of the available capital every trade is opened with
+    #[serde(default = "default_capital_ratio")]
+    pub capital_ratio: RuleValue,
+    #[serde(default = "default_leverage")]
+    pub leverage: RuleValue,
+}
+
+impl Default for Sizing {
+    fn default() -> Self {
+        return Self {
+            capital_ratio: default_capital_ratio(),
+            leverage: default_leverage(),
+        };
+    }
+}
+
+fn default_capital_ratio() -> RuleValue {
+    RuleValue::Number(0.1)
+}
+
+fn default_leverage() -> RuleValue {
+    RuleValue::Number(1.0)
+}
+
+impl RuleStrategyDefinition {
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+
+        let definition: Self = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => serde_json::from_str(&content)?,
+            Some("toml") => toml::from_str(&content)?,
+            _ => return Err(format!("{} is not a JSON or TOML file", path.display()).into()),
+        };
+
+        return Ok(definition);
+    }
+}
+
+enum InjectableInstance {
+    Price {
+        name: String,
+        source: String,
+        injectable: Box<dyn IInjectable<f32, f32>>,
+    },
+    Range {
+        name: String,
+        injectable: Box<dyn IInjectable<(f32, f32, f32), f32>>,
+    },
+    Bands {
+        name: String,
+        source: String,
+        injectable: BollingerBands,
+    },
+}
+
+impl InjectableInstance {
+    // Feeds the bar to the injectable and writes its outputs next to the bar's fields,
+    // nothing is written while it warms up
+    fn allocate(&mut self, values: &mut HashMap<String, f32>) {
+        match self {
+            Self::Price {
+                name,
+                source,
+                injectable,
+            } => {
+                if let Some(&value) = values.get(source.as_str()) {
+                    injectable.allocate(value);
+                }
+
+                if let Some(output) = injectable.get_data() {
+                    values.insert(name.clone(), output);
+                }
+            }
+            Self::Range { name, injectable } => {
+                if let (Some(&high), Some(&low), Some(&close)) =
+                    (values.get("high"), values.get("low"), values.get("close"))
+                {
+                    injectable.allocate((high, low, close));
+                }
+
+                if let Some(output) = injectable.get_data() {
+                    values.insert(name.clone(), output);
+                }
+            }
+            Self::Bands {
+                name,
+                source,
+                injectable,
+            } => {
+                if let Some(&value) = values.get(source.as_str()) {
+                    injectable.allocate(value);
+                }
+
+                if let Some((upper, lower)) = injectable.get_data() {
+                    values.insert(format!("{}.upper", name), upper);
+                    values.insert(format!("{}.lower", name), lower);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RuleStrategy {
+    id: String,
+    definition: Arc<RuleStrategyDefinition>,
+    definition_hash: u64,
+    composition: Arc<dyn IComposition>,
+    overrides: StrategyOverrides,
+}
+
+impl IStrategy for RuleStrategy {
+    fn id(&self) -> &str {
+        return &self.id;
+    }
+
+    fn name(&self) -> &str {
+        return &self.definition.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.definition.description;
+    }
+
+    fn definition_hash(&self) -> Option<u64> {
+        return Some(self.definition_hash);
+    }
+
+    fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
+        if self.definition.parameters.is_empty() {
+            return None;
+        }
+
+        let optimization_space = OptimizationSpace::new(self.optimization_ranges());
+
+        let strategy: Box<dyn IStrategy> = Box::new(self.clone());
+
+        let optimization_results = self
+            .optimizer(Box::new(RandomSearchOptimizer::new(48, 42)))
+            .optimize(&strategy, &optimization_space)
+            .unwrap_or(Vec::new());
+
+        Some(optimization_results)
+    }
+
+    fn backtest(
+        &self,
+        optimization_map: Option<&HashMap<String, CompositionDataType>>,
+    ) -> Result<BacktestResult, Box<dyn Error>> {
+        let mut backtest_manager = BacktestManager::new(super::BacktestOptions {
+            initial_capital: 1_000.0,
+            fees: 0.001,
+        });
+
+        let composition = self.composition();
+        let composition_data = self.composed_data();
+
+        let parameters = self.parameters_with_defaults(optimization_map);
+        let definition = &self.definition;
+
+        let mut injectables = definition
+            .injectables
+            .iter()
+            .map(|injectable| instantiate(injectable, &parameters))
+            .collect::<Result<Vec<InjectableInstance>, Box<dyn Error>>>()?;
+
+        let capital_ratio = resolve(&definition.sizing.capital_ratio, &parameters)?;
+        let leverage = resolve(&definition.sizing.leverage, &parameters)?;
+        let stop_loss = match &definition.stop_loss {
+            Some(stop_loss) => Some(resolve(stop_loss, &parameters)?),
+            None => None,
+        };
+        let take_profit = match &definition.take_profit {
+            Some(take_profit) => Some(resolve(take_profit, &parameters)?),
+            None => None,
+        };
+
+        let composition_fields = composition.composition_fields();
+        let timestamp_position = composition.get_composition_field_position("timestamp");
+
+        if !composition_fields.contains_key(definition.price_field.as_str()) {
+            return Err(format!(
+                "{} has no field {}",
+                composition.name(),
+                definition.price_field
+            )
+            .into());
+        }
+
+        let mut latest_trade: Option<Trade> = None;
+        let mut previous_values: HashMap<String, f32> = HashMap::new();
+
+        for composition_point in &composition_data {
+            if backtest_manager.backtest_ended {
+                break;
+            }
+
+            let timestamp =
+                CompositionDataType::extract_i64(&composition_point[timestamp_position]);
+
+            let mut values: HashMap<String, f32> = composition_fields
+                .iter()
+                .filter_map(|(field, &position)| {
+                    numeric_value(&composition_point[position])
+                        .map(|value| (field.to_string(), value))
+                })
+                .collect();
+
+            let price = match values.get(definition.price_field.as_str()) {
+                Some(&price) => price,
+                None => continue,
+            };
+
+            backtest_manager.update_price(composition.name(), timestamp, price);
+
+            for injectable in injectables.iter_mut() {
+                injectable.allocate(&mut values);
+            }
+
+            let holds = |conditions: &[Condition]| -> Result<bool, Box<dyn Error>> {
+                all_hold(conditions, &values, &previous_values, &parameters)
+            };
+
+            let long_entry = holds(&definition.rules.long_entry)?;
+            let short_entry = holds(&definition.rules.short_entry)?;
+
+            if let Some(ref mut trade) = latest_trade {
+                let change = trade
+                    .open_price()
+                    .map_or(0.0, |open_price| (price - open_price) / open_price);
+                let change = match trade.side() {
+                    TradeSide::LONG => change,
+                    TradeSide::SHORT => -change,
+                };
+
+                let should_close = stop_loss.map_or(false, |stop_loss| change <= -stop_loss)
+                    || take_profit.map_or(false, |take_profit| change >= take_profit)
+                    || match trade.side() {
+                        TradeSide::LONG => short_entry || holds(&definition.rules.long_exit)?,
+                        TradeSide::SHORT => long_entry || holds(&definition.rules.short_exit)?,
+                    };
+
+                if should_close {
+                    backtest_manager.close_trade(trade);
+                    latest_trade = None;
+                }
+            }
+
+            let side = if long_entry {
+                Some(TradeSide::LONG)
+            } else if short_entry {
+                Some(TradeSide::SHORT)
+            } else {
+                None
+            };
+
+            if let (None, Some(side)) = (&latest_trade, side) {
+                let trade_allocation = backtest_manager.available_capital() * capital_ratio;
+                let mut new_trade = Trade::new(TradeOptions {
+                    asset_name: composition.name(),
+                    side,
+                    capital_allocation: Some(trade_allocation),
+                    leverage: Some(leverage),
+                });
+                backtest_manager.open_trade(&mut new_trade);
+                latest_trade = Some(new_trade);
+            }
+
+            previous_values = values;
+        }
+
+        let backtest_result = backtest_manager.backtest_end();
+        Ok(backtest_result)
+    }
+
+    fn composition(&self) -> &dyn IComposition {
+        return self.composition.as_ref();
+    }
+
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        return self.definition.parameters.clone();
+    }
+
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        return Some(&self.overrides);
+    }
+
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        let mut strategy = self.clone();
+        strategy.overrides = overrides;
+
+        return Some(Box::new(strategy));
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl RuleStrategy {
+    pub fn new(definition: RuleStrategyDefinition) -> Result<Self, Box<dyn Error>> {
+        let composition: Arc<dyn IComposition> = compositions()
+            .into_iter()
+            .find(|composition| composition.name() == definition.composition)
+            .ok_or_else(|| format!("Unknown composition {}", definition.composition))?;
+
+        let definition_hash = fnv1a(serde_json::to_string(&definition)?.bytes());
+
+        let mut strategy = Self {
+            id: Uuid::new_v4().into(),
+            definition: Arc::new(definition),
+            definition_hash,
+            composition,
+            overrides: StrategyOverrides::default(),
+        };
+
+        strategy.overrides.composition_data = Some(strategy.composition().compose()?);
+
+        return Ok(strategy);
+    }
+}
+
+/// Every rule strategy definition of the app data dir, files that don't parse or name
+/// an unknown composition are reported and skipped.
+pub fn load_rule_strategies() -> Vec<Box<dyn IStrategy>> {
+    let mut strategies: Vec<Box<dyn IStrategy>> = vec![];
+
+    let directory = match join_app_data_dir(RULE_STRATEGIES_DIRECTORY) {
+        Ok(directory) => directory,
+        Err(_) => return strategies,
+    };
+
+    let mut paths: Vec<_> = match fs::read_dir(&directory) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                matches!(
+                    path.extension().and_then(|extension| extension.to_str()),
+                    Some("json") | Some("toml")
+                )
+            })
+            .collect(),
+        Err(_) => return strategies,
+    };
+    paths.sort();
+
+    for path in paths {
+        match RuleStrategyDefinition::from_file(&path).and_then(RuleStrategy::new) {
+            Ok(strategy) => strategies.push(Box::new(strategy)),
+            Err(error) => eprintln!("Couldn't load strategy {}: {}", path.display(), error),
+        }
+    }
+
+    strategies
+}
+
+fn resolve(
+    value: &RuleValue,
+    parameters: &HashMap<String, CompositionDataType>,
+) -> Result<f32, Box<dyn Error>> {
+    match value {
+        RuleValue::Number(number) => Ok(*number),
+        RuleValue::Parameter { parameter } => parameters
+            .get(parameter)
+            .and_then(numeric_value)
+            .ok_or_else(|| format!("Parameter {} is missing or not numeric", parameter).into()),
+    }
+}
+
+fn instantiate(
+    definition: &InjectableDefinition,
+    parameters: &HashMap<String, CompositionDataType>,
+) -> Result<InjectableInstance, Box<dyn Error>> {
+    let period = |value: &RuleValue| -> Result<usize, Box<dyn Error>> {
+        Ok(resolve(value, parameters)?.round().max(1.0) as usize)
+    };
+
+    let injectable = match definition {
+        InjectableDefinition::Sma {
+            name,
+            period: sma_period,
+            source,
+        } => InjectableInstance::Price {
+            name: name.clone(),
+            source: source.clone(),
+            injectable: Box::new(SMA::new(period(sma_period)?)),
+        },
+        InjectableDefinition::KalmanFilter {
+            name,
+            q_noise,
+            r_noise,
+            source,
+        } => InjectableInstance::Price {
+            name: name.clone(),
+            source: source.clone(),
+            injectable: Box::new(KalmanFilter::new(
+                resolve(q_noise, parameters)?,
+                resolve(r_noise, parameters)?,
+            )),
+        },
+        InjectableDefinition::Renko {
+            name,
+            change,
+            source,
+        } => InjectableInstance::Price {
+            name: name.clone(),
+            source: source.clone(),
+            injectable: Box::new(Renko::new(resolve(change, parameters)?)),
+        },
+        InjectableDefinition::BollingerBands {
+            name,
+            period: bands_period,
+            source,
+        } => InjectableInstance::Bands {
+            name: name.clone(),
+            source: source.clone(),
+            injectable: BollingerBands::new(period(bands_period)?),
+        },
+        InjectableDefinition::Tr { name } => InjectableInstance::Range {
+            name: name.clone(),
+            injectable: Box::new(TR::new()),
+        },
+        InjectableDefinition::Atr {
+            name,
+            period: atr_period,
+        } => InjectableInstance::Range {
+            name: name.clone(),
+            injectable: Box::new(ATR::new(period(atr_period)?)),
+        },
+        InjectableDefinition::TheilSen {
+            name,
+            window_length,
+            atr_length,
+            atr_multiplier,
+        } => InjectableInstance::Range {
+            name: name.clone(),
+            injectable: Box::new(TheilSen::new(
+                Some(period(window_length)?),
+                atr_length.as_ref().map(period).transpose()?,
+                atr_multiplier
+                    .as_ref()
+                    .map(|value| resolve(value, parameters))
+                    .transpose()?,
+            )),
+        },
+    };
+
+    Ok(injectable)
+}
+
+// Conditions on a value that is still warming up don't hold
+fn all_hold(
+    conditions: &[Condition],
+    values: &HashMap<String, f32>,
+    previous_values: &HashMap<String, f32>,
+    parameters: &HashMap<String, CompositionDataType>,
+) -> Result<bool, Box<dyn Error>> {
+    if conditions.is_empty() {
+        return Ok(false);
+    }
+
+    let operand =
+        |operand: &Operand, values: &HashMap<String, f32>| -> Result<Option<f32>, Box<dyn Error>> {
+            match operand {
+                Operand::Value(value) => Ok(Some(resolve(value, parameters)?)),
+                Operand::Series(series) => Ok(values.get(series.as_str()).copied()),
+            }
+        };
+
+    for condition in conditions {
+        let (left, right) = match (
+            operand(&condition.left, values)?,
+            operand(&condition.right, values)?,
+        ) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return Ok(false),
+        };
+
+        let holds = match condition.operator {
+            Comparison::GreaterThan => left > right,
+            Comparison::LessThan => left < right,
+            Comparison::CrossesAbove | Comparison::CrossesBelow => {
+                let (previous_left, previous_right) = match (
+                    operand(&condition.left, previous_values)?,
+                    operand(&condition.right, previous_values)?,
+                ) {
+                    (Some(previous_left), Some(previous_right)) => (previous_left, previous_right),
+                    _ => return Ok(false),
+                };
+
+                if condition.operator == Comparison::CrossesAbove {
+                    previous_left <= previous_right && left > right
+                } else {
+                    previous_left >= previous_right && left < right
+                }
+            }
+        };
+
+        if !holds {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
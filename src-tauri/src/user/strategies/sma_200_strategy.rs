@@ -38,7 +38,7 @@ impl IStrategy for Sma200Strategy {
             fees: 0.001,
         });
 
-        let composition: &'static dyn IComposition = self.composition();
+        let composition: &dyn IComposition = self.composition();
         let composition_data = self.composed_data();
 
         let timestamp_position = composition.get_composition_field_position("timestamp");
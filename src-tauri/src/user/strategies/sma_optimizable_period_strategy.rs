@@ -1,10 +1,14 @@
-use super::{BacktestManager, BacktestResult, IStrategy, Trade, TradeOptions, TradeSide};
+use super::{
+    BacktestManager, BacktestResult, IStrategy, ParameterSchema, StrategyOverrides, Trade,
+    TradeOptions, TradeSide,
+};
 use crate::{
     library::engines::optimizers::{
         grid::{
-            GridOptimizer, NumericOptimizationParameter, OptimizationParameter,
+            GridOptimizer, NumericKind, NumericOptimizationParameter, OptimizationParameter,
             OptimizedBacktestResult,
         },
+        space::OptimizationSpace,
         Optimizer,
     },
     user::{
@@ -24,7 +28,7 @@ pub struct SmaOptimizablePeriodStrategy {
     id: String,
     name: String,
     description: String,
-    composition_data: Option<Vec<Vec<CompositionDataType>>>,
+    overrides: StrategyOverrides,
 }
 
 impl IStrategy for SmaOptimizablePeriodStrategy {
@@ -41,19 +45,14 @@ impl IStrategy for SmaOptimizablePeriodStrategy {
     }
 
     fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
-        let optimization_parameters = [OptimizationParameter::Numeric(
-            NumericOptimizationParameter {
-                name: "sma_period".into(),
-                start: 10.0,
-                end: 200.0,
-                step: 5.0,
-            },
-        )];
+        let optimization_space = OptimizationSpace::new(self.optimization_ranges());
 
         let strategy: Box<dyn IStrategy> = Box::new(self.clone());
 
-        let optimization_results =
-            GridOptimizer::optimize(&strategy, &optimization_parameters).unwrap_or(Vec::new());
+        let optimization_results = self
+            .optimizer(Box::new(GridOptimizer::new()))
+            .optimize(&strategy, &optimization_space)
+            .unwrap_or(Vec::new());
 
         Some(optimization_results)
     }
@@ -67,13 +66,11 @@ impl IStrategy for SmaOptimizablePeriodStrategy {
             fees: 0.001,
         });
 
-        let composition: &'static dyn IComposition = self.composition();
+        let composition: &dyn IComposition = self.composition();
         let composition_data = self.composed_data();
 
-        if optimization_map.is_none() {
-            let backtest_result = backtest_manager.backtest_end();
-            return Ok(backtest_result);
-        }
+        let parameters = self.parameters_with_defaults(optimization_map);
+        let optimization_map = Some(&parameters);
 
         let sma_comp = optimization_map
             .unwrap()
@@ -143,12 +140,29 @@ impl IStrategy for SmaOptimizablePeriodStrategy {
         return ETH_STANDALONE_4H_4Y::instance();
     }
 
-    fn composed_data(&self) -> Vec<Vec<CompositionDataType>> {
-        if self.composition_data.is_some() {
-            return self.composition_data.as_ref().unwrap().to_vec();
-        }
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        vec![ParameterSchema {
+            default: CompositionDataType::Usize(50),
+            range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                name: "sma_period".into(),
+                start: 10.0,
+                end: 200.0,
+                step: 5.0,
+                kind: NumericKind::Integer,
+                log_scale: false,
+            }),
+        }]
+    }
+
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        return Some(&self.overrides);
+    }
+
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        let mut strategy = self.clone();
+        strategy.overrides = overrides;
 
-        return self.composition().compose().unwrap();
+        return Some(Box::new(strategy));
     }
 
     fn save(&self) -> Result<(), Box<dyn Error>> {
@@ -162,10 +176,10 @@ impl SmaOptimizablePeriodStrategy {
             id: Uuid::new_v4().into(),
             name: "SMA optimizable period price crossover".into(),
             description: "Long when price > sma(period) and short when price < sma(period) where period is optimizable".into(),
-            composition_data: None,
+            overrides: StrategyOverrides::default(),
         };
 
-        strategy.composition_data = Some(strategy.composition().compose().unwrap());
+        strategy.overrides.composition_data = Some(strategy.composition().compose().unwrap());
 
         return strategy;
     }
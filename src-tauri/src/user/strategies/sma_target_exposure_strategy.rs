@@ -0,0 +1,182 @@
+use super::target_position::{backtest_target_positions, TargetPosition, TargetPositionOptions};
+use super::{BacktestManager, BacktestResult, IStrategy, ParameterSchema, StrategyOverrides};
+use crate::{
+    library::engines::optimizers::{
+        grid::{
+            GridOptimizer, NumericKind, NumericOptimizationParameter, OptimizationParameter,
+            OptimizedBacktestResult,
+        },
+        space::OptimizationSpace,
+        Optimizer,
+    },
+    user::{
+        composer::{
+            eth_standalone_4h_4y_composition::ETH_STANDALONE_4H_4Y, CompositionDataType,
+            IComposition,
+        },
+        library::{sma::SMA, IInjectable},
+    },
+};
+use std::collections::HashMap;
+use std::{error::Error, vec};
+use uuid::Uuid;
+
+// Relative distance of the price from its SMA at which the exposure is at its maximum
+const FULL_EXPOSURE_DISTANCE: f32 = 0.10;
+
+#[derive(Clone)]
+pub struct SmaTargetExposureStrategy {
+    id: String,
+    name: String,
+    description: String,
+    overrides: StrategyOverrides,
+}
+
+impl IStrategy for SmaTargetExposureStrategy {
+    fn id(&self) -> &str {
+        return &self.id;
+    }
+
+    fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
+        let optimization_space = OptimizationSpace::new(self.optimization_ranges());
+
+        let strategy: Box<dyn IStrategy> = Box::new(self.clone());
+
+        let optimization_results = self
+            .optimizer(Box::new(GridOptimizer::new()))
+            .optimize(&strategy, &optimization_space)
+            .unwrap_or(Vec::new());
+
+        Some(optimization_results)
+    }
+
+    fn backtest(
+        &self,
+        optimization_map: Option<&HashMap<String, CompositionDataType>>,
+    ) -> Result<BacktestResult, Box<dyn Error>> {
+        let backtest_manager = BacktestManager::new(super::BacktestOptions {
+            initial_capital: 1_000.0,
+            fees: 0.001,
+        });
+
+        let composition: &dyn IComposition = self.composition();
+        let composition_data = self.composed_data();
+
+        let parameters = self.parameters_with_defaults(optimization_map);
+
+        let sma_period = CompositionDataType::extract_usize(parameters.get("sma_period").unwrap());
+        let max_exposure =
+            CompositionDataType::extract_f32(parameters.get("max_exposure").unwrap());
+        let rebalance_threshold =
+            CompositionDataType::extract_f32(parameters.get("rebalance_threshold").unwrap());
+
+        let close_position = composition.get_composition_field_position("close");
+        let mut sma_injectable = SMA::new(sma_period);
+
+        // Sized by how far the price is from its SMA, resized once the wanted exposure
+        // moved by more than the threshold
+        let backtest_result = backtest_target_positions(
+            backtest_manager,
+            composition,
+            &composition_data,
+            "close",
+            TargetPositionOptions {
+                leverage: 1.0,
+                rebalance_threshold: Some(rebalance_threshold),
+            },
+            |composition_point| {
+                let close = CompositionDataType::extract_f32(&composition_point[close_position]);
+
+                sma_injectable.allocate(close);
+
+                let sma_value = sma_injectable.get_data()?;
+                let distance = (close - sma_value) / sma_value;
+                let exposure = (distance / FULL_EXPOSURE_DISTANCE).clamp(-1.0, 1.0) * max_exposure;
+
+                Some(TargetPosition::Exposure(exposure))
+            },
+        );
+
+        Ok(backtest_result)
+    }
+
+    fn composition(&self) -> &'static dyn IComposition {
+        return ETH_STANDALONE_4H_4Y::instance();
+    }
+
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        vec![
+            ParameterSchema {
+                default: CompositionDataType::Usize(50),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "sma_period".into(),
+                    start: 10.0,
+                    end: 200.0,
+                    step: 10.0,
+                    kind: NumericKind::Integer,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::F32(0.5),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "max_exposure".into(),
+                    start: 0.1,
+                    end: 0.9,
+                    step: 0.2,
+                    kind: NumericKind::Float,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::F32(0.1),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "rebalance_threshold".into(),
+                    start: 0.05,
+                    end: 0.25,
+                    step: 0.05,
+                    kind: NumericKind::Float,
+                    log_scale: false,
+                }),
+            },
+        ]
+    }
+
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        return Some(&self.overrides);
+    }
+
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        let mut strategy = self.clone();
+        strategy.overrides = overrides;
+
+        return Some(Box::new(strategy));
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl SmaTargetExposureStrategy {
+    pub fn new() -> Self {
+        let mut strategy = Self {
+            id: Uuid::new_v4().into(),
+            name: "SMA distance target exposure".into(),
+            description: "Holds an exposure scaled by the distance of price from sma(period), long above and short below, rebalanced through target positions".into(),
+            overrides: StrategyOverrides::default(),
+        };
+
+        strategy.overrides.composition_data = Some(strategy.composition().compose().unwrap());
+
+        return strategy;
+    }
+}
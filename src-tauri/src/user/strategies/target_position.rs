@@ -0,0 +1,229 @@
+use super::{BacktestManager, BacktestResult, Trade, TradeOptions, TradeSide};
+use crate::user::composer::{CompositionDataType, IComposition};
+
+// Exposures closer to zero than this are flat
+const FLAT_EXPOSURE: f32 = 1e-4;
+
+// Share of the initial capital kept as cash, the manager ends a backtest once cash falls
+// below 1% of it so a fully invested position would end it on the next trade
+const CASH_RESERVE: f32 = 0.02;
+
+// Position a strategy wants to hold after a bar
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TargetPosition {
+    // Signed share of equity, -1.0 fully short to 1.0 fully long, clamped to that range
+    Exposure(f32),
+    // Signed quantity of the asset, negative for short
+    Quantity(f32),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TargetPositionOptions {
+    // Leverage trades are opened with, an exposure of 1.0 holds equity * leverage
+    pub leverage: f32,
+    // Smallest change of exposure that resizes a position on the same side. `None`
+    // only trades when the target changes side or goes flat, so the position drifts
+    // with the price
+    pub rebalance_threshold: Option<f32>,
+}
+
+impl Default for TargetPositionOptions {
+    fn default() -> Self {
+        return Self {
+            leverage: 1.0,
+            rebalance_threshold: None,
+        };
+    }
+}
+
+// Turns target positions into the trades that reach them. A position is made of the
+// trades opened for it, resizing opens a trade for the difference or closes the newest
+// trades until the position is at or below its target
+pub struct TargetPositionAdapter {
+    asset_name: String,
+    options: TargetPositionOptions,
+    open_trades: Vec<Trade>,
+}
+
+impl TargetPositionAdapter {
+    pub fn new(asset_name: &str, options: TargetPositionOptions) -> Self {
+        return Self {
+            asset_name: asset_name.into(),
+            options,
+            open_trades: Vec::new(),
+        };
+    }
+
+    /// Signed quantity of the asset the open trades hold.
+    pub fn quantity(&self) -> f32 {
+        self.open_trades
+            .iter()
+            .map(|trade| {
+                let quantity = trade.capital_allocation().unwrap_or(0.0) * trade.leverage()
+                    / trade.open_price().unwrap();
+
+                match trade.side() {
+                    TradeSide::LONG => quantity,
+                    TradeSide::SHORT => -quantity,
+                }
+            })
+            .sum()
+    }
+
+    /// Signed share of equity the open trades hold at the latest price, in the units of
+    /// `TargetPosition::Exposure`.
+    pub fn exposure(&self, backtest_manager: &BacktestManager) -> f32 {
+        let equity = backtest_manager.equity();
+
+        match backtest_manager.asset_price(&self.asset_name) {
+            Some(price) if equity > 0.0 => {
+                self.quantity() * price / (equity * self.options.leverage)
+            }
+            _ => 0.0,
+        }
+    }
+
+    pub fn rebalance(&mut self, backtest_manager: &mut BacktestManager, target: TargetPosition) {
+        if backtest_manager.backtest_ended() {
+            return;
+        }
+
+        let price = match backtest_manager.asset_price(&self.asset_name) {
+            Some(price) => price,
+            None => return,
+        };
+
+        let equity = backtest_manager.equity();
+        if equity <= 0.0 {
+            return;
+        }
+
+        let target_exposure = match target {
+            TargetPosition::Exposure(exposure) => exposure.clamp(-1.0, 1.0),
+            TargetPosition::Quantity(quantity) => {
+                quantity * price / (equity * self.options.leverage)
+            }
+        };
+        let exposure = self.exposure(backtest_manager);
+
+        let target_side = exposure_side(target_exposure);
+
+        if target_side != exposure_side(exposure) {
+            self.close_all(backtest_manager);
+
+            if let Some(side) = target_side {
+                self.open(backtest_manager, side, target_exposure.abs());
+            }
+
+            return;
+        }
+
+        let side = match target_side {
+            Some(side) => side,
+            None => return,
+        };
+
+        let difference = target_exposure.abs() - exposure.abs();
+
+        match self.options.rebalance_threshold {
+            Some(rebalance_threshold) if difference.abs() >= rebalance_threshold => {}
+            _ => return,
+        }
+
+        if difference > 0.0 {
+            self.open(backtest_manager, side, difference);
+            return;
+        }
+
+        while let Some(mut trade) = self.open_trades.pop() {
+            backtest_manager.close_trade(&mut trade);
+
+            if self.exposure(backtest_manager).abs() <= target_exposure.abs() {
+                break;
+            }
+        }
+
+        let shortfall = target_exposure.abs() - self.exposure(backtest_manager).abs();
+        if shortfall > FLAT_EXPOSURE {
+            self.open(backtest_manager, side, shortfall);
+        }
+    }
+
+    pub fn close_all(&mut self, backtest_manager: &mut BacktestManager) {
+        for mut trade in self.open_trades.drain(..) {
+            backtest_manager.close_trade(&mut trade);
+        }
+    }
+
+    fn open(&mut self, backtest_manager: &mut BacktestManager, side: TradeSide, exposure: f32) {
+        let leverage = self.options.leverage;
+
+        // Capped so the allocation and its opening fee fit in the available capital
+        // without touching the cash reserve
+        let spendable_capital = backtest_manager.available_capital()
+            - backtest_manager.initial_capital() * CASH_RESERVE;
+        let capital_allocation = (exposure * backtest_manager.equity())
+            .min(spendable_capital / (1.0 + leverage * backtest_manager.fees()));
+
+        if capital_allocation <= 0.0 {
+            return;
+        }
+
+        let mut trade = Trade::new(TradeOptions {
+            asset_name: &self.asset_name,
+            side,
+            capital_allocation: Some(capital_allocation),
+            leverage: Some(leverage),
+        });
+        backtest_manager.open_trade(&mut trade);
+
+        // The manager skips trades it can't open
+        if trade.open_price().is_some() {
+            self.open_trades.push(trade);
+        }
+    }
+}
+
+fn exposure_side(exposure: f32) -> Option<TradeSide> {
+    if exposure > FLAT_EXPOSURE {
+        Some(TradeSide::LONG)
+    } else if exposure < -FLAT_EXPOSURE {
+        Some(TradeSide::SHORT)
+    } else {
+        None
+    }
+}
+
+/// Backtests a strategy that only decides the position it wants: `target` is called on
+/// every row after the price is updated, `None` keeps the current position.
+pub fn backtest_target_positions(
+    mut backtest_manager: BacktestManager,
+    composition: &dyn IComposition,
+    composition_data: &[Vec<CompositionDataType>],
+    price_field: &str,
+    options: TargetPositionOptions,
+    mut target: impl FnMut(&[CompositionDataType]) -> Option<TargetPosition>,
+) -> BacktestResult {
+    let timestamp_position = composition.get_composition_field_position("timestamp");
+    let price_position = composition.get_composition_field_position(price_field);
+
+    let mut adapter = TargetPositionAdapter::new(composition.name(), options);
+
+    for composition_point in composition_data {
+        if backtest_manager.backtest_ended() {
+            break;
+        }
+
+        let timestamp = CompositionDataType::extract_i64(&composition_point[timestamp_position]);
+        let price = CompositionDataType::extract_f32(&composition_point[price_position]);
+
+        backtest_manager.update_price(composition.name(), timestamp, price);
+        backtest_manager.update_benchmark(price);
+
+        if let Some(target_position) = target(composition_point) {
+            adapter.rebalance(&mut backtest_manager, target_position);
+        }
+    }
+
+    backtest_manager.backtest_end()
+}
@@ -1,8 +1,13 @@
-use super::{BacktestManager, BacktestResult, IStrategy, Trade, TradeOptions, TradeSide};
+use super::{
+    BacktestManager, BacktestResult, IStrategy, ParameterSchema, StrategyOverrides, Trade,
+    TradeOptions, TradeSide,
+};
+use crate::library::engines::optimizers::space::OptimizationSpace;
 use crate::library::engines::optimizers::Optimizer;
 use crate::{
     library::engines::optimizers::grid::{
-        GridOptimizer, NumericOptimizationParameter, OptimizationParameter, OptimizedBacktestResult,
+        GridOptimizer, NumericKind, NumericOptimizationParameter, OptimizationParameter,
+        OptimizedBacktestResult,
     },
     user::{
         composer::{
@@ -20,7 +25,7 @@ pub struct TheilSenOptimizeableStrategy {
     id: String,
     name: String,
     description: String,
-    composition_data: Option<Vec<Vec<CompositionDataType>>>,
+    overrides: StrategyOverrides,
 }
 
 impl IStrategy for TheilSenOptimizeableStrategy {
@@ -37,25 +42,14 @@ impl IStrategy for TheilSenOptimizeableStrategy {
     }
 
     fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
-        let optimization_parameters = [
-            OptimizationParameter::Numeric(NumericOptimizationParameter {
-                name: "theilsen_window_length".into(),
-                start: 10.0,
-                end: 200.0,
-                step: 15.0,
-            }),
-            OptimizationParameter::Numeric(NumericOptimizationParameter {
-                name: "capital_ratio".into(),
-                start: 0.1,
-                end: 0.9,
-                step: 0.2,
-            }),
-        ];
+        let optimization_space = OptimizationSpace::new(self.optimization_ranges());
 
         let strategy: Box<dyn IStrategy> = Box::new(self.clone());
 
-        let optimization_results =
-            GridOptimizer::optimize(&strategy, &optimization_parameters).unwrap_or(Vec::new());
+        let optimization_results = self
+            .optimizer(Box::new(GridOptimizer::new()))
+            .optimize(&strategy, &optimization_space)
+            .unwrap_or(Vec::new());
 
         Some(optimization_results)
     }
@@ -69,13 +63,11 @@ impl IStrategy for TheilSenOptimizeableStrategy {
             fees: 0.001,
         });
 
-        let composition: &'static dyn IComposition = self.composition();
+        let composition: &dyn IComposition = self.composition();
         let composition_data = self.composed_data();
 
-        if optimization_map.is_none() {
-            let backtest_result = backtest_manager.backtest_end();
-            return Ok(backtest_result);
-        }
+        let parameters = self.parameters_with_defaults(optimization_map);
+        let optimization_map = Some(&parameters);
 
         let theilsen_window_length_comp = optimization_map
             .unwrap()
@@ -177,12 +169,42 @@ impl IStrategy for TheilSenOptimizeableStrategy {
         }
     }
 
-    fn composed_data(&self) -> Vec<Vec<CompositionDataType>> {
-        if self.composition_data.is_some() {
-            return self.composition_data.as_ref().unwrap().to_vec();
-        }
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        vec![
+            ParameterSchema {
+                default: CompositionDataType::Usize(50),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "theilsen_window_length".into(),
+                    start: 10.0,
+                    end: 200.0,
+                    step: 15.0,
+                    kind: NumericKind::Integer,
+                    log_scale: false,
+                }),
+            },
+            ParameterSchema {
+                default: CompositionDataType::F32(0.3),
+                range: OptimizationParameter::Numeric(NumericOptimizationParameter {
+                    name: "capital_ratio".into(),
+                    start: 0.1,
+                    end: 0.9,
+                    step: 0.2,
+                    kind: NumericKind::Float,
+                    log_scale: false,
+                }),
+            },
+        ]
+    }
+
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        return Some(&self.overrides);
+    }
+
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        let mut strategy = self.clone();
+        strategy.overrides = overrides;
 
-        return self.composition().compose().unwrap();
+        return Some(Box::new(strategy));
     }
 
     fn save(&self) -> Result<(), Box<dyn Error>> {
@@ -196,10 +218,10 @@ impl TheilSenOptimizeableStrategy {
             id: Uuid::new_v4().into(),
             name: "Theilsen".into(),
             description: "Theilsen".into(),
-            composition_data: None,
+            overrides: StrategyOverrides::default(),
         };
 
-        strategy.composition_data = Some(strategy.composition().compose().unwrap());
+        strategy.overrides.composition_data = Some(strategy.composition().compose().unwrap());
 
         return strategy;
     }
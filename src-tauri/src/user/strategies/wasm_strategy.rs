@@ -0,0 +1,461 @@
+use super::{
+    BacktestManager, BacktestResult, IStrategy, ParameterSchema, StrategyOverrides, Trade,
+    TradeOptions, TradeSide,
+};
+use crate::{
+    library::engines::optimizers::{
+        grid::OptimizedBacktestResult,
+        random_search::RandomSearchOptimizer,
+        space::{numeric_value, OptimizationSpace},
+        study::fnv1a,
+        Optimizer,
+    },
+    user::composer::{compositions, CompositionDataType, IComposition},
+    utils::paths::join_app_data_dir,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::HashMap, error::Error, fs, path::Path, sync::Arc, sync::LazyLock};
+use uuid::Uuid;
+use wasmtime::{Config, Engine, Instance, Memory, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+// Directory of the app data dir `.wasm` strategy plugins are loaded from
+pub const WASM_PLUGINS_DIRECTORY: &str = "plugins";
+
+// Plugins get no imports, so they can't reach the file system or the network, and run
+// with a fuel budget per call and a memory cap so a runaway plugin fails its backtest
+// instead of hanging or exhausting the app
+const FUEL_PER_CALL: u64 = 100_000_000;
+const MAX_PLUGIN_MEMORY: usize = 256 * 1024 * 1024;
+// Largest message read back from a plugin, checked before anything is allocated for it
+const MAX_PLUGIN_MESSAGE: usize = 16 * 1024 * 1024;
+
+static WASM_ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+
+    Engine::new(&config).unwrap()
+});
+
+// The ABI a plugin implements, every message is JSON in the plugin's memory:
+//
+// memory                          exported linear memory
+// alloc(len: i32) -> i32          buffer the host writes a message of `len` bytes to
+// metadata() -> i64               PluginMetadata
+// start(ptr: i32, len: i32)       StartMessage, before every backtest
+// on_bar(ptr: i32, len: i32) -> i64
+//                                 BarMessage, answered with a list of OrderIntent
+//
+// Messages returned to the host are packed as `ptr << 32 | len`, 0 for none
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PluginMetadata {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    // Name of one of the registered compositions
+    pub composition: String,
+    // Composition field trades are opened and closed at
+    #[serde(default = "default_price_field")]
+    pub price_field: String,
+    #[serde(default)]
+    pub parameters: Vec<ParameterSchema>,
+}
+
+fn default_price_field() -> String {
+    "close".into()
+}
+
+#[derive(Serialize)]
+struct StartMessage<'a> {
+    // Order of the values of every bar
+    fields: &'a [&'a str],
+    parameters: &'a HashMap<String, CompositionDataType>,
+}
+
+#[derive(Serialize)]
+struct BarMessage {
+    values: Vec<serde_json::Value>,
+    available_capital: f32,
+    portfolio_value: f32,
+    open_trades: Vec<OpenTradeMessage>,
+}
+
+#[derive(Serialize)]
+struct OpenTradeMessage {
+    side: TradeSide,
+    open_price: Option<f32>,
+    capital_allocation: Option<f32>,
+    leverage: f32,
+}
+
+// What a plugin asks for on a bar, applied in order. `trade` is an index in the
+// bar's `open_trades`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OrderIntent {
+    Open {
+        side: TradeSide,
+        // Share of the available capital the trade is opened with
+        capital_ratio: f32,
+        leverage: Option<f32>,
+    },
+    Close {
+        trade: usize,
+    },
+    CloseAll,
+}
+
+// One instantiation of a plugin, backtests get their own so they share no state
+struct PluginInstance {
+    store: Store<StoreLimits>,
+    instance: Instance,
+    memory: Memory,
+}
+
+impl PluginInstance {
+    fn new(module: &Module) -> Result<Self, Box<dyn Error>> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_PLUGIN_MEMORY)
+            .build();
+
+        let mut store = Store::new(&WASM_ENGINE, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL_PER_CALL)?;
+
+        let instance = Instance::new(&mut store, module, &[])?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("Plugin doesn't export its memory")?;
+
+        return Ok(Self {
+            store,
+            instance,
+            memory,
+        });
+    }
+
+    fn write(&mut self, message: &impl Serialize) -> Result<(i32, i32), Box<dyn Error>> {
+        let bytes = serde_json::to_vec(message)?;
+
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut self.store, "alloc")?;
+        let ptr = alloc.call(&mut self.store, bytes.len() as i32)?;
+
+        self.memory.write(&mut self.store, ptr as usize, &bytes)?;
+
+        Ok((ptr, bytes.len() as i32))
+    }
+
+    fn read<T: DeserializeOwned>(&mut self, packed: i64) -> Result<Option<T>, Box<dyn Error>> {
+        if packed == 0 {
+            return Ok(None);
+        }
+
+        let ptr = (packed as u64 >> 32) as usize;
+        let len = (packed as u64 & 0xffff_ffff) as usize;
+
+        if len > MAX_PLUGIN_MESSAGE {
+            return Err(format!("Plugin message of {} bytes is too large", len).into());
+        }
+
+        let in_memory = ptr
+            .checked_add(len)
+            .map_or(false, |end| end <= self.memory.data_size(&self.store));
+
+        if !in_memory {
+            return Err("Plugin message is outside of its memory".into());
+        }
+
+        let mut bytes = vec![0; len];
+        self.memory.read(&self.store, ptr, &mut bytes)?;
+
+        Ok(Some(serde_json::from_slice(&bytes)?))
+    }
+
+    fn metadata(&mut self) -> Result<PluginMetadata, Box<dyn Error>> {
+        self.store.set_fuel(FUEL_PER_CALL)?;
+
+        let metadata = self
+            .instance
+            .get_typed_func::<(), i64>(&mut self.store, "metadata")?;
+        let packed = metadata.call(&mut self.store, ())?;
+
+        Ok(self.read(packed)?.ok_or("Plugin returned no metadata")?)
+    }
+
+    fn start(&mut self, message: &StartMessage) -> Result<(), Box<dyn Error>> {
+        self.store.set_fuel(FUEL_PER_CALL)?;
+
+        let (ptr, len) = self.write(message)?;
+        let start = self
+            .instance
+            .get_typed_func::<(i32, i32), ()>(&mut self.store, "start")?;
+        start.call(&mut self.store, (ptr, len))?;
+
+        Ok(())
+    }
+
+    fn on_bar(&mut self, message: &BarMessage) -> Result<Vec<OrderIntent>, Box<dyn Error>> {
+        self.store.set_fuel(FUEL_PER_CALL)?;
+
+        let (ptr, len) = self.write(message)?;
+        let on_bar = self
+            .instance
+            .get_typed_func::<(i32, i32), i64>(&mut self.store, "on_bar")?;
+        let packed = on_bar.call(&mut self.store, (ptr, len))?;
+
+        Ok(self.read(packed)?.unwrap_or_default())
+    }
+}
+
+#[derive(Clone)]
+pub struct WasmStrategy {
+    id: String,
+    metadata: Arc<PluginMetadata>,
+    module: Module,
+    // The module's bytes, a rebuilt plugin under the same name starts a new study
+    definition_hash: u64,
+    composition: Arc<dyn IComposition>,
+    overrides: StrategyOverrides,
+}
+
+impl IStrategy for WasmStrategy {
+    fn id(&self) -> &str {
+        return &self.id;
+    }
+
+    fn name(&self) -> &str {
+        return &self.metadata.name;
+    }
+
+    fn description(&self) -> &str {
+        return &self.metadata.description;
+    }
+
+    fn definition_hash(&self) -> Option<u64> {
+        return Some(self.definition_hash);
+    }
+
+    fn optimize(&self) -> Option<Vec<OptimizedBacktestResult>> {
+        if self.metadata.parameters.is_empty() {
+            return None;
+        }
+
+        let optimization_space = OptimizationSpace::new(self.optimization_ranges());
+
+        let strategy: Box<dyn IStrategy> = Box::new(self.clone());
+
+        let optimization_results = self
+            .optimizer(Box::new(RandomSearchOptimizer::new(48, 42)))
+            .optimize(&strategy, &optimization_space)
+            .unwrap_or(Vec::new());
+
+        Some(optimization_results)
+    }
+
+    fn backtest(
+        &self,
+        optimization_map: Option<&HashMap<String, CompositionDataType>>,
+    ) -> Result<BacktestResult, Box<dyn Error>> {
+        let mut backtest_manager = BacktestManager::new(super::BacktestOptions {
+            initial_capital: 1_000.0,
+            fees: 0.001,
+        });
+
+        let composition = self.composition();
+        let composition_data = self.composed_data();
+
+        let parameters = self.parameters_with_defaults(optimization_map);
+
+        let composition_fields = composition.composition_fields();
+        let mut fields: Vec<&str> = composition_fields.keys().copied().collect();
+        fields.sort_by_key(|field| composition_fields[field]);
+
+        let timestamp_position = composition.get_composition_field_position("timestamp");
+        let price_position = *composition_fields
+            .get(self.metadata.price_field.as_str())
+            .ok_or_else(|| {
+                format!(
+                    "{} has no field {}",
+                    composition.name(),
+                    self.metadata.price_field
+                )
+            })?;
+
+        let mut plugin = PluginInstance::new(&self.module)?;
+        plugin.start(&StartMessage {
+            fields: &fields,
+            parameters: &parameters,
+        })?;
+
+        let mut open_trades: Vec<Trade> = vec![];
+
+        for composition_point in &composition_data {
+            if backtest_manager.backtest_ended {
+                break;
+            }
+
+            let timestamp =
+                CompositionDataType::extract_i64(&composition_point[timestamp_position]);
+            let price = match numeric_value(&composition_point[price_position]) {
+                Some(price) => price,
+                None => continue,
+            };
+
+            backtest_manager.update_price(composition.name(), timestamp, price);
+
+            let intents = plugin.on_bar(&BarMessage {
+                values: fields
+                    .iter()
+                    .map(|field| json_value(&composition_point[composition_fields[field]]))
+                    .collect(),
+                available_capital: backtest_manager.available_capital(),
+                portfolio_value: backtest_manager.current_portfolio_value(),
+                open_trades: open_trades
+                    .iter()
+                    .map(|trade| OpenTradeMessage {
+                        side: trade.side(),
+                        open_price: trade.open_price(),
+                        capital_allocation: trade.capital_allocation(),
+                        leverage: trade.leverage(),
+                    })
+                    .collect(),
+            })?;
+
+            for intent in intents {
+                match intent {
+                    OrderIntent::Open {
+                        side,
+                        capital_ratio,
+                        leverage,
+                    } => {
+                        if !capital_ratio.is_finite()
+                            || leverage.map_or(false, |leverage| !leverage.is_finite())
+                        {
+                            return Err(format!(
+                                "{} opened a trade with a non-finite size",
+                                self.name()
+                            )
+                            .into());
+                        }
+
+                        let trade_allocation =
+                            backtest_manager.available_capital() * capital_ratio.clamp(0.0, 1.0);
+                        let mut new_trade = Trade::new(TradeOptions {
+                            asset_name: composition.name(),
+                            side,
+                            capital_allocation: Some(trade_allocation),
+                            leverage: Some(leverage.unwrap_or(1.0)),
+                        });
+                        backtest_manager.open_trade(&mut new_trade);
+                        open_trades.push(new_trade);
+                    }
+                    OrderIntent::Close { trade } => {
+                        if trade < open_trades.len() {
+                            let mut trade = open_trades.remove(trade);
+                            backtest_manager.close_trade(&mut trade);
+                        }
+                    }
+                    OrderIntent::CloseAll => {
+                        for mut trade in open_trades.drain(..) {
+                            backtest_manager.close_trade(&mut trade);
+                        }
+                    }
+                }
+            }
+        }
+
+        let backtest_result = backtest_manager.backtest_end();
+        Ok(backtest_result)
+    }
+
+    fn composition(&self) -> &dyn IComposition {
+        return self.composition.as_ref();
+    }
+
+    fn parameter_schema(&self) -> Vec<ParameterSchema> {
+        return self.metadata.parameters.clone();
+    }
+
+    fn overrides(&self) -> Option<&StrategyOverrides> {
+        return Some(&self.overrides);
+    }
+
+    fn with_overrides(&self, overrides: StrategyOverrides) -> Option<Box<dyn IStrategy>> {
+        let mut strategy = self.clone();
+        strategy.overrides = overrides;
+
+        return Some(Box::new(strategy));
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl WasmStrategy {
+    /// Compiles the plugin and reads its metadata from a first instantiation.
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let bytes = fs::read(path)?;
+        let module = Module::new(&WASM_ENGINE, &bytes)?;
+        let metadata = PluginInstance::new(&module)?.metadata()?;
+
+        let composition: Arc<dyn IComposition> = compositions()
+            .into_iter()
+            .find(|composition| composition.name() == metadata.composition)
+            .ok_or_else(|| format!("Unknown composition {}", metadata.composition))?;
+
+        let mut strategy = Self {
+            id: Uuid::new_v4().into(),
+            metadata: Arc::new(metadata),
+            module,
+            definition_hash: fnv1a(bytes.into_iter()),
+            composition,
+            overrides: StrategyOverrides::default(),
+        };
+
+        strategy.overrides.composition_data = Some(strategy.composition().compose()?);
+
+        return Ok(strategy);
+    }
+}
+
+/// Every strategy plugin of the app data dir, plugins that don't compile, miss part of
+/// the ABI or name an unknown composition are reported and skipped.
+pub fn load_wasm_strategies() -> Vec<Box<dyn IStrategy>> {
+    let mut strategies: Vec<Box<dyn IStrategy>> = vec![];
+
+    let directory = match join_app_data_dir(WASM_PLUGINS_DIRECTORY) {
+        Ok(directory) => directory,
+        Err(_) => return strategies,
+    };
+
+    let mut paths: Vec<_> = match fs::read_dir(&directory) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension().and_then(|extension| extension.to_str()) == Some("wasm")
+            })
+            .collect(),
+        Err(_) => return strategies,
+    };
+    paths.sort();
+
+    for path in paths {
+        match WasmStrategy::from_file(&path) {
+            Ok(strategy) => strategies.push(Box::new(strategy)),
+            Err(error) => eprintln!("Couldn't load plugin {}: {}", path.display(), error),
+        }
+    }
+
+    strategies
+}
+
+fn json_value(value: &CompositionDataType) -> serde_json::Value {
+    match value {
+        CompositionDataType::String(value) => serde_json::Value::from(value.clone()),
+        CompositionDataType::Bool(value) => serde_json::Value::from(*value),
+        CompositionDataType::I64(value) => serde_json::Value::from(*value),
+        value => numeric_value(value).map_or(serde_json::Value::Null, serde_json::Value::from),
+    }
+}
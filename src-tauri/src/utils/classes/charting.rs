@@ -10,6 +10,7 @@ pub enum ChartingData {
     BarChartingData(BarChartingData),
     HistogramChartingData(HistogramChartingData),
     AreaChartingData(AreaChartingData),
+    HeatmapChartingData(HeatmapChartingData),
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -51,7 +52,24 @@ pub struct LineData {
 }
 
 #[derive(Serialize, Deserialize, Clone)]
-pub struct BarChartingData {}
+pub struct BarChartingData {
+    pub chart_type: String,
+    pub height: Option<i16>,
+    pub data: Vec<Option<BarData>>,
+    pub pane: Option<i8>,
+    pub title: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct BarData {
+    pub time: i64,
+    pub open: f32,
+    pub high: f32,
+    pub low: f32,
+    pub close: f32,
+    pub color: Option<String>,
+}
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct HistogramChartingData {
@@ -72,6 +90,20 @@ pub struct HistogramData {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct AreaChartingData {}
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HeatmapChartingData {
+    pub chart_type: String,
+    pub height: Option<i16>,
+    pub pane: Option<i8>,
+    pub title: Option<String>,
+    pub x_label: String,
+    pub y_label: String,
+    pub x_values: Vec<f32>,
+    pub y_values: Vec<f32>,
+    // One row per y value, one cell per x value, `None` where nothing was evaluated
+    pub data: Vec<Vec<Option<f32>>>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct DataBlock {}
 
@@ -0,0 +1,134 @@
+// Dense helpers for the small symmetric systems optimizers build (GP kernels, covariances),
+// computed in f64 since those matrices are often close to singular.
+
+/// Lower triangular `L` such that `matrix = L * L^T`, `None` if not positive definite.
+pub fn cholesky(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut lower = vec![vec![0.0; n]; n];
+
+    for i in 0..n {
+        for j in 0..=i {
+            let mut sum = matrix[i][j];
+
+            for k in 0..j {
+                sum -= lower[i][k] * lower[j][k];
+            }
+
+            if i == j {
+                if sum <= 0.0 {
+                    return None;
+                }
+                lower[i][j] = sum.sqrt();
+            } else {
+                lower[i][j] = sum / lower[j][j];
+            }
+        }
+    }
+
+    Some(lower)
+}
+
+/// Solves `L * x = b` for a lower triangular `L`.
+pub fn forward_substitution(lower: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let n = lower.len();
+    let mut x = vec![0.0; n];
+
+    for i in 0..n {
+        let mut sum = b[i];
+
+        for k in 0..i {
+            sum -= lower[i][k] * x[k];
+        }
+
+        x[i] = sum / lower[i][i];
+    }
+
+    x
+}
+
+/// Solves `L^T * x = b` for a lower triangular `L`.
+pub fn backward_substitution(lower: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let n = lower.len();
+    let mut x = vec![0.0; n];
+
+    for i in (0..n).rev() {
+        let mut sum = b[i];
+
+        for k in (i + 1)..n {
+            sum -= lower[k][i] * x[k];
+        }
+
+        x[i] = sum / lower[i][i];
+    }
+
+    x
+}
+
+/// Solves `matrix * x = b` given the Cholesky factor of `matrix`.
+pub fn cholesky_solve(lower: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let y = forward_substitution(lower, b);
+    backward_substitution(lower, &y)
+}
+
+/// Eigenvalues and eigenvectors (as columns) of a symmetric matrix by cyclic Jacobi
+/// rotations, accurate enough for the handful of dimensions optimizers search.
+pub fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut vectors = vec![vec![0.0; n]; n];
+
+    for i in 0..n {
+        vectors[i][i] = 1.0;
+    }
+
+    for _ in 0..100 {
+        let mut off_diagonal = 0.0;
+
+        for i in 0..n {
+            for j in (i + 1)..n {
+                off_diagonal += a[i][j] * a[i][j];
+            }
+        }
+
+        if off_diagonal < 1e-20 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in (p + 1)..n {
+                if a[p][q].abs() < 1e-30 {
+                    continue;
+                }
+
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for k in 0..n {
+                    let a_kp = a[k][p];
+                    let a_kq = a[k][q];
+                    a[k][p] = c * a_kp - s * a_kq;
+                    a[k][q] = s * a_kp + c * a_kq;
+                }
+
+                for k in 0..n {
+                    let a_pk = a[p][k];
+                    let a_qk = a[q][k];
+                    a[p][k] = c * a_pk - s * a_qk;
+                    a[q][k] = s * a_pk + c * a_qk;
+                }
+
+                for k in 0..n {
+                    let v_kp = vectors[k][p];
+                    let v_kq = vectors[k][q];
+                    vectors[k][p] = c * v_kp - s * v_kq;
+                    vectors[k][q] = s * v_kp + c * v_kq;
+                }
+            }
+        }
+    }
+
+    ((0..n).map(|i| a[i][i]).collect(), vectors)
+}
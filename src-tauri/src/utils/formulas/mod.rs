@@ -1 +1,3 @@
-pub mod processing;
\ No newline at end of file
+pub mod processing;
+pub mod statistics;
+pub mod linear_algebra;
\ No newline at end of file
@@ -0,0 +1,89 @@
+const EULER_MASCHERONI: f32 = 0.577_215_7;
+
+pub fn normal_pdf(x: f32) -> f32 {
+    (-0.5 * x * x).exp() / (2.0 * std::f32::consts::PI).sqrt()
+}
+
+// Abramowitz & Stegun 7.1.26 approximation of erf, max error ~1.5e-7
+pub fn normal_cdf(x: f32) -> f32 {
+    let z = x.abs() / std::f32::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * z);
+    let polynomial = t
+        * (0.254_829_6
+            + t * (-0.284_496_74 + t * (1.421_413_7 + t * (-1.453_152_1 + t * 1.061_405_4))));
+    let erf = 1.0 - polynomial * (-z * z).exp();
+
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+// Acklam's rational approximation of the inverse normal CDF
+pub fn normal_ppf(p: f32) -> f32 {
+    if p <= 0.0 {
+        return f32::NEG_INFINITY;
+    }
+
+    if p >= 1.0 {
+        return f32::INFINITY;
+    }
+
+    let a = [
+        -3.969_683e1,
+        2.209_46e2,
+        -2.759_285_1e2,
+        1.383_577_5e2,
+        -3.066_48e1,
+        2.506_628_3,
+    ];
+    let b = [
+        -5.447_609_9e1,
+        1.615_858_4e2,
+        -1.556_989_8e2,
+        6.680_131_2e1,
+        -1.328_068_2e1,
+    ];
+    let c = [
+        -7.784_894e-3,
+        -3.223_964_6e-1,
+        -2.400_758_3,
+        -2.549_732_5,
+        4.374_664_1,
+        2.938_163_9,
+    ];
+    let d = [7.784_696e-3, 3.224_671_3e-1, 2.445_134_2, 3.754_408_7];
+
+    let p_low = 0.024_25;
+    let p_high = 1.0 - p_low;
+
+    if p < p_low {
+        let q = (-2.0 * p.ln()).sqrt();
+        return (((((c[0] * q + c[1]) * q + c[2]) * q + c[3]) * q + c[4]) * q + c[5])
+            / ((((d[0] * q + d[1]) * q + d[2]) * q + d[3]) * q + 1.0);
+    }
+
+    if p > p_high {
+        let q = (-2.0 * (1.0 - p).ln()).sqrt();
+        return -(((((c[0] * q + c[1]) * q + c[2]) * q + c[3]) * q + c[4]) * q + c[5])
+            / ((((d[0] * q + d[1]) * q + d[2]) * q + d[3]) * q + 1.0);
+    }
+
+    let q = p - 0.5;
+    let r = q * q;
+    (((((a[0] * r + a[1]) * r + a[2]) * r + a[3]) * r + a[4]) * r + a[5]) * q
+        / (((((b[0] * r + b[1]) * r + b[2]) * r + b[3]) * r + b[4]) * r + 1.0)
+}
+
+/// Expected maximum of `trials` independent standard normal draws (False Strategy Theorem).
+pub fn expected_maximum_standard_normal(trials: usize) -> f32 {
+    if trials <= 1 {
+        return 0.0;
+    }
+
+    let n = trials as f32;
+
+    (1.0 - EULER_MASCHERONI) * normal_ppf(1.0 - 1.0 / n)
+        + EULER_MASCHERONI * normal_ppf(1.0 - 1.0 / (n * std::f32::consts::E))
+}
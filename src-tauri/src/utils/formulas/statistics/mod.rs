@@ -0,0 +1 @@
+pub mod distribution;
\ No newline at end of file
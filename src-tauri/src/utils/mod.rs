@@ -2,4 +2,5 @@ pub mod classes;
 pub mod date;
 pub mod paths;
 pub mod load_mmap;
-pub mod formulas;
\ No newline at end of file
+pub mod formulas;
+pub mod random;
\ No newline at end of file
@@ -0,0 +1,73 @@
+/// Small seeded xoshiro256** generator, optimizers rely on it for reproducible runs.
+#[derive(Clone, Debug)]
+pub struct SeededRng {
+    state: [u64; 4],
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        let mut splitmix_state = seed;
+        let mut state = [0u64; 4];
+
+        for value in state.iter_mut() {
+            splitmix_state = splitmix_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = splitmix_state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            *value = z ^ (z >> 31);
+        }
+
+        return Self { state };
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        result
+    }
+
+    /// Uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range_f32(&mut self, start: f32, end: f32) -> f32 {
+        start + (end - start) * self.next_f32()
+    }
+
+    /// Uniform in [start, end)
+    pub fn range_usize(&mut self, start: usize, end: usize) -> usize {
+        if end <= start {
+            return start;
+        }
+
+        start + (self.next_u64() % (end - start) as u64) as usize
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    /// Standard normal draw (Box-Muller)
+    pub fn normal(&mut self) -> f32 {
+        let u1 = self.next_f32().max(f32::MIN_POSITIVE);
+        let u2 = self.next_f32();
+
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+    }
+
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            let j = self.range_usize(0, i + 1);
+            values.swap(i, j);
+        }
+    }
+}
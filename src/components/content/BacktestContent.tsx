@@ -11,18 +11,56 @@ import {
 import {
   BacktestDataResponse,
   BacktestResultDataResponse,
+  BacktestStrategyParams,
+  CancelOptimizationParams,
   ChartingSeries,
   Metric,
+  ObjectiveDirection,
+  ObjectiveSelection,
+  OptimizationParameter,
+  OptimizationParameterPair,
+  OptimizationProgressResponse,
+  OptimizerKind,
+  ParameterSchema,
 } from "@/types";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import BaseChart from "../charting/BaseChart";
 
 enum GraphType {
   FixedEquity = "FixedEquity",
   TradePercentage = "TradePercentage",
   PortfolioPercentage = "PortfolioPercentage",
+  RollingMetrics = "RollingMetrics",
 }
 
+const parameterName = (range: OptimizationParameter) =>
+  "Numeric" in range
+    ? range.Numeric.name
+    : "Boolean" in range
+    ? range.Boolean.name
+    : range.Categoric.name;
+
+// Keeps the variant of the schema default, e.g. { Usize: 20 } edited to { Usize: 30 }
+const editedValue = (
+  defaultValue: Record<string, unknown>,
+  input: string | boolean
+): Record<string, unknown> => {
+  const variant = Object.keys(defaultValue)[0];
+
+  switch (variant) {
+    case "Bool":
+      return { Bool: input };
+    case "String":
+      return { String: input };
+    case "Usize":
+    case "I64":
+      return { [variant]: Math.round(Number(input)) };
+    default:
+      return { [variant]: Number(input) };
+  }
+};
+
 export default function BacktestContent() {
   const { selectedItem } = useSidebarState();
 
@@ -35,23 +73,141 @@ export default function BacktestContent() {
     useState<BacktestDataResponse>();
   const [displayHighestSharpeOnly, setDisplayHighestSharpeOnly] =
     useState(true);
+  const [parameterSchema, setParameterSchema] = useState<ParameterSchema[]>(
+    []
+  );
+  const [parameters, setParameters] = useState<OptimizationParameterPair[]>(
+    []
+  );
+  const [optimizationRanges, setOptimizationRanges] = useState<
+    OptimizationParameter[]
+  >([]);
+  const [optimizer, setOptimizer] = useState<OptimizerKind>();
+  const [objectives, setObjectives] = useState<ObjectiveSelection[]>([]);
+  const [displayParetoFrontOnly, setDisplayParetoFrontOnly] = useState(false);
+  const [optimizationProgress, setOptimizationProgress] =
+    useState<OptimizationProgressResponse>();
 
+  // Progress of the selected strategy's optimization, its run id is what cancels it
   useEffect(() => {
-    const getBacktestData = async () => {
+    let progressUnlisten: (() => void) | null = null;
+
+    const listenProgress = async () => {
+      progressUnlisten = await listen<OptimizationProgressResponse>(
+        "optimization_progress",
+        (event) => {
+          if (event.payload.strategy_id != selectedItem?.id) return;
+
+          setOptimizationProgress(
+            event.payload.progress.finished ? undefined : event.payload
+          );
+        }
+      );
+    };
+
+    listenProgress();
+
+    return () => {
+      if (progressUnlisten) {
+        progressUnlisten();
+      }
+      setOptimizationProgress(undefined);
+    };
+  }, [selectedItem]);
+
+  const cancelOptimization = useCallback(async () => {
+    if (!optimizationProgress) return;
+
+    const params: CancelOptimizationParams = {
+      run_id: optimizationProgress.run_id,
+    };
+
+    await invoke<boolean>("cancel_optimization", { params });
+  }, [optimizationProgress]);
+
+  const runBacktest = useCallback(
+    async (params: Omit<BacktestStrategyParams, "id">) => {
       const backtestStrategyData = await invoke<BacktestDataResponse>(
         "backtest_strategy",
         {
           params: {
             id: selectedItem?.id,
+            ...params,
           },
         }
       );
 
       setBacktestStrategy(backtestStrategyData);
+    },
+    [selectedItem]
+  );
+
+  useEffect(() => {
+    const getParameterSchema = async () => {
+      const schema = await invoke<ParameterSchema[]>(
+        "get_strategy_parameters",
+        {
+          params: {
+            id: selectedItem?.id,
+          },
+        }
+      );
+
+      setParameterSchema(schema);
+      setParameters(
+        schema.map((parameter) => ({
+          key: parameterName(parameter.range),
+          value: parameter.default,
+        }))
+      );
+      setOptimizationRanges(schema.map((parameter) => parameter.range));
     };
 
-    getBacktestData();
-  }, [selectedItem]);
+    getParameterSchema();
+    runBacktest({});
+  }, [selectedItem, runBacktest]);
+
+  const setParameter = (index: number, input: string | boolean) => {
+    setParameters(
+      parameters.map((parameter, parameterIndex) =>
+        parameterIndex == index
+          ? {
+              key: parameter.key,
+              value: editedValue(parameterSchema[index].default, input),
+            }
+          : parameter
+      )
+    );
+  };
+
+  // Metrics the backtests report, plus the trade count objectives can also rank on
+  const objectiveMetrics = [
+    ...(backtestStrategy?.backtests[0]?.metrics.map((metric) => metric.key) ??
+      []),
+    "TradeCount",
+  ];
+
+  const setObjective = (index: number, objective: ObjectiveSelection) => {
+    setObjectives(
+      objectives.map((current, objectiveIndex) =>
+        objectiveIndex == index ? objective : current
+      )
+    );
+  };
+
+  const setNumericRange = (
+    index: number,
+    field: "start" | "end" | "step",
+    input: string
+  ) => {
+    setOptimizationRanges(
+      optimizationRanges.map((range, rangeIndex) =>
+        rangeIndex == index && "Numeric" in range
+          ? { Numeric: { ...range.Numeric, [field]: Number(input) } }
+          : range
+      )
+    );
+  };
 
   useEffect(() => {
     if (!backtestStrategy) return;
@@ -60,7 +216,14 @@ export default function BacktestContent() {
 
     let highestSharpeBacktest: BacktestResultDataResponse | null = null;
 
-    for (const backtest of backtestStrategy.backtests) {
+    // Non-dominated backtests only, the highest Sharpe is then picked among them
+    const displayedBacktests = displayParetoFrontOnly
+      ? backtestStrategy.backtests.filter((backtest) =>
+          backtestStrategy.pareto_front.includes(backtest.id)
+        )
+      : backtestStrategy.backtests;
+
+    for (const backtest of displayedBacktests) {
       if (displayHighestSharpeOnly) {
         if (!highestSharpeBacktest) highestSharpeBacktest = backtest;
 
@@ -90,6 +253,9 @@ export default function BacktestContent() {
         case GraphType.TradePercentage:
           chartSeries = backtest.percentage_growth_charting_data;
           break;
+        case GraphType.RollingMetrics:
+          chartSeries = backtest.rolling_metrics_charting_data;
+          break;
       }
 
       if (chartSeries) {
@@ -104,7 +270,12 @@ export default function BacktestContent() {
     }
 
     setChartingData(backtestsChartingData);
-  }, [backtestStrategy, graphType, displayHighestSharpeOnly]);
+  }, [
+    backtestStrategy,
+    graphType,
+    displayHighestSharpeOnly,
+    displayParetoFrontOnly,
+  ]);
 
   const setHighestSharpeDisplayOnly = useCallback(
     (e: React.ChangeEvent<HTMLInputElement>) => {
@@ -129,6 +300,19 @@ export default function BacktestContent() {
                   />
                   Highest Sharpe
                 </label>
+                {backtestStrategy?.pareto_front.length ? (
+                  <label className="label">
+                    <input
+                      onChange={(e) =>
+                        setDisplayParetoFrontOnly(e.currentTarget.checked)
+                      }
+                      type="checkbox"
+                      checked={displayParetoFrontOnly}
+                      className="checkbox"
+                    />
+                    Pareto front ({backtestStrategy.pareto_front.length})
+                  </label>
+                ) : null}
               </fieldset>
             </>
           ) : null}
@@ -144,12 +328,230 @@ export default function BacktestContent() {
             })}
           </select>
         </div>
+        {optimizationProgress ? (
+          <div className="w-full flex gap-2 items-center justify-center px-4">
+            <progress
+              className="progress progress-primary w-full"
+              value={optimizationProgress.progress.completed}
+              max={Math.max(optimizationProgress.progress.total, 1)}
+            ></progress>
+            <kbd className="kbd p-1">
+              {optimizationProgress.progress.completed}/
+              {optimizationProgress.progress.total}
+            </kbd>
+            <button
+              className="btn btn-xs"
+              disabled={optimizationProgress.progress.cancelled}
+              onClick={cancelOptimization}
+            >
+              Cancel
+            </button>
+          </div>
+        ) : null}
         <div className={`p-4 w-full h-full`}>
           {chartingData !== undefined ? (
             <BaseChart chartingData={chartingData} />
           ) : null}
         </div>
       </div>
+      {parameterSchema.length > 0 ? (
+        <div className="p-4 flex flex-col">
+          <span className="font-semibold text-2xl">Parameters</span>
+          <div className="p-4">
+            <div className="overflow-x-auto">
+              <table className="table table-xs">
+                <thead>
+                  <tr>
+                    <th>Parameter</th>
+                    <th>Value</th>
+                    <th>Start</th>
+                    <th>End</th>
+                    <th>Step</th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {parameters.map((parameter, parameterIndex) => {
+                    const range = optimizationRanges[parameterIndex];
+                    const value = Object.values(parameter.value)[0];
+
+                    return (
+                      <tr key={parameter.key}>
+                        <td>{parameter.key}</td>
+                        <td>
+                          {typeof value == "boolean" ? (
+                            <input
+                              type="checkbox"
+                              className="checkbox checkbox-xs"
+                              checked={value}
+                              onChange={(e) =>
+                                setParameter(
+                                  parameterIndex,
+                                  e.currentTarget.checked
+                                )
+                              }
+                            />
+                          ) : (
+                            <input
+                              className="input input-xs"
+                              value={String(value)}
+                              onChange={(e) =>
+                                setParameter(
+                                  parameterIndex,
+                                  e.currentTarget.value
+                                )
+                              }
+                            />
+                          )}
+                        </td>
+                        {range && "Numeric" in range ? (
+                          (["start", "end", "step"] as const).map((field) => (
+                            <td key={field}>
+                              <input
+                                type="number"
+                                className="input input-xs"
+                                value={range.Numeric[field]}
+                                onChange={(e) =>
+                                  setNumericRange(
+                                    parameterIndex,
+                                    field,
+                                    e.currentTarget.value
+                                  )
+                                }
+                              />
+                            </td>
+                          ))
+                        ) : (
+                          <td colSpan={3}></td>
+                        )}
+                      </tr>
+                    );
+                  })}
+                </tbody>
+              </table>
+            </div>
+            <div className="overflow-x-auto pt-4">
+              <table className="table table-xs">
+                <thead>
+                  <tr>
+                    <th>Objective (two or more rank by Pareto front)</th>
+                    <th>Direction</th>
+                    <th></th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {objectives.map((objective, objectiveIndex) => (
+                    <tr key={objectiveIndex}>
+                      <td>
+                        <select
+                          className="select select-xs"
+                          value={objective.metric}
+                          onChange={(e) =>
+                            setObjective(objectiveIndex, {
+                              ...objective,
+                              metric: e.currentTarget.value,
+                            })
+                          }
+                        >
+                          {objectiveMetrics.map((metric) => (
+                            <option key={metric} value={metric}>
+                              {metric}
+                            </option>
+                          ))}
+                        </select>
+                      </td>
+                      <td>
+                        <select
+                          className="select select-xs"
+                          value={objective.direction}
+                          onChange={(e) =>
+                            setObjective(objectiveIndex, {
+                              ...objective,
+                              direction: e.currentTarget
+                                .value as ObjectiveDirection,
+                            })
+                          }
+                        >
+                          {Object.values(ObjectiveDirection).map(
+                            (direction) => (
+                              <option key={direction} value={direction}>
+                                {direction}
+                              </option>
+                            )
+                          )}
+                        </select>
+                      </td>
+                      <td>
+                        <button
+                          className="btn btn-xs"
+                          onClick={() =>
+                            setObjectives(
+                              objectives.filter(
+                                (_, index) => index != objectiveIndex
+                              )
+                            )
+                          }
+                        >
+                          Remove
+                        </button>
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+              <button
+                className="btn btn-xs mt-2"
+                onClick={() =>
+                  setObjectives([
+                    ...objectives,
+                    {
+                      metric: objectiveMetrics[0],
+                      direction: ObjectiveDirection.Maximize,
+                    },
+                  ])
+                }
+              >
+                Add objective
+              </button>
+            </div>
+            <div className="flex gap-2 pt-4">
+              <button
+                className="btn btn-xs"
+                onClick={() => runBacktest({ parameters })}
+              >
+                Run configuration
+              </button>
+              <button
+                className="btn btn-xs"
+                onClick={() =>
+                  runBacktest({
+                    optimization_ranges: optimizationRanges,
+                    optimizer,
+                    objectives: objectives.length > 1 ? objectives : undefined,
+                  })
+                }
+              >
+                Optimize range
+              </button>
+              <select
+                onChange={(e) => {
+                  setOptimizer(
+                    e.currentTarget.value
+                      ? (e.currentTarget.value as OptimizerKind)
+                      : undefined
+                  );
+                }}
+                value={optimizer ?? ""}
+                className="select select-xs"
+              >
+                <option value="">Strategy optimizer</option>
+                {Object.values(OptimizerKind).map((kind) => {
+                  return <option value={kind}>{kind}</option>;
+                })}
+              </select>
+            </div>
+          </div>
+        </div>
+      ) : null}
       <div className="w-full h-[200px]">
         <div className="p-4 flex flex-col">
           <span className="font-semibold text-2xl">Metrics</span>
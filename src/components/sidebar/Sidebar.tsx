@@ -6,9 +6,11 @@ import {
   SelectedItemType,
   CompositionMetadata,
   StrategyMetadata,
+  ScriptReloadReport,
 } from "../../types";
 import { useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useSidebarState } from "@/lib/state/sidebar";
 
 //TODO: tooltip when clicking on a selecteable item: properties, delete, etc...
@@ -38,6 +40,27 @@ export default function Sidebar() {
     getStaticResources();
   }, []);
 
+  // Scripts define strategies and compositions, edits to them are reloaded live
+  useEffect(() => {
+    const unlisten = listen<ScriptReloadReport>(
+      "scripts_reloaded",
+      async () => {
+        const compositions = await invoke<CompositionMetadata[]>(
+          "get_compositions"
+        );
+
+        const strategies = await invoke<StrategyMetadata[]>("get_strategies");
+
+        sidebarState.setCompositionMetadatas(compositions);
+        sidebarState.setStrategiesMetadatas(strategies);
+      }
+    );
+
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
   return (
     <>
       <div className={`flex flex-col h-full`}>
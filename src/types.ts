@@ -137,14 +137,188 @@ export interface BacktestResultDataResponse {
   equity_growth_charting_data: ChartingSeries[];
   portfolio_growth_charting_data: ChartingSeries[];
   percentage_growth_charting_data: ChartingSeries[];
+  rolling_metrics_charting_data: ChartingSeries[];
   data_blocks: DataBlock[];
   metrics: Metric[];
+  parameters: OptimizationParameterPair[];
+  probabilistic_sharpe_ratio?: number;
+  deflated_sharpe_ratio?: number;
+  objectives: ObjectivePair[];
+  pareto_rank?: number;
+  score?: number;
+  robustness_score?: number;
+}
+
+export interface ObjectivePair {
+  key: string;
+  value?: number;
+}
+
+export interface OptimizationParameterPair {
+  key: string;
+  value: Record<string, unknown>;
 }
 
 export interface BacktestDataResponse {
   name: string;
   description: string;
   backtests: BacktestResultDataResponse[];
+  pareto_front: string[];
+  robustness_heatmaps_charting_data: HeatmapSeries[];
+  score_ranking: string[];
+  robustness_ranking: string[];
+  objective?: ObjectiveDefinition;
+  convergence_charting_data: ChartingSeries[];
+  overfitting?: BacktestOverfittingResponse;
+  cancelled: boolean;
+  run_id?: string;
+  parameter_importance: Metric[];
+  importance_charting_data: ChartingSeries[];
+  marginal_charting_data: ChartingSeries[];
+}
+
+export interface OptimizationProgress {
+  completed: number;
+  total: number;
+  best_score?: number;
+  elapsed_seconds: number;
+  eta_seconds?: number;
+  cancelled: boolean;
+  finished: boolean;
+}
+
+export interface OptimizationProgressResponse {
+  run_id: string;
+  strategy_id: string;
+  progress: OptimizationProgress;
+}
+
+export interface CancelOptimizationParams {
+  run_id: string;
+}
+
+export interface BacktestOverfittingResponse {
+  probability_of_overfitting: number;
+  logits: number[];
+  in_sample_performances: number[];
+  out_of_sample_performances: number[];
+  degradation_slope?: number;
+  degradation_intercept?: number;
+  probability_of_loss: number;
+}
+
+export type OptimizationParameter =
+  | {
+      Numeric: {
+        name: string;
+        start: number;
+        end: number;
+        step: number;
+        kind: "Integer" | "Float";
+        log_scale?: boolean;
+      };
+    }
+  | { Boolean: { name: string } }
+  | { Categoric: { name: string; categories: string[] } };
+
+export interface ParameterSchema {
+  default: Record<string, unknown>;
+  range: OptimizationParameter;
+}
+
+export interface BacktestStrategyParams {
+  id?: string;
+  objective?: ObjectiveDefinition;
+  parameters?: OptimizationParameterPair[];
+  optimization_ranges?: OptimizationParameter[];
+  optimizer?: OptimizerKind;
+  objectives?: ObjectiveSelection[];
+}
+
+export enum ObjectiveDirection {
+  Maximize = "Maximize",
+  Minimize = "Minimize",
+}
+
+export interface ObjectiveSelection {
+  metric: string;
+  direction: ObjectiveDirection;
+}
+
+export enum OptimizerKind {
+  Grid = "Grid",
+  RandomSearch = "RandomSearch",
+  SimulatedAnnealing = "SimulatedAnnealing",
+  ParticleSwarm = "ParticleSwarm",
+  CmaEs = "CmaEs",
+  Bayesian = "Bayesian",
+  Genetic = "Genetic",
+  LatinHypercube = "LatinHypercube",
+  SuccessiveHalving = "SuccessiveHalving",
+  Hyperband = "Hyperband",
+}
+
+export interface ObjectiveDefinition {
+  expression: string;
+  min_trades?: number;
+}
+
+export interface HeatmapSeries {
+  chart_type: "heatmap";
+  height?: number;
+  pane?: number;
+  title?: string;
+  x_label: string;
+  y_label: string;
+  x_values: number[];
+  y_values: number[];
+  data: (number | null)[][];
+}
+
+export interface StudyRecordResponse {
+  parameters: OptimizationParameterPair[];
+  score?: number;
+  metrics: Metric[];
+}
+
+export interface RowRange {
+  start: number;
+  end: number;
+}
+
+export interface CrossValidationFoldResponse {
+  train: RowRange[];
+  test: RowRange[];
+  parameters: OptimizationParameterPair[];
+  in_sample: BacktestResultDataResponse[];
+  out_of_sample: BacktestResultDataResponse[];
+}
+
+export interface CrossValidationPathResponse {
+  total_return: number;
+  sharpe_ratio?: number;
+}
+
+export interface CrossValidationResponse {
+  folds: CrossValidationFoldResponse[];
+  paths: CrossValidationPathResponse[];
+  in_sample_mean_score?: number;
+  out_of_sample_mean_score?: number;
+  out_of_sample_score_deviation: number;
+  score_efficiency?: number;
+  out_of_sample_mean_metrics: Metric[];
+  cancelled: boolean;
+  run_id: string;
+}
+
+export interface ScriptError {
+  path: string;
+  message: string;
+}
+
+export interface ScriptReloadReport {
+  reloaded: string[];
+  errors: ScriptError[];
 }
 
 export interface SidebarData {